@@ -1,7 +1,7 @@
-use foreign::ForeignType;
+use foreign::{ForeignId, ForeignType};
 use jsonwrap::{Foreign, ForeignOpt};
 use poem::{Route, Server, listener::TcpListener};
-use poem_openapi::{OpenApi, OpenApiService, payload::Json};
+use poem_openapi::{OpenApi, OpenApiService, param::Query, payload::Json};
 
 struct Api;
 
@@ -14,18 +14,30 @@ impl Api {
         }))
     }
 
-    // this doesn't work well because the response isnt marked as nullable
+    #[oai(path = "/echo", method = "post")]
+    async fn echo(&self, body: Json<Foreign<ForeignType>>) -> Json<Foreign<ForeignType>> {
+        body
+    }
+
+    #[oai(path = "/lookup", method = "get")]
+    async fn lookup(&self, id: Query<Foreign<ForeignId>>) -> Json<Foreign<ForeignType>> {
+        Json(Foreign(ForeignType {
+            text: format!("item {}", id.0.0.0),
+        }))
+    }
+
+    // `Json<Option<Foreign<_>>>` would not be marked as nullable, as poem's `Option` reuses
+    // the inner schema as is; `Foreign<Option<_>>` is.
     #[oai(path = "/optional", method = "get")]
-    async fn optional(&self) -> Json<Option<Foreign<ForeignType>>> {
-        Json(Some(Foreign(ForeignType {
+    async fn optional(&self) -> Json<Foreign<Option<ForeignType>>> {
+        Json(Foreign(Some(ForeignType {
             text: "optional value".to_string(),
         })))
     }
 
-    // this doesn't work well because the response isnt marked as nullable
     #[oai(path = "/optional-none", method = "get")]
-    async fn optional_none(&self) -> Json<Option<Foreign<ForeignType>>> {
-        Json(None)
+    async fn optional_none(&self) -> Json<Foreign<Option<ForeignType>>> {
+        Json(Foreign(None))
     }
 
     #[oai(path = "/foreign-opt", method = "get")]
@@ -4,3 +4,6 @@ use serde::{Deserialize, Serialize};
 pub struct ForeignType {
     pub text: String,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForeignId(pub u64);
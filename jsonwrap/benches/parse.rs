@@ -0,0 +1,64 @@
+//! Compares parsing a body through a `serde_json::Value` with parsing it from its bytes.
+//!
+//! Run with `cargo bench -p jsonwrap --bench parse`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use jsonwrap::Foreign;
+use poem_openapi::types::ParseFromJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    sku: String,
+    quantity: u32,
+    price: f64,
+    note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    customer: String,
+    lines: Vec<Line>,
+}
+
+fn time(name: &str, iterations: u32, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!("{name:>24}: {:?} per body", elapsed / iterations);
+    elapsed
+}
+
+fn main() {
+    let order = Order {
+        customer: "ada".to_string(),
+        lines: (0..500)
+            .map(|i| Line {
+                sku: format!("SKU-{i}"),
+                quantity: i,
+                price: f64::from(i) * 1.25,
+                note: (i % 3 == 0).then(|| "gift wrap".to_string()),
+            })
+            .collect(),
+    };
+    let body = serde_json::to_vec(&order).unwrap();
+    let iterations = 2_000;
+
+    let via_value = time("bytes -> Value -> T", iterations, || {
+        let value: Value = serde_json::from_slice(black_box(&body)).unwrap();
+        black_box(Foreign::<Order>::parse_from_json(Some(value)).ok());
+    });
+    let direct = time("bytes -> T", iterations, || {
+        black_box(Foreign::<Order>::from_json_slice(black_box(&body)).ok());
+    });
+    println!(
+        "{:>24}: {:.2}x",
+        "speedup",
+        via_value.as_secs_f64() / direct.as_secs_f64()
+    );
+}
@@ -0,0 +1,17 @@
+//! Bounded strings from `arrayvec`.
+//!
+//! `ArrayString<N>` deserializes through `deserialize_str`, so tracing sees a plain string
+//! and loses the capacity. It is recovered from the type name instead.
+
+use arrayvec::ArrayString;
+
+/// The capacity `N` when `T` is `ArrayString<N>`.
+pub(crate) fn capacity<T: ?Sized>() -> Option<usize> {
+    let probe = std::any::type_name::<ArrayString<0>>();
+    let prefix = probe.strip_suffix("0>")?;
+    std::any::type_name::<T>()
+        .strip_prefix(prefix)?
+        .strip_suffix('>')?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,136 @@
+//! Finding the types that are documented as opaque values instead of their real shape.
+//!
+//! Registration notes every fallback to an opaque schema while an audit is running on the
+//! current thread: types that could not be traced, enums cut off by tracing, and formats
+//! tracing left unresolved. Outside an audit, noting costs one thread-local lookup.
+//!
+//! Registration also keeps track of the containers and fields it is inside of, audit or
+//! not, so that errors can name the field they come from.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use poem_openapi::registry::Registry;
+
+#[derive(Default)]
+struct Audit {
+    /// The containers being registered, innermost last.
+    containers: Vec<String>,
+    opaque: BTreeSet<String>,
+}
+
+/// A container being registered, and the field of it being registered.
+struct Segment {
+    container: String,
+    field: Option<String>,
+}
+
+thread_local! {
+    static AUDIT: RefCell<Option<Audit>> = const { RefCell::new(None) };
+    static PATH: RefCell<Vec<Segment>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Registers every type in `types` into a scratch registry and returns the names of those
+/// documented as opaque values, sorted.
+///
+/// A type is listed under its component name when it could not be traced and has no
+/// samples, or is an enum tracing only saw part of. A format tracing could not resolve is
+/// listed under the container it appears in. Pass the `register` function of each type,
+/// as for [`warmup`](crate::warmup):
+///
+/// ```ignore
+/// let opaque = jsonwrap::opaque_types(&[<Foreign<User> as Type>::register]);
+/// assert!(opaque.is_empty(), "opaque schemas: {opaque:?}");
+/// ```
+pub fn opaque_types(types: &[fn(&mut Registry)]) -> Vec<String> {
+    let outer = AUDIT.with(|audit| audit.replace(Some(Audit::default())));
+    let mut scratch = Registry::new();
+    for register in types {
+        register(&mut scratch);
+    }
+    let audit = AUDIT.with(|audit| audit.replace(outer));
+    audit.map_or_else(Vec::new, |audit| audit.opaque.into_iter().collect())
+}
+
+/// Notes that the component `name` is documented as an opaque value.
+pub(crate) fn opaque(name: &str) {
+    AUDIT.with(|audit| {
+        if let Some(audit) = audit.borrow_mut().as_mut() {
+            audit.opaque.insert(name.to_string());
+        }
+    });
+}
+
+/// Notes that a format inside the container being registered is documented as an opaque
+/// value.
+pub(crate) fn opaque_within() {
+    AUDIT.with(|audit| {
+        if let Some(audit) = audit.borrow_mut().as_mut() {
+            let name = audit.containers.last().map_or("<root>", String::as_str);
+            audit.opaque.insert(name.to_string());
+        }
+    });
+}
+
+/// Runs `register` with `name` as the container being registered.
+pub(crate) fn within<R>(name: &str, register: impl FnOnce() -> R) -> R {
+    PATH.with(|path| {
+        path.borrow_mut().push(Segment {
+            container: name.to_string(),
+            field: None,
+        })
+    });
+    let result = audit_within(name, register);
+    PATH.with(|path| path.borrow_mut().pop());
+    result
+}
+
+/// Runs `register` with `field` as the field being registered, of the innermost container.
+pub(crate) fn field<R>(field: &str, register: impl FnOnce() -> R) -> R {
+    let outer = PATH.with(|path| {
+        path.borrow_mut()
+            .last_mut()
+            .map(|segment| segment.field.replace(field.to_string()))
+    });
+    let result = register();
+    if let Some(outer) = outer {
+        PATH.with(|path| {
+            if let Some(segment) = path.borrow_mut().last_mut() {
+                segment.field = outer;
+            }
+        });
+    }
+    result
+}
+
+/// The field being registered, as `Container.field`, and the outermost container.
+pub(crate) fn current_field() -> Option<(String, String)> {
+    PATH.with(|path| {
+        let path = path.borrow();
+        let innermost = path.last()?;
+        let name = match &innermost.field {
+            Some(field) => format!("{}.{field}", innermost.container),
+            None => innermost.container.clone(),
+        };
+        Some((name, path.first()?.container.clone()))
+    })
+}
+
+fn audit_within<R>(name: &str, register: impl FnOnce() -> R) -> R {
+    let auditing = AUDIT.with(|audit| match audit.borrow_mut().as_mut() {
+        Some(audit) => {
+            audit.containers.push(name.to_string());
+            true
+        }
+        None => false,
+    });
+    let result = register();
+    if auditing {
+        AUDIT.with(|audit| {
+            if let Some(audit) = audit.borrow_mut().as_mut() {
+                audit.containers.pop();
+            }
+        });
+    }
+    result
+}
@@ -0,0 +1,22 @@
+//! Byte buffers written as base64 strings, for [`BytesRepr::Base64`](crate::BytesRepr).
+//!
+//! serde_json writes bytes as an array of numbers, and reads them back from one. With the
+//! base64 representation, bytes are encoded when a foreign value is serialized, and decoded
+//! back into arrays by [`decode`](crate::decode) before an incoming body is deserialized.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::Value;
+
+pub(crate) fn encode(bytes: &[u8]) -> Value {
+    Value::String(STANDARD.encode(bytes))
+}
+
+/// The array of numbers serde_json reads the bytes encoded in `text` from.
+pub(crate) fn decode(text: &str) -> Result<Value, base64::DecodeError> {
+    Ok(STANDARD
+        .decode(text.as_bytes())?
+        .into_iter()
+        .map(Value::from)
+        .collect())
+}
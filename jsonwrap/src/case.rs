@@ -0,0 +1,90 @@
+//! Renaming the fields of foreign structs on the wire, for types whose serde attributes
+//! cannot be changed.
+//!
+//! A [`KeyCase`] set with [`ForeignConfig::key_case`](crate::ForeignConfig::key_case) applies
+//! to the struct containers of a trace, by serde container name. The same name is what
+//! `serialize_struct` is given, so responses, request bodies and schemas all find it.
+
+use std::borrow::Cow;
+
+/// The casing struct fields are renamed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyCase {
+    /// `userId`
+    CamelCase,
+    /// `UserId`
+    PascalCase,
+    /// `user_id`
+    SnakeCase,
+    /// `user-id`
+    KebabCase,
+}
+
+impl KeyCase {
+    /// Renames `name`, written in any of the casings, to this one.
+    ///
+    /// Words are split at `_` and `-` and before an uppercase letter that follows a lowercase
+    /// letter or a digit, so `user_id`, `userId` and `UserId` all read as `user` and `id`.
+    pub fn apply(self, name: &str) -> String {
+        let mut words: Vec<String> = Vec::new();
+        let mut word = String::new();
+        let mut previous: Option<char> = None;
+        for c in name.chars() {
+            if c == '_' || c == '-' {
+                words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+                previous = None;
+                continue;
+            }
+            if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit())
+            {
+                words.push(std::mem::take(&mut word));
+            }
+            word.extend(c.to_lowercase());
+            previous = Some(c);
+        }
+        words.extend((!word.is_empty()).then_some(word));
+
+        let capitalized = |word: &str| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        };
+        match self {
+            KeyCase::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        word.clone()
+                    } else {
+                        capitalized(word)
+                    }
+                })
+                .collect(),
+            KeyCase::PascalCase => words.iter().map(|word| capitalized(word)).collect(),
+            KeyCase::SnakeCase => words.join("_"),
+            KeyCase::KebabCase => words.join("-"),
+        }
+    }
+}
+
+/// The casing of one struct container, with the fields it leaves alone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct KeyCasing {
+    pub(crate) case: KeyCase,
+    pub(crate) except: Vec<String>,
+}
+
+impl KeyCasing {
+    /// The name `field` is written under.
+    pub(crate) fn wire_name<'a>(&self, field: &'a str) -> Cow<'a, str> {
+        if self.except.iter().any(|except| except == field) {
+            Cow::Borrowed(field)
+        } else {
+            Cow::Owned(self.case.apply(field))
+        }
+    }
+}
@@ -0,0 +1,782 @@
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use poem_openapi::registry::MetaSchema;
+#[cfg(feature = "schemars")]
+use poem_openapi::registry::{MetaSchemaRef, Registry};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+#[cfg(feature = "trace")]
+use serde_reflection::TracerConfig;
+
+use crate::Trace;
+#[cfg(feature = "trace")]
+use crate::case::KeyCase;
+use crate::case::KeyCasing;
+use crate::enums::EnumRepr;
+#[cfg(feature = "bitflags")]
+use crate::flags::{DeclaredFlags, FlagsRepr};
+use crate::repr::Repr;
+use crate::ser::{self, KeyEncoder};
+
+/// Process-wide settings controlling how `Foreign` wrappers render their schemas.
+///
+/// Build one with [`ForeignConfig::new`], chain the setters you need and call
+/// [`ForeignConfig::install`] during startup, before the spec is generated.
+#[derive(Clone, Debug)]
+pub struct ForeignConfig {
+    pub(crate) inline_all: bool,
+    pub(crate) nullable_title: bool,
+    pub(crate) fields: HashMap<(String, String), FieldOverrides>,
+    pub(crate) key_encoders: HashMap<TypeId, KeyEncoder>,
+    pub(crate) key_cases: HashMap<String, KeyCasing>,
+    pub(crate) bytes_repr: BytesRepr,
+    pub(crate) int128_repr: Int128Repr,
+    pub(crate) non_finite_floats: NonFiniteFloats,
+    pub(crate) enum_samples: HashMap<String, Vec<Value>>,
+    pub(crate) enum_reprs: HashMap<String, EnumRepr>,
+    pub(crate) reprs: HashMap<String, (Repr, Option<Value>)>,
+    pub(crate) declared_keys: HashMap<&'static str, String>,
+    pub(crate) string_formats: HashMap<String, &'static str>,
+    pub(crate) string_types: HashMap<String, &'static str>,
+    pub(crate) tuple_items: HashMap<String, Vec<TupleItem>>,
+    pub(crate) descriptions: HashMap<String, String>,
+    #[cfg(feature = "trace")]
+    pub(crate) tracer_config: fn() -> TracerConfig,
+    pub(crate) docs_sides: HashMap<TypeId, DocsSide>,
+    #[cfg(feature = "chrono")]
+    pub(crate) naive_date_time_format: &'static str,
+    #[cfg(feature = "bitflags")]
+    pub(crate) flags: HashMap<String, DeclaredFlags>,
+    #[cfg(feature = "schemars")]
+    pub(crate) json_schemas: HashMap<String, fn(&mut Registry) -> MetaSchemaRef>,
+}
+
+/// How byte buffers (`serde_bytes`, `ByteBuf`, `&[u8]`) are described in the schema and
+/// written in `Foreign` payloads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BytesRepr {
+    /// `{ "type": "string", "format": "byte" }`, OpenAPI's base64 convention. Bytes are
+    /// written as base64 and request bodies decoded from it.
+    #[default]
+    Base64,
+    /// `{ "type": "array", "items": { "type": "integer" } }`, the list of numbers serde_json
+    /// writes by default.
+    Array,
+}
+
+/// How `i128` and `u128` values are described in the schema and written in `Foreign`
+/// payloads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Int128Repr {
+    /// `{ "type": "string", "format": "int128" }` holding the decimal digits, which no client
+    /// rounds. Values are written as strings and read back from them, though request bodies
+    /// can only carry values that also fit in 64 bits.
+    #[default]
+    String,
+    /// A JSON number, as serde_json writes it. JavaScript and most generated clients round
+    /// values beyond 2^53, and serde_json fails to write values beyond 64 bits.
+    Number,
+}
+
+/// What `Foreign` payloads do with NaN and infinite `f32`/`f64` values, which JSON has no
+/// numbers for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFiniteFloats {
+    /// Write `null`, as serde_json does. The schema still says `number`, so clients that
+    /// validate responses reject it.
+    #[default]
+    Null,
+    /// Fail to serialize the value, which logs an error naming the float and makes `to_json`
+    /// return `None`.
+    Reject,
+    /// Write the strings `"NaN"`, `"Infinity"` and `"-Infinity"`, and describe floats as one
+    /// of a number or those strings. Request bodies cannot carry them, as serde_json has no
+    /// value to read them into.
+    String,
+}
+
+/// Which serde impl of a type its schema is traced from, for types whose `Serialize` and
+/// `Deserialize` disagree about the shape.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForeignDocsSide {
+    /// What the type accepts, traced from `Deserialize`. Used for every type not configured
+    /// otherwise.
+    #[default]
+    Deserialize,
+    /// What the type writes, traced from `Serialize` on a sample value.
+    Serialize,
+    /// Both: `Foreign<T>` is documented as it is written, and request bodies read through
+    /// [`ForeignJson`](crate::ForeignJson) as they are accepted, under a separate component
+    /// whose name ends in `Input`.
+    Both,
+}
+
+/// A [`ForeignDocsSide`] with the trace of the `Serialize` side.
+#[derive(Clone, Debug)]
+pub(crate) struct DocsSide {
+    pub(crate) side: ForeignDocsSide,
+    pub(crate) trace: Arc<Trace>,
+}
+
+impl Default for ForeignConfig {
+    fn default() -> Self {
+        Self {
+            inline_all: false,
+            nullable_title: true,
+            fields: HashMap::new(),
+            key_encoders: HashMap::new(),
+            key_cases: HashMap::new(),
+            bytes_repr: BytesRepr::default(),
+            int128_repr: Int128Repr::default(),
+            non_finite_floats: NonFiniteFloats::default(),
+            enum_samples: HashMap::new(),
+            enum_reprs: HashMap::new(),
+            reprs: HashMap::new(),
+            declared_keys: HashMap::new(),
+            string_formats: HashMap::new(),
+            string_types: HashMap::new(),
+            tuple_items: HashMap::new(),
+            descriptions: HashMap::new(),
+            #[cfg(feature = "trace")]
+            tracer_config: default_tracer_config,
+            docs_sides: HashMap::new(),
+            #[cfg(feature = "chrono")]
+            naive_date_time_format: "local-date-time",
+            #[cfg(feature = "bitflags")]
+            flags: HashMap::new(),
+            #[cfg(feature = "schemars")]
+            json_schemas: HashMap::new(),
+        }
+    }
+}
+
+/// Strings are traced with a non-empty value, so that types which reject `""`, such as
+/// `NonEmptyString`, can still be traced.
+#[cfg(feature = "trace")]
+fn default_tracer_config() -> TracerConfig {
+    TracerConfig::default()
+        .default_borrowed_str_value("x")
+        .default_string_value("x".to_string())
+}
+
+/// Per-property additions applied on top of the traced schema.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FieldOverrides {
+    pub(crate) default: Option<Value>,
+    pub(crate) example: Option<Value>,
+    pub(crate) constraints: FieldConstraints,
+    pub(crate) deprecated: bool,
+}
+
+impl ForeignConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inline every schema at its use site instead of registering components.
+    ///
+    /// With this enabled `register()` becomes a no-op and `schema_ref()` returns the
+    /// fully expanded schema. Recursive types cannot be inlined and cause a panic
+    /// naming the cycle.
+    pub fn inline_all(mut self, value: bool) -> Self {
+        self.inline_all = value;
+        self
+    }
+
+    /// Set `title` to the component name on the nullable schemas that stand in for a reference.
+    ///
+    /// Components with a `type` are referenced through a `{name}Nullable` copy, which gets
+    /// the title of the component it copies; the others through a nullable `allOf` wrapper.
+    ///
+    /// Enabled by default. Some tools render that title as a field label, so it can be
+    /// turned off to emit just `{ "nullable": true, "allOf": [{ "$ref": ... }] }` and
+    /// untitled copies.
+    pub fn nullable_title(mut self, value: bool) -> Self {
+        self.nullable_title = value;
+        self
+    }
+
+    /// Choose how byte buffers are described. Defaults to [`BytesRepr::Base64`].
+    pub fn bytes_repr(mut self, value: BytesRepr) -> Self {
+        self.bytes_repr = value;
+        self
+    }
+
+    /// Choose how 128-bit integers are described and written. Defaults to
+    /// [`Int128Repr::String`].
+    pub fn int128_repr(mut self, value: Int128Repr) -> Self {
+        self.int128_repr = value;
+        self
+    }
+
+    /// Choose what NaN and infinite floats are written as. Defaults to
+    /// [`NonFiniteFloats::Null`].
+    pub fn non_finite_floats(mut self, value: NonFiniteFloats) -> Self {
+        self.non_finite_floats = value;
+        self
+    }
+
+    /// Document default values on the properties of a foreign type.
+    pub fn field_defaults(mut self, defaults: ForeignFieldDefaults) -> Self {
+        let type_name = (defaults.key)(&self);
+        for (field, value) in defaults.fields {
+            self.field_mut(&type_name, &field).default = Some(value);
+        }
+        self
+    }
+
+    /// Attach validation keywords to one property of `T`, named as it appears in the JSON.
+    pub fn field_constraints<T: DeserializeOwned + 'static>(
+        mut self,
+        field: impl Into<String>,
+        constraints: FieldConstraints,
+    ) -> Self {
+        self.field_mut(&crate::metadata_key::<T>(&self), &field.into())
+            .constraints = constraints;
+        self
+    }
+
+    /// Document an example value on one property of `T`, named as it appears in the JSON.
+    ///
+    /// A warning is logged when the example does not match the property's schema, as for
+    /// defaults.
+    pub fn example_field<T: DeserializeOwned + 'static>(
+        mut self,
+        field: impl Into<String>,
+        value: impl Serialize,
+    ) -> Self {
+        let type_name = crate::metadata_key::<T>(&self);
+        let field = field.into();
+        let value = serde_json::to_value(value).unwrap_or_else(|err| {
+            panic!("jsonwrap: the example for `{type_name}.{field}` failed to serialize: {err}")
+        });
+        self.field_mut(&type_name, &field).example = Some(value);
+        self
+    }
+
+    /// Mark one property of `T`, named as it appears in the JSON, as `deprecated`.
+    pub fn deprecate_field<T: DeserializeOwned + 'static>(
+        mut self,
+        field: impl Into<String>,
+    ) -> Self {
+        self.field_mut(&crate::metadata_key::<T>(&self), &field.into())
+            .deprecated = true;
+        self
+    }
+
+    /// Encode map keys inside `T` that serialize to arrays or objects, such as tuple keys.
+    ///
+    /// String, number and bool keys are always written as JSON object keys. Without an
+    /// encoder, registering a `T` whose maps have composite keys panics, since serde_json
+    /// would fail to serialize every value of it.
+    pub fn map_key_encoder<T: 'static>(mut self, encoder: KeyEncoder) -> Self {
+        self.key_encoders.insert(TypeId::of::<T>(), encoder);
+        self
+    }
+
+    /// Rename the fields of `T`, and of every struct it contains, to `case` on the wire,
+    /// except those named in `except`.
+    ///
+    /// This is for types whose serde attributes cannot be changed, such as a vendor crate
+    /// writing snake_case for an API that uses camelCase. Responses are written, request
+    /// bodies read and schemas documented with the renamed fields. The setting belongs to
+    /// the struct containers, so it also applies where they appear inside other types.
+    /// Field settings such as [`field_constraints`](Self::field_constraints), and parse
+    /// errors, keep the names `T` declares; map keys and enum variants are left alone.
+    ///
+    /// Panics if `T` cannot be traced, if renaming makes two fields of one struct share a
+    /// name, or if a struct already has a different casing.
+    #[cfg(feature = "trace")]
+    pub fn key_case<T: DeserializeOwned + 'static>(
+        mut self,
+        case: KeyCase,
+        except: &[&str],
+    ) -> Self {
+        let name = crate::type_name::<T>();
+        let trace = crate::trace_uncached::<T>(self.tracer_config, &|_, _| Ok(()))
+            .unwrap_or_else(|err| panic!("jsonwrap: `{name}` could not be traced: {err}"));
+        let casing = KeyCasing {
+            case,
+            except: except.iter().map(|field| field.to_string()).collect(),
+        };
+        for (container, format) in trace.registry() {
+            let crate::format::ContainerFormat::Struct(fields) = format else {
+                continue;
+            };
+            let mut seen = HashMap::new();
+            for field in fields {
+                let wire = casing.wire_name(&field.name).into_owned();
+                if let Some(other) = seen.insert(wire.clone(), &field.name) {
+                    panic!(
+                        "jsonwrap: `{container}` has the fields `{other}` and `{}`, which are \
+                         both written as `{wire}` in {case:?}",
+                        field.name
+                    );
+                }
+            }
+            match self.key_cases.get(container) {
+                Some(existing) if *existing != casing => panic!(
+                    "jsonwrap: `{container}` is reached from `{name}` with a different casing \
+                     from the one it already has"
+                ),
+                _ => {
+                    self.key_cases.insert(container.clone(), casing.clone());
+                }
+            }
+        }
+        self
+    }
+
+    /// Register sample values for an enum that cannot be traced from its type alone.
+    ///
+    /// Tagged and untagged enums (`#[serde(tag = ...)]`, `#[serde(untagged)]`) are not
+    /// supported by serde_reflection. When tracing `T` fails, its schema is built from these
+    /// samples instead, with the tagging inferred by [`EnumRepr::infer`] unless it was set
+    /// with [`enum_repr`](Self::enum_repr). Provide at least one sample per variant.
+    pub fn enum_samples<T: Serialize + 'static>(
+        mut self,
+        samples: impl IntoIterator<Item = T>,
+    ) -> Self {
+        let values = samples
+            .into_iter()
+            .filter_map(|sample| serde_json::to_value(sample).ok());
+        self.enum_samples
+            .entry(std::any::type_name::<T>().to_string())
+            .or_default()
+            .extend(values);
+        self
+    }
+
+    /// State the tagging of the enum `T` instead of inferring it from its samples.
+    ///
+    /// Inference has to guess when several string fields appear in every sample, and cannot
+    /// tell an internally tagged enum from an externally tagged one with a single sampled
+    /// variant. The samples registered with [`enum_samples`](Self::enum_samples) are still
+    /// needed to describe the variants' fields, and registering `T` panics if one lacks the
+    /// stated tag or, for adjacent tagging, has a key other than the tag and content.
+    pub fn enum_repr<T: 'static>(mut self, repr: EnumRepr) -> Self {
+        self.enum_reprs
+            .insert(std::any::type_name::<T>().to_string(), repr);
+        self
+    }
+
+    /// Declare how `T` is written on the wire, replacing whatever tracing finds.
+    ///
+    /// Use this for types whose serde output depends on their crate's feature flags, or
+    /// that cannot be traced at all, such as `uuid::Uuid` without the `uuid` feature. `sample`
+    /// is serialized here and
+    /// compared with `repr` when `T` is registered; a mismatch panics rather than ship a
+    /// spec describing a shape the API never sends.
+    pub fn declare_repr<T: Serialize + DeserializeOwned + 'static>(
+        mut self,
+        repr: Repr,
+        sample: T,
+    ) -> Self {
+        let sample = serde_json::to_value(sample).ok();
+        let key = self.declared_key_of::<T>();
+        self.reprs.insert(key, (repr, sample));
+        self
+    }
+
+    /// Document the string newtype `T` with an OpenAPI `format`, such as `"email"`,
+    /// `"hostname"` or `"uri"`.
+    ///
+    /// Unlike [`declare_repr`](Self::declare_repr) the schema is still traced, so `T` must
+    /// wrap a string; the format is only added on top, and a warning is logged for newtypes
+    /// around anything else.
+    pub fn string_format<T: DeserializeOwned + 'static>(mut self, format: &'static str) -> Self {
+        self.string_formats
+            .insert(crate::metadata_key::<T>(&self), format);
+        self
+    }
+
+    /// Describe the serde container `name` as a string with `format` wherever it appears,
+    /// without looking at its traced schema.
+    ///
+    /// This is for types that serialize as a string but are traced as some other container,
+    /// keyed by the container name rather than a Rust type. With the `chrono` feature, chrono's
+    /// types are already known, traced as the containers `chrono::DateTime`,
+    /// `chrono::NaiveDate`, `chrono::NaiveTime` and `chrono::NaiveDateTime`, and with the
+    /// `uuid` feature uuid's as `uuid::Uuid`; entries given here take precedence.
+    pub fn string_type(mut self, name: impl Into<String>, format: &'static str) -> Self {
+        self.string_types.insert(name.into(), format);
+        self
+    }
+
+    /// Document the positions of the tuple struct `T`, in order.
+    ///
+    /// Each [`TupleItem`] annotates the schema of the position at the same index, so a
+    /// `LatLng(f64, f64)` can name and bound its latitude and longitude separately.
+    pub fn tuple_items<T: DeserializeOwned + 'static>(
+        mut self,
+        items: impl IntoIterator<Item = TupleItem>,
+    ) -> Self {
+        self.tuple_items
+            .insert(crate::metadata_key::<T>(&self), items.into_iter().collect());
+        self
+    }
+
+    /// Set the `description` of the component registered for `T`.
+    pub fn description<T: DeserializeOwned + 'static>(
+        mut self,
+        description: impl Into<String>,
+    ) -> Self {
+        self.descriptions
+            .insert(crate::metadata_key::<T>(&self), description.into());
+        self
+    }
+
+    /// The `format` of `chrono::NaiveDateTime`, which has no offset and so is not an OpenAPI
+    /// `date-time`. Defaults to `"local-date-time"`.
+    #[cfg(feature = "chrono")]
+    pub fn naive_date_time_format(mut self, format: &'static str) -> Self {
+        self.naive_date_time_format = format;
+        self
+    }
+
+    /// Describe and write the `bitflags` type `F` as `repr`, instead of the `"A | B"` text its
+    /// serde support writes to JSON.
+    ///
+    /// `F` is found by its name: as the container `F` wherever it appears when it derives
+    /// serde's default newtype impls, and only as the root of `Foreign<F>` when it is
+    /// `#[serde(transparent)]`, since it is then traced as a plain integer.
+    #[cfg(feature = "bitflags")]
+    pub fn bitflags<F>(mut self, repr: FlagsRepr) -> Self
+    where
+        F: bitflags::Flags + DeserializeOwned + 'static,
+        F::Bits:
+            bitflags::parser::ParseHex + bitflags::parser::WriteHex + Serialize + DeserializeOwned,
+    {
+        let key = self.declared_key_of::<F>();
+        self.flags.insert(key, DeclaredFlags::new::<F>(repr));
+        self
+    }
+
+    /// Describe `T` with its `schemars::JsonSchema` impl instead of tracing it.
+    ///
+    /// schemars keeps what tracing loses, such as doc comments, formats and validation
+    /// attributes. `T` is registered under its schemars name, along with the definitions its
+    /// schema refers to, and is used that way wherever its container appears. Keywords without
+    /// a `MetaSchema` counterpart are dropped, with a warning listing them.
+    #[cfg(feature = "schemars")]
+    pub fn schemars<T: schemars::JsonSchema + DeserializeOwned + 'static>(mut self) -> Self {
+        let key = self.declared_key_of::<T>();
+        self.json_schemas
+            .insert(key, crate::json_schema::register::<T>);
+        self
+    }
+
+    /// Choose which of `T`'s serde impls its schema is traced from, when they disagree, such as
+    /// a hand-written `Deserialize` that also accepts an older shape.
+    ///
+    /// serde_reflection can only trace `Serialize` by serializing a value, so `sample` is
+    /// traced here, and has to show the whole shape: every `Option` set, every sequence and
+    /// map non-empty. Otherwise this panics with the part that stayed unknown. The setting
+    /// applies to `Foreign<T>` itself, not to wrappers such as `Foreign<Vec<T>>`. With
+    /// [`ForeignDocsSide::Both`], containers that `T` contains are registered once, from
+    /// whichever side is registered first; only `T`'s own component is split.
+    #[cfg(feature = "trace")]
+    pub fn docs_side<T: Serialize + 'static>(mut self, side: ForeignDocsSide, sample: T) -> Self {
+        let name = crate::type_name::<T>();
+        let trace = crate::trace_serialized(self.tracer_config, &sample).unwrap_or_else(|err| {
+            panic!("jsonwrap: the sample given for `{name}` could not be traced: {err}")
+        });
+        self.docs_sides.insert(
+            TypeId::of::<T>(),
+            DocsSide {
+                side,
+                trace: Arc::new(trace),
+            },
+        );
+        self
+    }
+
+    /// Build the serde_reflection `TracerConfig` that foreign types are traced with.
+    ///
+    /// `make` is called for every trace, as a tracer consumes its configuration. It replaces
+    /// the default entirely, which only sets non-empty default strings; keep those if
+    /// `NonEmptyString` or other types rejecting `""` are traced. Call it before the per-type
+    /// setters, which trace their type to find the component it is documented as.
+    #[cfg(feature = "trace")]
+    pub fn tracer_config(mut self, make: fn() -> TracerConfig) -> Self {
+        self.tracer_config = make;
+        self
+    }
+
+    /// Make this the configuration used by every `Foreign` wrapper.
+    ///
+    /// The configuration is frozen the first time it is read, so that component names and
+    /// the schemas registered under them always come from the same settings. Installing
+    /// after that point, or a second time, panics; call this at the top of `main`, before
+    /// the spec is built.
+    pub fn install(self) {
+        if CONFIG.set(self).is_err() {
+            panic!(
+                "jsonwrap: `ForeignConfig::install` called after the configuration was already \
+                 installed or read; install it once, before any `Foreign` type is used"
+            );
+        }
+    }
+
+    /// The name the field `field` of the struct container `container` is written under.
+    pub(crate) fn wire_name<'a>(&self, container: &str, field: &'a str) -> Cow<'a, str> {
+        match self.key_cases.get(container) {
+            Some(casing) => casing.wire_name(field),
+            None => Cow::Borrowed(field),
+        }
+    }
+
+    pub(crate) fn field(&self, type_name: &str, field: &str) -> Option<&FieldOverrides> {
+        self.fields.get(&(type_name.to_string(), field.to_string()))
+    }
+
+    /// The samples registered for the type whose full path is `full` and its tagging, declared
+    /// or inferred from them.
+    pub(crate) fn samples_for(&self, full: &str) -> Option<(EnumRepr, &[Value])> {
+        let samples = self.enum_samples.get(full)?;
+        let repr = match self.enum_reprs.get(full) {
+            Some(repr) => repr.clone(),
+            None => EnumRepr::infer(samples)?,
+        };
+        Some((repr, samples))
+    }
+
+    /// The container name the settings of the type whose full path is `full` are stored under,
+    /// if it was given to [`declare_repr`](Self::declare_repr), [`bitflags`](Self::bitflags) or
+    /// [`schemars`](Self::schemars).
+    pub(crate) fn declared_key(&self, full: &str) -> Option<&str> {
+        self.declared_keys.get(full).map(String::as_str)
+    }
+
+    /// Keys the settings of `T` by the container it is traced as, which is how they are found
+    /// inside other types, and records that key under `T`'s full path for `Foreign<T>` itself.
+    fn declared_key_of<T: DeserializeOwned + 'static>(&mut self) -> String {
+        let key = crate::metadata_key::<T>(self);
+        self.declared_keys
+            .insert(std::any::type_name::<T>(), key.clone());
+        key
+    }
+
+    /// Whether any field constraint has to be checked when parsing request bodies.
+    pub(crate) fn enforces_constraints(&self) -> bool {
+        self.fields
+            .values()
+            .any(|overrides| overrides.constraints.enforce)
+    }
+
+    pub(crate) fn ser_options<T: 'static>(&'static self) -> ser::Options {
+        ser::Options {
+            key_encoder: self.key_encoders.get(&TypeId::of::<T>()).copied(),
+            key_cases: (!self.key_cases.is_empty()).then_some(&self.key_cases),
+            bytes_repr: self.bytes_repr,
+            int128_repr: self.int128_repr,
+            non_finite_floats: self.non_finite_floats,
+        }
+    }
+
+    fn field_mut(&mut self, type_name: &str, field: &str) -> &mut FieldOverrides {
+        self.fields
+            .entry((type_name.to_string(), field.to_string()))
+            .or_default()
+    }
+}
+
+/// Default values for the fields of one foreign type, emitted as `default` on each property.
+///
+/// Tracing cannot observe `#[serde(default)]` functions, so the values have to be supplied
+/// here. Each value is checked against the property's schema at registration time and a
+/// warning is logged if it does not fit.
+#[derive(Clone, Debug)]
+pub struct ForeignFieldDefaults {
+    key: fn(&ForeignConfig) -> String,
+    fields: Vec<(String, Value)>,
+}
+
+impl ForeignFieldDefaults {
+    /// Start an empty set of defaults for `T`.
+    pub fn new<T: DeserializeOwned + 'static>() -> Self {
+        Self {
+            key: crate::metadata_key::<T>,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Take the defaults from the serialized form of `T::default()`.
+    pub fn from_default<T: Default + Serialize + DeserializeOwned + 'static>() -> Self {
+        let mut defaults = Self::new::<T>();
+        match serde_json::to_value(T::default()) {
+            Ok(Value::Object(map)) => defaults.fields.extend(map),
+            _ => tracing::warn!(
+                "jsonwrap: `{}::default()` did not serialize to an object, no defaults recorded",
+                crate::type_name::<T>()
+            ),
+        }
+        defaults
+    }
+
+    /// Set the default for a single field, named as it appears in the serialized JSON.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Validation keywords for a single property of a foreign type.
+///
+/// These are schema metadata only unless [`FieldConstraints::enforce`] is set, in which case
+/// request bodies that violate them are rejected before deserialization.
+#[derive(Clone, Debug, Default)]
+pub struct FieldConstraints {
+    pub(crate) enum_values: Vec<String>,
+    pub(crate) display_names: Vec<String>,
+    pub(crate) min_items: Option<usize>,
+    pub(crate) max_items: Option<usize>,
+    pub(crate) min_properties: Option<usize>,
+    pub(crate) max_properties: Option<usize>,
+    pub(crate) enforce: bool,
+}
+
+impl FieldConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict a string property to a closed set of values, emitted as `enum`.
+    ///
+    /// Meant for plain `String` fields that carry codes from another system; Rust enums
+    /// already get their variants listed.
+    pub fn enum_values(mut self, values: &[&str]) -> Self {
+        self.enum_values = values.iter().map(|value| value.to_string()).collect();
+        self
+    }
+
+    /// Human-readable labels for [`enum_values`](Self::enum_values), in the same order.
+    ///
+    /// poem-openapi cannot emit vendor extensions such as `x-enum-varnames` on a schema, so
+    /// the labels are listed in the property's `description` instead.
+    pub fn display_names(mut self, names: &[&str]) -> Self {
+        self.display_names = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Bound the length of an array property, emitted as `minItems`.
+    pub fn min_items(mut self, value: usize) -> Self {
+        self.min_items = Some(value);
+        self
+    }
+
+    /// Bound the length of an array property, emitted as `maxItems`.
+    pub fn max_items(mut self, value: usize) -> Self {
+        self.max_items = Some(value);
+        self
+    }
+
+    /// Bound the number of entries of a map property, emitted as `minProperties`.
+    pub fn min_properties(mut self, value: usize) -> Self {
+        self.min_properties = Some(value);
+        self
+    }
+
+    /// Bound the number of entries of a map property, emitted as `maxProperties`.
+    pub fn max_properties(mut self, value: usize) -> Self {
+        self.max_properties = Some(value);
+        self
+    }
+
+    /// Reject request bodies whose value for this property is outside the constraints.
+    pub fn enforce(mut self, value: bool) -> Self {
+        self.enforce = value;
+        self
+    }
+
+    /// Checks a value of the constrained property, returning what is wrong with it.
+    pub(crate) fn check(&self, value: &Value) -> Result<(), String> {
+        if let Value::String(value) = value
+            && !self.enum_values.is_empty()
+            && !self.enum_values.contains(value)
+        {
+            return Err(format!("\"{value}\" is not one of {:?}", self.enum_values));
+        }
+        let (len, min, max, unit) = match value {
+            Value::Array(items) => (items.len(), self.min_items, self.max_items, "items"),
+            Value::Object(map) => (
+                map.len(),
+                self.min_properties,
+                self.max_properties,
+                "entries",
+            ),
+            _ => return Ok(()),
+        };
+        if let Some(min) = min
+            && len < min
+        {
+            return Err(format!("has {len} {unit}, at least {min} required"));
+        }
+        if let Some(max) = max
+            && len > max
+        {
+            return Err(format!("has {len} {unit}, at most {max} allowed"));
+        }
+        Ok(())
+    }
+}
+
+/// Documentation for one position of a tuple struct.
+#[derive(Clone, Debug, Default)]
+pub struct TupleItem {
+    title: Option<String>,
+    description: Option<String>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+}
+
+impl TupleItem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A short name for the position, emitted as `title`.
+    pub fn title(mut self, value: impl Into<String>) -> Self {
+        self.title = Some(value.into());
+        self
+    }
+
+    pub fn description(mut self, value: impl Into<String>) -> Self {
+        self.description = Some(value.into());
+        self
+    }
+
+    /// Lower bound of a numeric position, emitted as `minimum`.
+    pub fn minimum(mut self, value: f64) -> Self {
+        self.minimum = Some(value);
+        self
+    }
+
+    /// Upper bound of a numeric position, emitted as `maximum`.
+    pub fn maximum(mut self, value: f64) -> Self {
+        self.maximum = Some(value);
+        self
+    }
+
+    pub(crate) fn apply(&self, schema: &mut MetaSchema) {
+        if let Some(title) = &self.title {
+            schema.title = Some(title.clone());
+        }
+        if let Some(description) = &self.description {
+            schema.description = Some(crate::leak_str(description));
+        }
+        schema.minimum = self.minimum.or(schema.minimum);
+        schema.maximum = self.maximum.or(schema.maximum);
+    }
+}
+
+static CONFIG: OnceLock<ForeignConfig> = OnceLock::new();
+
+/// The installed configuration, freezing the defaults if nothing was installed yet.
+pub(crate) fn current() -> &'static ForeignConfig {
+    CONFIG.get_or_init(ForeignConfig::default)
+}
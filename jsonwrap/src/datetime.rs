@@ -0,0 +1,63 @@
+//! Date and time types from `chrono`.
+//!
+//! chrono's deserializers parse the string they are given, which the tracer's placeholder
+//! never is, so its types are traced through [`stand_in`](crate::stand_in) as containers of
+//! their own, named after their path, and described as formatted strings.
+
+#[cfg(feature = "trace")]
+use std::any::type_name;
+
+#[cfg(feature = "trace")]
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::config;
+#[cfg(feature = "trace")]
+use crate::stand_in::StandIn;
+
+const DATE_TIME: &str = "chrono::DateTime";
+const NAIVE_DATE_TIME: &str = "chrono::NaiveDateTime";
+const NAIVE_DATE: &str = "chrono::NaiveDate";
+const NAIVE_TIME: &str = "chrono::NaiveTime";
+
+/// The stand-in for the chrono type whose visitor reads values of the type path `value`.
+#[cfg(feature = "trace")]
+pub(crate) fn stand_in(value: &str) -> Option<StandIn> {
+    let (container, text) = if is_date_time(value) {
+        (DATE_TIME, "2000-01-01T00:00:00Z")
+    } else if value == type_name::<NaiveDateTime>() {
+        (NAIVE_DATE_TIME, "2000-01-01T00:00:00")
+    } else if value == type_name::<NaiveDate>() {
+        (NAIVE_DATE, "2000-01-01")
+    } else if value == type_name::<NaiveTime>() {
+        (NAIVE_TIME, "00:00:00")
+    } else {
+        return None;
+    };
+    Some(StandIn {
+        container,
+        text,
+        bytes: None,
+    })
+}
+
+/// Whether `value` is the path of a `DateTime<Tz>`, whatever `Tz` is. Every one is read as a
+/// `DateTime<FixedOffset>` and converted, but written as itself.
+#[cfg(feature = "trace")]
+fn is_date_time(value: &str) -> bool {
+    let path = type_name::<DateTime<FixedOffset>>();
+    let base = &path[..path.find('<').unwrap_or(path.len())];
+    value
+        .strip_prefix(base)
+        .is_some_and(|rest| rest.starts_with('<'))
+}
+
+/// The string `format` of the chrono type traced as the container `name`.
+pub(crate) fn format(name: &str) -> Option<&'static str> {
+    match name {
+        DATE_TIME => Some("date-time"),
+        NAIVE_DATE_TIME => Some(config::current().naive_date_time_format),
+        NAIVE_DATE => Some("date"),
+        NAIVE_TIME => Some("time"),
+        _ => None,
+    }
+}
@@ -0,0 +1,242 @@
+//! Rewriting request bodies into the JSON that foreign types deserialize from.
+//!
+//! Some values are written differently from what their `Deserialize` impl reads through
+//! serde_json: byte buffers as base64 with [`BytesRepr::Base64`], 128-bit integers as strings
+//! with [`Int128Repr::String`], struct fields renamed with a
+//! [`KeyCase`](crate::KeyCase), and, with the `bitflags` feature, flags types in their
+//! declared representation. Their positions in a body come
+//! from walking it alongside the traced format, since a string or a number alone could be
+//! anything.
+
+use serde_json::{Map, Number, Value};
+
+use crate::config::{self, BytesRepr, Int128Repr};
+use crate::error::ForeignParseError;
+use crate::format::{ContainerFormat, Format, Named, Registry as SerdeRegistry, VariantFormat};
+use crate::validate::escape;
+use crate::{Trace, bytes};
+
+/// Whether a body for the type whose full path is `full`, traced as `trace`, has anything to
+/// rewrite.
+pub(crate) fn needed(
+    trace: &Trace,
+    #[cfg_attr(not(feature = "bitflags"), allow(unused_variables))] full: &str,
+) -> bool {
+    let config = config::current();
+    let needed = (config.bytes_repr == BytesRepr::Base64 && trace.has_bytes())
+        || (config.int128_repr == Int128Repr::String && trace.has_int128())
+        || (!config.key_cases.is_empty()
+            && trace
+                .registry()
+                .keys()
+                .any(|container| config.key_cases.contains_key(container)));
+    #[cfg(feature = "bitflags")]
+    let needed = needed || crate::flags::within(trace, full);
+    needed
+}
+
+/// Rewrites a body for the type whose full path is `full`, traced as `trace`, in place.
+pub(crate) fn decode(
+    value: &mut Value,
+    trace: &Trace,
+    #[cfg_attr(not(feature = "bitflags"), allow(unused_variables))] full: &str,
+) -> Result<(), ForeignParseError> {
+    let mut decoder = Decoder {
+        serde_reg: trace.registry(),
+        base64: config::current().bytes_repr == BytesRepr::Base64,
+        int128: config::current().int128_repr == Int128Repr::String,
+        path: String::new(),
+    };
+    // A flags type serialized transparently is traced as its bits, without a container
+    // name to find it by, so the root is matched on the Rust type instead.
+    #[cfg(feature = "bitflags")]
+    if let Some(key) = config::current().declared_key(full)
+        && crate::flags::declared(key).is_some()
+    {
+        return decoder.format(value, &Format::TypeName(key.to_string()));
+    }
+    decoder.format(value, trace.root())
+}
+
+struct Decoder<'a> {
+    serde_reg: &'a SerdeRegistry,
+    base64: bool,
+    int128: bool,
+    path: String,
+}
+
+impl Decoder<'_> {
+    fn format(&mut self, value: &mut Value, format: &Format) -> Result<(), ForeignParseError> {
+        match (format, value) {
+            (Format::Bytes, value) => {
+                if self.base64
+                    && let Value::String(text) = value
+                {
+                    *value = bytes::decode(text)
+                        .map_err(|err| ForeignParseError::new(self.path.clone(), err))?;
+                }
+                Ok(())
+            }
+            (Format::I128 | Format::U128, value) => {
+                if self.int128
+                    && let Value::String(text) = value
+                {
+                    let number = int128(format, text)
+                        .map_err(|message| ForeignParseError::new(self.path.clone(), message))?;
+                    *value = Value::Number(number);
+                }
+                Ok(())
+            }
+            (Format::TypeName(name), value) => {
+                #[cfg(feature = "bitflags")]
+                if let Some(flags) = crate::flags::declared(name) {
+                    return flags
+                        .decode(value)
+                        .map_err(|message| ForeignParseError::new(self.path.clone(), message));
+                }
+                match self.serde_reg.get(name) {
+                    Some(container) => self.container(value, name, container),
+                    None => Ok(()),
+                }
+            }
+            (Format::Option(_), Value::Null) => Ok(()),
+            (Format::Option(inner), value) => self.format(value, inner),
+            (
+                Format::Seq(inner) | Format::TupleArray { content: inner, .. },
+                Value::Array(items),
+            ) => items
+                .iter_mut()
+                .enumerate()
+                .try_for_each(|(index, item)| self.nested(&index.to_string(), item, inner)),
+            (Format::Tuple(formats), Value::Array(items)) => self.positions(items, formats),
+            (Format::Map { value: inner, .. }, Value::Object(map)) => map
+                .iter_mut()
+                .try_for_each(|(key, item)| self.nested(key, item, inner)),
+            _ => Ok(()),
+        }
+    }
+
+    fn container(
+        &mut self,
+        value: &mut Value,
+        name: &str,
+        container: &ContainerFormat,
+    ) -> Result<(), ForeignParseError> {
+        match (container, value) {
+            (ContainerFormat::NewTypeStruct(inner), value) => self.format(value, inner),
+            (ContainerFormat::TupleStruct(formats), Value::Array(items)) => {
+                self.positions(items, formats)
+            }
+            (ContainerFormat::Struct(fields), Value::Object(map)) => {
+                self.rename(map, name, fields);
+                self.fields(map, fields)
+            }
+            // Externally tagged, the only layout tracing supports: `{ "Variant": payload }`.
+            (ContainerFormat::Enum(variants), Value::Object(map)) => {
+                for (name, payload) in map.iter_mut() {
+                    if let Some(variant) = variants.values().find(|variant| variant.name == *name) {
+                        self.within(name, |decoder| decoder.variant(payload, &variant.value))?;
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn variant(
+        &mut self,
+        value: &mut Value,
+        variant: &VariantFormat,
+    ) -> Result<(), ForeignParseError> {
+        match (variant, value) {
+            (VariantFormat::NewType(inner), value) => self.format(value, inner),
+            (VariantFormat::Tuple(formats), Value::Array(items)) => self.positions(items, formats),
+            (VariantFormat::Struct(fields), Value::Object(map)) => self.fields(map, fields),
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves renamed fields of the struct container `name` back under their declared names.
+    fn rename(&self, map: &mut Map<String, Value>, name: &str, fields: &[Named<Format>]) {
+        let config = config::current();
+        if !config.key_cases.contains_key(name) {
+            return;
+        }
+        for field in fields {
+            let wire = config.wire_name(name, &field.name);
+            if wire != field.name
+                && let Some(item) = map.remove(wire.as_ref())
+            {
+                map.insert(field.name.clone(), item);
+            }
+        }
+    }
+
+    fn fields(
+        &mut self,
+        map: &mut Map<String, Value>,
+        fields: &[Named<Format>],
+    ) -> Result<(), ForeignParseError> {
+        for field in fields {
+            if let Some(item) = map.get_mut(&field.name) {
+                self.nested(&field.name, item, &field.value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn positions(
+        &mut self,
+        items: &mut [Value],
+        formats: &[Format],
+    ) -> Result<(), ForeignParseError> {
+        formats
+            .iter()
+            .zip(items)
+            .enumerate()
+            .try_for_each(|(index, (format, item))| self.nested(&index.to_string(), item, format))
+    }
+
+    /// Descends into `value` under the pointer segment `segment`.
+    fn nested(
+        &mut self,
+        segment: &str,
+        value: &mut Value,
+        format: &Format,
+    ) -> Result<(), ForeignParseError> {
+        self.within(segment, |decoder| decoder.format(value, format))
+    }
+
+    fn within(
+        &mut self,
+        segment: &str,
+        f: impl FnOnce(&mut Self) -> Result<(), ForeignParseError>,
+    ) -> Result<(), ForeignParseError> {
+        let len = self.path.len();
+        self.path.push('/');
+        self.path.push_str(&escape(segment));
+        let result = f(self);
+        self.path.truncate(len);
+        result
+    }
+}
+
+/// Parses a 128-bit integer written as a string into the number serde_json reads it from.
+///
+/// `Value` only holds 64-bit integers, so larger values are refused here rather than
+/// rounded. They are still written in full.
+fn int128(format: &Format, text: &str) -> Result<Number, String> {
+    let number = if matches!(format, Format::I128) {
+        text.parse::<i128>().ok().map(Number::from_i128)
+    } else {
+        text.parse::<u128>().ok().map(Number::from_u128)
+    };
+    match number {
+        Some(Some(number)) => Ok(number),
+        Some(None) => Err(format!(
+            "{text} is beyond the 64-bit range request bodies can carry"
+        )),
+        None => Err(format!("expected an integer in a string, found \"{text}\"")),
+    }
+}
@@ -0,0 +1,141 @@
+//! A readable account of how a foreign type is documented, for diagnostics.
+
+use std::any::TypeId;
+use std::fmt;
+
+use poem_openapi::registry::Registry;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::format::Format;
+use crate::{Foreign, Resolution, SchemaSource, TRACES};
+
+/// What jsonwrap resolved `Foreign<T>` to, returned by [`Foreign::describe`].
+///
+/// `Display` renders it over a few lines for people; `Debug` keeps it on one line, for logs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForeignDescription {
+    rust_name: String,
+    name: String,
+    kind: String,
+    serde_name: Option<String>,
+    documented_as: Option<String>,
+    components: Vec<String>,
+    cached: bool,
+}
+
+impl ForeignDescription {
+    /// The full Rust path of `T`.
+    pub fn rust_name(&self) -> &str {
+        &self.rust_name
+    }
+
+    /// The name `Foreign<T>` reports to poem-openapi, which is also the component name when
+    /// `T` is registered as one.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How `T` is described: `component`, `inline`, `declared`, `untraced` or `schemars`,
+    /// prefixed with `nullable` or `array of` for the wrappers jsonwrap looks through.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The serde container `T` traced as, if it traced as a named one.
+    pub fn serde_name(&self) -> Option<&str> {
+        self.serde_name.as_deref()
+    }
+
+    /// The container `T` is documented as, which differs from
+    /// [`serde_name`](Self::serde_name) when newtypes are looked through.
+    pub fn documented_as(&self) -> Option<&str> {
+        self.documented_as.as_deref()
+    }
+
+    /// The components registering `T` adds to a spec, sorted.
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /// Whether `T` had been traced before, so that describing it did not trace it.
+    pub fn was_cached(&self) -> bool {
+        self.cached
+    }
+}
+
+impl fmt::Display for ForeignDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "`{}` as `{}` ({})", self.rust_name, self.name, self.kind)?;
+        match (&self.serde_name, &self.documented_as) {
+            (Some(serde_name), Some(container)) if serde_name != container => writeln!(
+                f,
+                "  serde container `{serde_name}` is transparent, documented as `{container}`"
+            )?,
+            (Some(serde_name), _) => writeln!(f, "  serde container `{serde_name}`")?,
+            (None, _) => {}
+        }
+        if self.components.is_empty() {
+            writeln!(f, "  components: none")?;
+        } else {
+            writeln!(f, "  components: {}", self.components.join(", "))?;
+        }
+        let cache = if self.cached { "cached" } else { "traced now" };
+        write!(f, "  trace: {cache}")
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> Foreign<T> {
+    /// Describes the name, schema kind and components jsonwrap resolves `T` to, including the
+    /// newtypes it looked through, for example to log at startup next to the spec.
+    ///
+    /// It traces `T` if needed, so it is also a way to warm the trace cache.
+    pub fn describe() -> ForeignDescription {
+        let cached = TRACES
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&TypeId::of::<T>());
+        let resolution = Resolution::of::<T>();
+        let mut scratch = Registry::new();
+        Self::register_components(Resolution::of::<T>(), &mut scratch);
+
+        let mut kind = String::new();
+        let mut innermost = &resolution;
+        loop {
+            match innermost {
+                Resolution::Nullable(inner) => {
+                    kind.push_str("nullable ");
+                    innermost = inner;
+                }
+                Resolution::Array { items, .. } => {
+                    kind.push_str("array of ");
+                    innermost = items;
+                }
+                _ => break,
+            }
+        }
+        let (base, documented_as) = match innermost {
+            Resolution::Component { container, .. } => ("component", Some(container.clone())),
+            Resolution::Inline { .. } => ("inline", None),
+            Resolution::Declared { .. } => ("declared", None),
+            Resolution::Untraced { .. } => ("untraced", None),
+            #[cfg(feature = "schemars")]
+            Resolution::Schemars { .. } => ("schemars", None),
+            Resolution::Nullable(_) | Resolution::Array { .. } => unreachable!(),
+        };
+        kind.push_str(base);
+        let serde_name = innermost.trace().and_then(|trace| match trace.root() {
+            Format::TypeName(name) => Some(name.clone()),
+            _ => None,
+        });
+
+        ForeignDescription {
+            rust_name: std::any::type_name::<T>().to_string(),
+            name: resolution.name().to_string(),
+            kind,
+            serde_name,
+            documented_as,
+            components: scratch.schemas.into_keys().collect(),
+            cached,
+        }
+    }
+}
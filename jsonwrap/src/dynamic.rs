@@ -0,0 +1,57 @@
+//! Output-only payloads whose type is only known at runtime.
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use poem_openapi::types::{ToJSON, Type};
+use serde_json::Value;
+
+use crate::ser;
+
+/// A response body serialized through `erased_serde`, for handlers that pick the payload
+/// type at runtime.
+///
+/// This is a separate wrapper because `Foreign<Box<dyn erased_serde::Serialize>>` would
+/// overlap with the `Foreign<T>` impls. The schema is an arbitrary JSON value, since
+/// nothing is known about the payload's shape, and it cannot be parsed from requests.
+pub struct ForeignDyn(pub Box<dyn erased_serde::Serialize + Send + Sync>);
+
+impl ForeignDyn {
+    pub fn new(value: impl serde::Serialize + Send + Sync + 'static) -> Self {
+        Self(Box::new(value))
+    }
+}
+
+impl Type for ForeignDyn {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> std::borrow::Cow<'static, str> {
+        "any".into()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Inline(Box::new(MetaSchema::ANY))
+    }
+
+    fn register(_: &mut Registry) {}
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl ToJSON for ForeignDyn {
+    fn to_json(&self) -> Option<Value> {
+        ser::to_value(&self.0, ser::Options::default())
+            .inspect_err(|err| {
+                tracing::error!("jsonwrap: failed to serialize a dynamic payload: {err}")
+            })
+            .ok()
+    }
+}
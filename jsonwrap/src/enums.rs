@@ -0,0 +1,358 @@
+//! Schemas for enums that serde_reflection cannot trace.
+//!
+//! Internally tagged, adjacently tagged and untagged enums deserialize through
+//! `deserialize_any`, which tracing does not support. For those, the schema is built from
+//! sample values registered with [`ForeignConfig::enum_samples`](crate::ForeignConfig::enum_samples).
+
+use std::collections::BTreeMap;
+
+use poem_openapi::registry::{MetaDiscriminatorObject, MetaSchema, MetaSchemaRef, Registry};
+use serde_json::Value;
+
+use crate::leak_str;
+
+/// How an enum is laid out on the wire, mirroring serde's tagging attributes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnumRepr {
+    /// serde's default: `"Unit"` or `{ "Variant": payload }`.
+    External,
+    /// `#[serde(tag = "...")]`: the variant name is a field of the payload object.
+    Internal { tag: String },
+    /// `#[serde(tag = "...", content = "...")]`: `{ tag: "Variant", content: payload }`.
+    Adjacent { tag: String, content: String },
+    /// `#[serde(untagged)]`: just the payload.
+    Untagged,
+}
+
+impl EnumRepr {
+    /// Infers the representation from serialized samples of an enum.
+    ///
+    /// Returns `None` when there are no samples. Samples that fit none of the tagged
+    /// layouts are reported as [`EnumRepr::Untagged`].
+    pub fn infer(samples: &[Value]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let objects: Vec<_> = samples.iter().filter_map(Value::as_object).collect();
+        if objects.len() < samples.len() {
+            // Bare strings are external unit variants; any other non-object is untagged.
+            let external = samples.iter().all(|sample| match sample {
+                Value::String(_) => true,
+                Value::Object(map) => map.len() == 1,
+                _ => false,
+            });
+            return Some(if external {
+                EnumRepr::External
+            } else {
+                EnumRepr::Untagged
+            });
+        }
+
+        // `{ "Variant": payload }` objects: the key changes with the variant, whereas a lone
+        // tag key stays the same and holds different variant names.
+        if objects.iter().all(|object| object.len() == 1) {
+            let (key, _) = objects[0].iter().next()?;
+            let mut names: Vec<_> = objects
+                .iter()
+                .map(|object| object.get(key).and_then(Value::as_str))
+                .collect();
+            names.sort();
+            names.dedup();
+            let lone_tag = names.len() > 1 && names.iter().all(Option::is_some);
+            return Some(if lone_tag {
+                EnumRepr::Internal { tag: key.clone() }
+            } else {
+                EnumRepr::External
+            });
+        }
+
+        // A tag is a key that every sample carries with a string value.
+        let mut tags: Vec<&String> = objects[0]
+            .iter()
+            .filter(|(key, _)| {
+                objects
+                    .iter()
+                    .all(|object| object.get(*key).is_some_and(Value::is_string))
+            })
+            .map(|(key, _)| key)
+            .collect();
+        // Prefer the key that tells the most samples apart.
+        tags.sort_by_key(|key| {
+            let mut values: Vec<_> = objects
+                .iter()
+                .map(|object| object[key.as_str()].as_str())
+                .collect();
+            values.sort();
+            values.dedup();
+            (std::cmp::Reverse(values.len()), key.as_str())
+        });
+
+        let Some(tag) = tags.first() else {
+            return Some(EnumRepr::Untagged);
+        };
+
+        // Adjacent tagging: besides the tag, samples have at most one other key, and it is
+        // the same key in every sample that has one.
+        let mut others = objects
+            .iter()
+            .flat_map(|object| object.keys().filter(|key| key != tag));
+        if let Some(content) = others.next()
+            && others.all(|key| key == content)
+            && objects.iter().all(|object| object.len() <= 2)
+        {
+            return Some(EnumRepr::Adjacent {
+                tag: tag.to_string(),
+                content: content.clone(),
+            });
+        }
+
+        Some(EnumRepr::Internal {
+            tag: tag.to_string(),
+        })
+    }
+
+    /// The name of the variant `sample` was serialized from, if the layout records it.
+    fn variant_of<'a>(&self, sample: &'a Value) -> Option<&'a str> {
+        match (self, sample) {
+            (EnumRepr::External, Value::String(name)) => Some(name),
+            (EnumRepr::External, Value::Object(map)) => map.keys().next().map(String::as_str),
+            (EnumRepr::Internal { tag } | EnumRepr::Adjacent { tag, .. }, Value::Object(map)) => {
+                map.get(tag).and_then(Value::as_str)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Builds the schema of the enum `name` from samples.
+///
+/// Internally and adjacently tagged enums become a `oneOf` of one component per variant,
+/// with a `discriminator` on the tag. Other layouts get a plain `oneOf` of the observed
+/// shapes: `{ "Variant": payload }` objects for external tagging, the bare payloads for
+/// untagged enums, whose unit variants make the schema nullable.
+pub(crate) fn sample_schema(
+    name: &str,
+    repr: &EnumRepr,
+    samples: &[Value],
+    poem_reg: &mut Registry,
+) -> MetaSchema {
+    let mut variants: BTreeMap<String, MetaSchema> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for sample in samples {
+        check_keys(name, repr, sample);
+        let schema = shape_schema(sample);
+        match repr.variant_of(sample) {
+            Some(variant) => match variants.remove(variant) {
+                Some(seen) => {
+                    variants.insert(variant.to_string(), merge_shapes(seen, schema));
+                }
+                None => {
+                    variants.insert(variant.to_string(), schema);
+                }
+            },
+            None => untagged.push(schema),
+        }
+    }
+
+    if let EnumRepr::Internal { tag } | EnumRepr::Adjacent { tag, .. } = repr {
+        return tagged_schema(name, tag, variants, poem_reg);
+    }
+
+    let mut branches: Vec<MetaSchema> = variants.into_values().collect();
+    // Untagged unit variants are written as `null`. OpenAPI 3.0 has no null schema to list
+    // as a branch, so they make the whole enum nullable instead.
+    let mut nullable = false;
+    for schema in untagged {
+        if schema.nullable && schema.ty.is_empty() && schema.properties.is_empty() {
+            nullable = true;
+        } else if !branches.contains(&schema) {
+            branches.push(schema);
+        }
+    }
+    MetaSchema {
+        nullable,
+        one_of: branches
+            .into_iter()
+            .map(|schema| MetaSchemaRef::Inline(Box::new(schema)))
+            .collect(),
+        ..MetaSchema::ANY
+    }
+}
+
+/// Panics if `sample` does not have the keys a stated tagging requires, as such samples
+/// would otherwise be left out of the schema or documented under the wrong property.
+fn check_keys(name: &str, repr: &EnumRepr, sample: &Value) {
+    let (tag, content) = match repr {
+        EnumRepr::Internal { tag } => (tag, None),
+        EnumRepr::Adjacent { tag, content } => (tag, Some(content)),
+        EnumRepr::External | EnumRepr::Untagged => return,
+    };
+    let keys = sample.as_object().map(|map| map.keys());
+    let stray = match (keys, content) {
+        (None, _) => Some(sample.to_string()),
+        (Some(_), _) if sample.get(tag).is_none_or(|value| !value.is_string()) => {
+            Some(format!("{sample} without a `{tag}` tag"))
+        }
+        (Some(mut keys), Some(content)) => keys
+            .find(|key| *key != tag && *key != content)
+            .map(|key| format!("{sample}, whose `{key}` is neither the tag nor the content")),
+        (Some(_), None) => None,
+    };
+    if let Some(stray) = stray {
+        panic!("jsonwrap: `{name}` is declared as {repr:?} but has a sample {stray}");
+    }
+}
+
+/// Registers each variant of a tagged enum as `{name}_{variant}` and joins them in a `oneOf`
+/// discriminated by `tag`.
+///
+/// Discriminators only consider referenced schemas, hence the per-variant components. For
+/// adjacent tagging the content property comes from the samples like any other field, and
+/// is absent from unit variants just as serde leaves it out.
+fn tagged_schema(
+    name: &str,
+    tag: &str,
+    variants: BTreeMap<String, MetaSchema>,
+    poem_reg: &mut Registry,
+) -> MetaSchema {
+    let tag = leak_str(tag);
+    let mut one_of = Vec::new();
+    let mut mapping = Vec::new();
+    for (variant, mut schema) in variants {
+        // The tag holds exactly this variant's name.
+        let tag_schema = MetaSchemaRef::Inline(Box::new(MetaSchema {
+            enum_items: vec![Value::String(variant.clone())],
+            ..MetaSchema::new("string")
+        }));
+        // Listed first, ahead of the sampled fields.
+        schema.properties.retain(|(key, _)| *key != tag);
+        schema.properties.insert(0, (tag, tag_schema));
+        schema.required.retain(|key| *key != tag);
+        schema.required.insert(0, tag);
+
+        let component = format!("{name}_{}", component_suffix(&variant));
+        poem_reg.create_schema::<(), _>(component.clone(), |_| schema);
+        mapping.push((variant, format!("#/components/schemas/{component}")));
+        one_of.push(MetaSchemaRef::Reference(component));
+    }
+    MetaSchema {
+        one_of,
+        discriminator: Some(MetaDiscriminatorObject {
+            property_name: tag,
+            mapping,
+        }),
+        ..MetaSchema::ANY
+    }
+}
+
+/// Replaces characters that component names may not contain.
+fn component_suffix(variant: &str) -> String {
+    variant
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Describes the JSON shape of a single value.
+fn shape_schema(value: &Value) -> MetaSchema {
+    match value {
+        Value::Null => MetaSchema {
+            nullable: true,
+            ..MetaSchema::ANY
+        },
+        Value::Bool(_) => MetaSchema::new("boolean"),
+        Value::Number(number) if number.is_f64() => MetaSchema::new("number"),
+        Value::Number(_) => MetaSchema::new("integer"),
+        Value::String(_) => MetaSchema::new("string"),
+        Value::Array(items) => MetaSchema {
+            ty: "array",
+            items: items
+                .iter()
+                .map(shape_schema)
+                .reduce(merge_shapes)
+                .map(|items| Box::new(MetaSchemaRef::Inline(Box::new(items)))),
+            ..MetaSchema::ANY
+        },
+        Value::Object(map) => MetaSchema {
+            ty: "object",
+            required: map.keys().map(|key| leak_str(key)).collect(),
+            properties: map
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        leak_str(key),
+                        MetaSchemaRef::Inline(Box::new(shape_schema(value))),
+                    )
+                })
+                .collect(),
+            ..MetaSchema::ANY
+        },
+    }
+}
+
+/// Combines the shapes of two samples of the same variant.
+///
+/// Properties seen in only one sample stay optional, `null` makes the other shape nullable
+/// and an integer seen next to a float widens to `number`.
+fn merge_shapes(a: MetaSchema, b: MetaSchema) -> MetaSchema {
+    match (a.ty, b.ty) {
+        ("", _) if a.nullable && a.properties.is_empty() => MetaSchema {
+            nullable: true,
+            ..b
+        },
+        (_, "") if b.nullable && b.properties.is_empty() => MetaSchema {
+            nullable: true,
+            ..a
+        },
+        ("integer", "number") | ("number", "integer") => MetaSchema::new("number"),
+        ("object", "object") => {
+            let mut properties = a.properties;
+            for (key, schema) in b.properties {
+                match properties.iter_mut().find(|(name, _)| *name == key) {
+                    Some((_, existing)) => {
+                        if let (MetaSchemaRef::Inline(x), MetaSchemaRef::Inline(y)) =
+                            (existing.clone(), schema)
+                        {
+                            *existing = MetaSchemaRef::Inline(Box::new(merge_shapes(*x, *y)));
+                        }
+                    }
+                    None => properties.push((key, schema)),
+                }
+            }
+            MetaSchema {
+                ty: "object",
+                required: a
+                    .required
+                    .into_iter()
+                    .filter(|key| b.required.contains(key))
+                    .collect(),
+                properties,
+                nullable: a.nullable || b.nullable,
+                ..MetaSchema::ANY
+            }
+        }
+        ("array", "array") => MetaSchema {
+            ty: "array",
+            items: match (a.items, b.items) {
+                (Some(x), Some(y)) => match (*x, *y) {
+                    (MetaSchemaRef::Inline(x), MetaSchemaRef::Inline(y)) => Some(Box::new(
+                        MetaSchemaRef::Inline(Box::new(merge_shapes(*x, *y))),
+                    )),
+                    (x, _) => Some(Box::new(x)),
+                },
+                (x, y) => x.or(y),
+            },
+            nullable: a.nullable || b.nullable,
+            ..MetaSchema::ANY
+        },
+        (x, y) if x == y => MetaSchema {
+            nullable: a.nullable || b.nullable,
+            ..a
+        },
+        _ => MetaSchema::ANY,
+    }
+}
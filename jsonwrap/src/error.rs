@@ -0,0 +1,81 @@
+//! The error returned when a request body does not parse as a foreign type.
+
+use std::fmt;
+
+use poem::error::ResponseError;
+use poem::http::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::Segment;
+
+use crate::validate::escape;
+
+/// Why a JSON value could not be parsed as `Foreign<T>`, and where in the value.
+///
+/// Responds with `400 Bad Request` when returned from a handler. As the reason of a failed
+/// `Json<Foreign<T>>` payload, its message becomes the body of poem's own 400 response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForeignParseError {
+    path: String,
+    message: String,
+}
+
+impl ForeignParseError {
+    pub(crate) fn new(path: String, message: impl fmt::Display) -> Self {
+        Self {
+            path,
+            message: message.to_string(),
+        }
+    }
+
+    /// The JSON pointer to the offending value, such as `/items/0/price`; empty for the
+    /// whole body.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// What serde or the enforced constraints reported, without the location.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ForeignParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ForeignParseError {}
+
+impl ResponseError for ForeignParseError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// Deserializes `value`, locating the failure on error.
+pub(crate) fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, ForeignParseError> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = pointer(err.path());
+        ForeignParseError::new(path, err.into_inner())
+    })
+}
+
+/// The JSON pointer of `path`. A variant of an externally tagged enum is the key it is
+/// written under; segments serde_path_to_error could not name are left out.
+fn pointer(path: &serde_path_to_error::Path) -> String {
+    path.iter()
+        .filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(index.to_string()),
+            Segment::Map { key } => Some(escape(key)),
+            Segment::Enum { variant } => Some(escape(variant)),
+            Segment::Unknown => None,
+        })
+        .map(|segment| format!("/{segment}"))
+        .collect()
+}
@@ -0,0 +1,175 @@
+//! Flags types from `bitflags`, written as an integer or as a list of flag names.
+//!
+//! bitflags' serde support writes a flags value to JSON as text, `"READ | WRITE"`, and to
+//! compact formats as its bits, which is what tracing sees. Neither says which flags exist.
+//! A type declared with [`ForeignConfig::bitflags`](crate::ForeignConfig::bitflags) is
+//! described and written in the chosen [`FlagsRepr`] instead, and converted back to text
+//! before its `Deserialize` impl sees a request body.
+
+use bitflags::Flags;
+use bitflags::parser::{self, ParseHex, WriteHex};
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use serde::de::{Deserialize, DeserializeOwned};
+use serde::ser::Serialize;
+use serde_json::Value;
+
+use crate::{Trace, config};
+
+/// How a flags type declared with [`ForeignConfig::bitflags`](crate::ForeignConfig::bitflags)
+/// is written on the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagsRepr {
+    /// Its bits, as an integer. The schema's `description` lists the value of each flag.
+    Integer,
+    /// The names of the flags it contains, as an array of strings. Bits without a name are
+    /// dropped when writing.
+    Names,
+}
+
+/// A flags type as declared in the config, with conversions between bitflags' text form and
+/// the declared one.
+#[derive(Clone, Debug)]
+pub(crate) struct DeclaredFlags {
+    repr: FlagsRepr,
+    /// The named flags and their bits, in declaration order.
+    flags: Vec<(&'static str, Value)>,
+    format: Option<&'static str>,
+    encode: fn(&str, FlagsRepr) -> Option<Value>,
+    decode: fn(&Value, FlagsRepr) -> Result<String, String>,
+}
+
+impl DeclaredFlags {
+    pub(crate) fn new<F>(repr: FlagsRepr) -> Self
+    where
+        F: Flags,
+        F::Bits: ParseHex + WriteHex + Serialize + DeserializeOwned,
+    {
+        let flags = F::FLAGS
+            .iter()
+            .filter(|flag| !flag.is_unnamed())
+            .filter_map(|flag| Some((flag.name(), serde_json::to_value(flag.value().bits()).ok()?)))
+            .collect();
+        let format = match std::mem::size_of::<F::Bits>() {
+            1 | 2 => Some("int32"),
+            4 | 8 => Some("int64"),
+            _ => None,
+        };
+        Self {
+            repr,
+            flags,
+            format,
+            encode: text_to_wire::<F>,
+            decode: wire_to_text::<F>,
+        }
+    }
+
+    pub(crate) fn schema(&self) -> MetaSchemaRef {
+        let schema = match self.repr {
+            // poem-openapi cannot emit vendor extensions such as `x-flag-values`, so the
+            // flags are listed in the description instead.
+            FlagsRepr::Integer => {
+                let flags = self
+                    .flags
+                    .iter()
+                    .map(|(name, bits)| format!("`{name}` = {bits}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                MetaSchema {
+                    format: self.format,
+                    minimum: Some(0.0),
+                    description: Some(crate::leak_str(&format!(
+                        "Bit flags, combined with bitwise or: {flags}."
+                    ))),
+                    ..MetaSchema::new("integer")
+                }
+            }
+            FlagsRepr::Names => MetaSchema {
+                items: Some(Box::new(MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    enum_items: self
+                        .flags
+                        .iter()
+                        .map(|(name, _)| Value::from(*name))
+                        .collect(),
+                    ..MetaSchema::new("string")
+                })))),
+                unique_items: Some(true),
+                ..MetaSchema::new("array")
+            },
+        };
+        MetaSchemaRef::Inline(Box::new(schema))
+    }
+
+    /// Rewrites the text bitflags serialized into the declared representation.
+    pub(crate) fn encode(&self, value: Value) -> Value {
+        match &value {
+            Value::String(text) => (self.encode)(text, self.repr).unwrap_or(value),
+            _ => value,
+        }
+    }
+
+    /// Rewrites a value in the declared representation into the text bitflags parses.
+    pub(crate) fn decode(&self, value: &mut Value) -> Result<(), String> {
+        *value = Value::String((self.decode)(value, self.repr)?);
+        Ok(())
+    }
+}
+
+fn text_to_wire<F: Flags>(text: &str, repr: FlagsRepr) -> Option<Value>
+where
+    F::Bits: ParseHex + Serialize,
+{
+    let flags = parser::from_str::<F>(text).ok()?;
+    match repr {
+        FlagsRepr::Integer => serde_json::to_value(flags.bits()).ok(),
+        FlagsRepr::Names => Some(
+            flags
+                .iter_names()
+                .map(|(name, _)| Value::from(name))
+                .collect(),
+        ),
+    }
+}
+
+fn wire_to_text<F: Flags>(value: &Value, repr: FlagsRepr) -> Result<String, String>
+where
+    F::Bits: WriteHex + DeserializeOwned,
+{
+    let flags = match (repr, value) {
+        (FlagsRepr::Integer, value) => {
+            F::from_bits_retain(F::Bits::deserialize(value).map_err(|err| err.to_string())?)
+        }
+        (FlagsRepr::Names, Value::Array(names)) => {
+            names.iter().try_fold(F::empty(), |flags, name| {
+                let name = name
+                    .as_str()
+                    .ok_or_else(|| format!("expected a flag name, found {name}"))?;
+                F::from_name(name)
+                    .map(|flag| flags.union(flag))
+                    .ok_or_else(|| format!("unknown flag `{name}`"))
+            })?
+        }
+        (FlagsRepr::Names, value) => {
+            return Err(format!("expected an array of flag names, found {value}"));
+        }
+    };
+    let mut text = String::new();
+    parser::to_writer(&flags, &mut text).map_err(|err| err.to_string())?;
+    Ok(text)
+}
+
+/// The flags type declared under the container name `name`, if any.
+pub(crate) fn declared(name: &str) -> Option<&'static DeclaredFlags> {
+    config::current().flags.get(name)
+}
+
+/// Whether a value of the type whose full path is `full`, traced as `trace`, is or contains a
+/// declared flags type.
+pub(crate) fn within(trace: &Trace, full: &str) -> bool {
+    let config = config::current();
+    let flags = &config.flags;
+    !flags.is_empty()
+        && (config
+            .declared_key(full)
+            .is_some_and(|key| flags.contains_key(key))
+            || trace.registry().keys().any(|name| flags.contains_key(name)))
+}
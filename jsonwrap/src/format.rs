@@ -0,0 +1,258 @@
+//! The formats of a finished trace, in a form that can be shared between threads.
+//!
+//! serde_reflection's formats keep the parts that are still being traced in variables, which
+//! hold an `Rc`. A finished trace has no use for them, so [`Trace`](crate::Trace) converts the
+//! formats it is built from into these copies, which have no variables and are `Send` and
+//! `Sync` by construction. The conversion fails on a format that is still unknown.
+//!
+//! The copies read the JSON serde_reflection writes for its own formats, so a trace saved by
+//! another build can be loaded without the `trace` feature.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::de::{self, Deserializer};
+#[cfg(feature = "trace")]
+use serde_reflection::{FormatHolder, Result};
+
+/// A copy of serde_reflection's `Format` without `Variable`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub(crate) enum Format {
+    TypeName(String),
+    Unit,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    Bytes,
+    Option(Box<Format>),
+    Seq(Box<Format>),
+    #[serde(rename_all = "UPPERCASE")]
+    Map {
+        key: Box<Format>,
+        value: Box<Format>,
+    },
+    Tuple(Vec<Format>),
+    #[serde(rename_all = "UPPERCASE")]
+    TupleArray {
+        content: Box<Format>,
+        size: usize,
+    },
+}
+
+/// A copy of serde_reflection's `ContainerFormat`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub(crate) enum ContainerFormat {
+    UnitStruct,
+    NewTypeStruct(Box<Format>),
+    TupleStruct(Vec<Format>),
+    Struct(Vec<Named<Format>>),
+    Enum(BTreeMap<u32, Named<VariantFormat>>),
+}
+
+/// A copy of serde_reflection's `VariantFormat` without `Variable`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub(crate) enum VariantFormat {
+    Unit,
+    NewType(Box<Format>),
+    Tuple(Vec<Format>),
+    Struct(Vec<Named<Format>>),
+}
+
+/// A copy of serde_reflection's `Named`: a field or variant and its name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Named<T> {
+    pub(crate) name: String,
+    pub(crate) value: T,
+}
+
+/// Read from a map with the name as its only key, as serde_reflection writes it.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Named<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let mut entries = BTreeMap::<String, T>::deserialize(deserializer)?.into_iter();
+        match (entries.next(), entries.next()) {
+            (Some((name, value)), None) => Ok(Named { name, value }),
+            _ => Err(de::Error::custom("expected a map with exactly one entry")),
+        }
+    }
+}
+
+/// The named containers of a trace.
+pub(crate) type Registry = BTreeMap<String, ContainerFormat>;
+
+impl Format {
+    /// Copies a traced format, resolving the variables it holds.
+    #[cfg(feature = "trace")]
+    pub(crate) fn from_traced(mut format: serde_reflection::Format) -> Result<Self> {
+        format.normalize()?;
+        Ok(Self::copy(format))
+    }
+
+    /// Copies a normalized format.
+    #[cfg(feature = "trace")]
+    fn copy(format: serde_reflection::Format) -> Self {
+        use serde_reflection::Format as Traced;
+        match format {
+            // `normalize` fails on a variable left unknown and replaces the others.
+            Traced::Variable(_) => unreachable!("normalized formats hold no variables"),
+            Traced::TypeName(name) => Format::TypeName(name),
+            Traced::Unit => Format::Unit,
+            Traced::Bool => Format::Bool,
+            Traced::I8 => Format::I8,
+            Traced::I16 => Format::I16,
+            Traced::I32 => Format::I32,
+            Traced::I64 => Format::I64,
+            Traced::I128 => Format::I128,
+            Traced::U8 => Format::U8,
+            Traced::U16 => Format::U16,
+            Traced::U32 => Format::U32,
+            Traced::U64 => Format::U64,
+            Traced::U128 => Format::U128,
+            Traced::F32 => Format::F32,
+            Traced::F64 => Format::F64,
+            Traced::Char => Format::Char,
+            Traced::Str => Format::Str,
+            Traced::Bytes => Format::Bytes,
+            Traced::Option(inner) => Format::Option(Box::new(Self::copy(*inner))),
+            Traced::Seq(inner) => Format::Seq(Box::new(Self::copy(*inner))),
+            Traced::Map { key, value } => Format::Map {
+                key: Box::new(Self::copy(*key)),
+                value: Box::new(Self::copy(*value)),
+            },
+            Traced::Tuple(formats) => Format::Tuple(copy_all(formats)),
+            Traced::TupleArray { content, size } => Format::TupleArray {
+                content: Box::new(Self::copy(*content)),
+                size,
+            },
+        }
+    }
+
+    /// Calls `f` on this format and every format nested in it, outermost first.
+    pub(crate) fn visit(&self, f: &mut impl FnMut(&Format)) {
+        f(self);
+        match self {
+            Format::Option(inner) | Format::Seq(inner) => inner.visit(f),
+            Format::Map { key, value } => {
+                key.visit(f);
+                value.visit(f);
+            }
+            Format::Tuple(formats) => formats.iter().for_each(|format| format.visit(f)),
+            Format::TupleArray { content, .. } => content.visit(f),
+            _ => {}
+        }
+    }
+}
+
+impl ContainerFormat {
+    /// Copies a traced container, resolving the variables it holds.
+    #[cfg(feature = "trace")]
+    pub(crate) fn from_traced(mut container: serde_reflection::ContainerFormat) -> Result<Self> {
+        use serde_reflection::ContainerFormat as Traced;
+        container.normalize()?;
+        Ok(match container {
+            Traced::UnitStruct => ContainerFormat::UnitStruct,
+            Traced::NewTypeStruct(inner) => {
+                ContainerFormat::NewTypeStruct(Box::new(Format::copy(*inner)))
+            }
+            Traced::TupleStruct(formats) => ContainerFormat::TupleStruct(copy_all(formats)),
+            Traced::Struct(fields) => ContainerFormat::Struct(copy_fields(fields)),
+            Traced::Enum(variants) => ContainerFormat::Enum(
+                variants
+                    .into_iter()
+                    .map(|(index, variant)| {
+                        let value = VariantFormat::copy(variant.value);
+                        (
+                            index,
+                            Named {
+                                name: variant.name,
+                                value,
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Calls `f` on every format nested in this container.
+    pub(crate) fn visit(&self, f: &mut impl FnMut(&Format)) {
+        match self {
+            ContainerFormat::UnitStruct => {}
+            ContainerFormat::NewTypeStruct(inner) => inner.visit(f),
+            ContainerFormat::TupleStruct(formats) => {
+                formats.iter().for_each(|format| format.visit(f))
+            }
+            ContainerFormat::Struct(fields) => fields.iter().for_each(|field| field.value.visit(f)),
+            ContainerFormat::Enum(variants) => {
+                for variant in variants.values() {
+                    match &variant.value {
+                        VariantFormat::Unit => {}
+                        VariantFormat::NewType(inner) => inner.visit(f),
+                        VariantFormat::Tuple(formats) => {
+                            formats.iter().for_each(|format| format.visit(f))
+                        }
+                        VariantFormat::Struct(fields) => {
+                            fields.iter().for_each(|field| field.value.visit(f))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl VariantFormat {
+    /// Copies a normalized variant.
+    #[cfg(feature = "trace")]
+    fn copy(variant: serde_reflection::VariantFormat) -> Self {
+        use serde_reflection::VariantFormat as Traced;
+        match variant {
+            Traced::Variable(_) => unreachable!("normalized formats hold no variables"),
+            Traced::Unit => VariantFormat::Unit,
+            Traced::NewType(inner) => VariantFormat::NewType(Box::new(Format::copy(*inner))),
+            Traced::Tuple(formats) => VariantFormat::Tuple(copy_all(formats)),
+            Traced::Struct(fields) => VariantFormat::Struct(copy_fields(fields)),
+        }
+    }
+}
+
+/// Copies every container of a traced registry.
+#[cfg(feature = "trace")]
+pub(crate) fn registry_from_traced(registry: serde_reflection::Registry) -> Result<Registry> {
+    registry
+        .into_iter()
+        .map(|(name, container)| Ok((name, ContainerFormat::from_traced(container)?)))
+        .collect()
+}
+
+#[cfg(feature = "trace")]
+fn copy_all(formats: Vec<serde_reflection::Format>) -> Vec<Format> {
+    formats.into_iter().map(Format::copy).collect()
+}
+
+#[cfg(feature = "trace")]
+fn copy_fields(
+    fields: Vec<serde_reflection::Named<serde_reflection::Format>>,
+) -> Vec<Named<Format>> {
+    fields
+        .into_iter()
+        .map(|field| Named {
+            name: field.name,
+            value: Format::copy(field.value),
+        })
+        .collect()
+}
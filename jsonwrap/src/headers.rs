@@ -0,0 +1,133 @@
+//! Response headers documented and set from the fields of a foreign struct.
+
+use poem::http::{HeaderName, HeaderValue};
+use poem::{IntoResponse, Response};
+use poem_openapi::ApiResponse;
+use poem_openapi::registry::{MetaHeader, MetaResponses, Registry};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::config;
+use crate::format::{ContainerFormat, Format};
+use crate::param::param_kind;
+use crate::{SchemaSource, foreign_to_json, format_to_schema, trace_type, type_name};
+
+/// A response `R` with headers taken from the fields of the foreign struct `M`.
+///
+/// Each field of `M` is a header named as the field is serialized, so
+/// `#[serde(rename_all = "kebab-case")]` or `#[serde(rename = "X-Request-Id")]` pick the
+/// header names. The headers are documented on every response of `R` with the schema of
+/// their field, and set from the serialized `M` when the response is written. `Option`
+/// fields are optional headers, left out when `None`.
+///
+/// Fields have to serialize to a string, a number or a bool; registering `M` panics
+/// otherwise, as does an `M` that cannot be traced. Requests that fail to parse are
+/// answered by poem's default error response, without the headers.
+///
+/// ```ignore
+/// async fn get(&self) -> WithMetaHeaders<Json<Foreign<User>>, ResponseMeta> { ... }
+/// ```
+pub struct WithMetaHeaders<R, M>(pub R, pub M);
+
+/// The headers of `M`, with their schemas registered in `poem_reg`.
+fn headers<M: DeserializeOwned + 'static>(poem_reg: &mut Registry) -> Vec<MetaHeader> {
+    let name = type_name::<M>();
+    let Some(trace) = trace_type::<M>() else {
+        panic!("jsonwrap: `{name}` could not be traced, so it cannot describe headers");
+    };
+    let (container, fields) = match trace.root() {
+        Format::TypeName(container) => match trace.registry().get(container) {
+            Some(ContainerFormat::Struct(fields)) => (container, fields),
+            _ => panic!("jsonwrap: `{name}` describes headers, so it has to be a struct"),
+        },
+        _ => panic!("jsonwrap: `{name}` describes headers, so it has to be a struct"),
+    };
+    fields
+        .iter()
+        .map(|field| {
+            if !param_kind(&field.value, trace.registry()).is_scalar() {
+                panic!(
+                    "jsonwrap: the header `{}` of `{name}` does not serialize to a string, \
+                     a number or a bool",
+                    field.name
+                );
+            }
+            // A `None` leaves the header out rather than writing a null.
+            let (format, required) = match &field.value {
+                Format::Option(inner) => (inner.as_ref(), false),
+                format => (format, true),
+            };
+            MetaHeader {
+                name: config::current()
+                    .wire_name(container, &field.name)
+                    .into_owned(),
+                description: None,
+                required,
+                deprecated: false,
+                schema: format_to_schema(format, trace.registry(), poem_reg),
+            }
+        })
+        .collect()
+}
+
+impl<R, M> ApiResponse for WithMetaHeaders<R, M>
+where
+    R: ApiResponse,
+    M: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static,
+{
+    fn meta() -> MetaResponses {
+        // The schemas are registered for real by `register`.
+        let headers: Vec<MetaHeader> = headers::<M>(&mut Registry::new());
+        let mut meta = R::meta();
+        for response in &mut meta.responses {
+            for header in &headers {
+                response.headers.push(MetaHeader {
+                    name: header.name.clone(),
+                    description: header.description.clone(),
+                    required: header.required,
+                    deprecated: header.deprecated,
+                    schema: header.schema.clone(),
+                });
+            }
+        }
+        meta
+    }
+
+    fn register(registry: &mut Registry) {
+        R::register(registry);
+        headers::<M>(registry);
+    }
+}
+
+impl<R, M> IntoResponse for WithMetaHeaders<R, M>
+where
+    R: IntoResponse,
+    M: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static,
+{
+    fn into_response(self) -> Response {
+        let mut response = self.0.into_response();
+        let Some(Value::Object(fields)) = foreign_to_json(&self.1) else {
+            return response;
+        };
+        for (name, value) in fields {
+            let text = match value {
+                Value::Null => continue,
+                Value::String(text) => text,
+                value => value.to_string(),
+            };
+            match (
+                HeaderName::try_from(name.as_str()),
+                HeaderValue::try_from(text),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    response.headers_mut().insert(name, value);
+                }
+                _ => tracing::error!(
+                    "jsonwrap: `{}` has a header `{name}` that cannot be written",
+                    type_name::<M>()
+                ),
+            }
+        }
+        response
+    }
+}
@@ -0,0 +1,194 @@
+//! Schemas taken from `schemars::JsonSchema` impls instead of tracing.
+//!
+//! A type declared with [`ForeignConfig::schemars`](crate::ForeignConfig::schemars) is
+//! described by schemars' OpenAPI 3.0 output, which keeps doc comments, formats and
+//! validation attributes that tracing cannot see. Its definitions become components and the
+//! common keywords are converted to `MetaSchema` fields; anything else is dropped and
+//! logged as a warning.
+
+use std::collections::BTreeSet;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde_json::{Map, Value};
+
+use crate::leak_str;
+
+const REF_PREFIX: &str = "#/components/schemas/";
+
+/// Registers the components of `T`'s schemars schema and returns the schema of `T` itself.
+///
+/// Unless `T` inlines its schema, it is registered as a component under its schemars name
+/// and referenced, like a traced container.
+pub(crate) fn register<T: JsonSchema>(poem_reg: &mut Registry) -> MetaSchemaRef {
+    let name = T::schema_name();
+    let mut root = SchemaSettings::openapi3()
+        .into_generator()
+        .root_schema_for::<T>()
+        .to_value();
+    let mut converter = Converter::default();
+    if let Value::Object(map) = &mut root {
+        map.remove("$schema");
+        // `root_schema_for` titles the root after the type, which a component does not need.
+        if map.get("title").and_then(Value::as_str) == Some(&name) {
+            map.remove("title");
+        }
+        if let Some(Value::Object(mut components)) = map.remove("components")
+            && let Some(Value::Object(definitions)) = components.remove("schemas")
+        {
+            for (definition, schema) in definitions {
+                poem_reg.create_schema::<(), _>(definition, |_| converter.schema(&schema));
+            }
+        }
+    }
+
+    let schema_ref = if T::inline_schema() {
+        converter.schema_ref(&root)
+    } else {
+        poem_reg.create_schema::<(), _>(name.to_string(), |_| converter.schema(&root));
+        MetaSchemaRef::Reference(name.to_string())
+    };
+    if !converter.dropped.is_empty() {
+        tracing::warn!(
+            "jsonwrap: dropped unsupported keywords {:?} from the schemars schema of `{name}`",
+            converter.dropped
+        );
+    }
+    schema_ref
+}
+
+#[derive(Default)]
+struct Converter {
+    /// The keywords that have no `MetaSchema` counterpart, by name.
+    dropped: BTreeSet<String>,
+}
+
+impl Converter {
+    fn schema_ref(&mut self, value: &Value) -> MetaSchemaRef {
+        if let Some(reference) = value.get("$ref").and_then(Value::as_str) {
+            match reference.strip_prefix(REF_PREFIX) {
+                Some(name) => return MetaSchemaRef::Reference(name.to_string()),
+                None => {
+                    self.dropped.insert(format!("$ref {reference}"));
+                }
+            }
+        }
+        MetaSchemaRef::Inline(Box::new(self.schema(value)))
+    }
+
+    fn schema(&mut self, value: &Value) -> MetaSchema {
+        match value {
+            Value::Object(map) => self.object(map),
+            Value::Bool(true) => MetaSchema::ANY,
+            other => {
+                self.dropped.insert(format!("{other} schema"));
+                MetaSchema::ANY
+            }
+        }
+    }
+
+    fn object(&mut self, map: &Map<String, Value>) -> MetaSchema {
+        let mut schema = MetaSchema::ANY;
+        for (keyword, value) in map {
+            match keyword.as_str() {
+                "$ref" => {}
+                "type" => match value {
+                    Value::String(ty) => schema.ty = leak_str(ty),
+                    Value::Array(types) => {
+                        for ty in types.iter().filter_map(Value::as_str) {
+                            match ty {
+                                "null" => schema.nullable = true,
+                                ty => schema.ty = leak_str(ty),
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                "format" => schema.format = value.as_str().map(leak_str),
+                "title" => schema.title = value.as_str().map(str::to_string),
+                "description" => schema.description = value.as_str().map(leak_str),
+                "default" => schema.default = Some(value.clone()),
+                "example" => schema.example = Some(value.clone()),
+                "enum" => schema.enum_items = value.as_array().cloned().unwrap_or_default(),
+                "required" => {
+                    schema.required = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .map(leak_str)
+                        .collect();
+                }
+                "properties" => {
+                    schema.properties = value
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                        .map(|(name, property)| (leak_str(name), self.schema_ref(property)))
+                        .collect();
+                }
+                "items" => schema.items = Some(Box::new(self.schema_ref(value))),
+                "additionalProperties" => match value {
+                    Value::Bool(true) => {}
+                    Value::Bool(false) => {
+                        self.dropped
+                            .insert("additionalProperties: false".to_string());
+                    }
+                    value => {
+                        schema.additional_properties = Some(Box::new(self.schema_ref(value)));
+                    }
+                },
+                "oneOf" => schema.one_of = self.schema_refs(value),
+                "anyOf" => schema.any_of = self.schema_refs(value),
+                "allOf" => schema.all_of = self.schema_refs(value),
+                "nullable" => schema.nullable = value == true,
+                "deprecated" => schema.deprecated = value == true,
+                "readOnly" => schema.read_only = value == true,
+                "writeOnly" => schema.write_only = value == true,
+                "multipleOf" => schema.multiple_of = value.as_f64(),
+                "minimum" => schema.minimum = value.as_f64(),
+                "maximum" => schema.maximum = value.as_f64(),
+                "exclusiveMinimum" => match value {
+                    Value::Bool(exclusive) => schema.exclusive_minimum = Some(*exclusive),
+                    value => {
+                        schema.minimum = value.as_f64();
+                        schema.exclusive_minimum = Some(true);
+                    }
+                },
+                "exclusiveMaximum" => match value {
+                    Value::Bool(exclusive) => schema.exclusive_maximum = Some(*exclusive),
+                    value => {
+                        schema.maximum = value.as_f64();
+                        schema.exclusive_maximum = Some(true);
+                    }
+                },
+                "minLength" => schema.min_length = as_usize(value),
+                "maxLength" => schema.max_length = as_usize(value),
+                "pattern" => schema.pattern = value.as_str().map(str::to_string),
+                "minItems" => schema.min_items = as_usize(value),
+                "maxItems" => schema.max_items = as_usize(value),
+                "uniqueItems" => schema.unique_items = value.as_bool(),
+                "minProperties" => schema.min_properties = as_usize(value),
+                "maxProperties" => schema.max_properties = as_usize(value),
+                other => {
+                    self.dropped.insert(other.to_string());
+                }
+            }
+        }
+        schema
+    }
+
+    fn schema_refs(&mut self, value: &Value) -> Vec<MetaSchemaRef> {
+        value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|schema| self.schema_ref(schema))
+            .collect()
+    }
+}
+
+fn as_usize(value: &Value) -> Option<usize> {
+    value.as_u64().and_then(|n| usize::try_from(n).ok())
+}
@@ -1,16 +1,202 @@
+#[cfg(feature = "arrayvec")]
+mod array_string;
+mod audit;
+mod bytes;
+mod case;
+mod config;
+#[cfg(feature = "chrono")]
+mod datetime;
+mod decode;
+mod describe;
+#[cfg(feature = "erased-serde")]
+mod dynamic;
+mod enums;
+mod error;
+#[cfg(feature = "bitflags")]
+mod flags;
+mod format;
+mod headers;
+#[cfg(feature = "schemars")]
+mod json_schema;
+mod non_empty;
+#[cfg(feature = "trace")]
+mod open;
+mod owned;
+#[cfg(feature = "trace")]
+mod page;
+mod param;
+mod payload;
+pub mod render;
+mod repr;
+mod schemas;
+mod ser;
+mod source;
+#[cfg(feature = "trace")]
+mod stand_in;
+#[cfg(feature = "uuid")]
+mod uuid;
+mod validate;
+mod warmup;
+
+pub use audit::opaque_types;
+pub use case::KeyCase;
+pub use config::{
+    BytesRepr, FieldConstraints, ForeignConfig, ForeignDocsSide, ForeignFieldDefaults, Int128Repr,
+    NonFiniteFloats, TupleItem,
+};
+pub use describe::ForeignDescription;
+#[cfg(feature = "erased-serde")]
+pub use dynamic::ForeignDyn;
+pub use enums::EnumRepr;
+pub use error::ForeignParseError;
+#[cfg(feature = "bitflags")]
+pub use flags::FlagsRepr;
+pub use headers::WithMetaHeaders;
+pub use non_empty::NonEmptyString;
+pub use owned::ForeignOwned;
+#[cfg(feature = "trace")]
+pub use page::ForeignPage;
+pub use payload::ForeignJson;
+pub use repr::Repr;
+pub use schemas::schema_endpoint;
+pub use ser::KeyEncoder;
+#[cfg(feature = "trace")]
+pub use serde_reflection::TracerConfig;
+pub use source::{SchemaSource, StaticSchema};
+pub use warmup::{warmup, warmup_with_progress};
+
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
 use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
-use poem_openapi::types::{ToJSON, Type};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
 use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
-use serde_reflection::{
-    ContainerFormat, Format, Registry as SerdeRegistry, Tracer, TracerConfig, VariantFormat,
-};
+#[cfg(feature = "trace")]
+use serde_reflection::{Samples, Tracer};
+
+use crate::format::{ContainerFormat, Format, Named, Registry as SerdeRegistry, VariantFormat};
+
+use crate::param::{ParamKind, param_kind};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+}
 
 pub struct Foreign<T>(pub T);
 pub struct ForeignOpt<T>(pub Option<T>);
 
+/// Returns a `'static` copy of `s`, leaking each distinct string at most once.
+///
+/// poem-openapi wants `&'static str` property names, and schemas are rebuilt every time a
+/// spec is generated, so the leaked copies are interned to keep memory bounded.
 fn leak_str(s: &str) -> &'static str {
-    Box::leak(s.to_owned().into_boxed_str())
+    static INTERNED: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Default::default);
+
+    let mut interned = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+    match interned.get(s) {
+        Some(existing) => existing,
+        None => {
+            let leaked: &'static str = Box::leak(s.to_owned().into_boxed_str());
+            interned.insert(leaked);
+            leaked
+        }
+    }
+}
+
+/// Marks a schema as accepting `null`.
+///
+/// Siblings of a `$ref` are ignored, so references get wrapped in a nullable `allOf`.
+fn nullable(schema_ref: MetaSchemaRef) -> MetaSchemaRef {
+    match schema_ref {
+        MetaSchemaRef::Reference(name) => MetaSchemaRef::Inline(Box::new(MetaSchema {
+            title: config::current().nullable_title.then(|| name.clone()),
+            nullable: true,
+            all_of: vec![MetaSchemaRef::Reference(name)],
+            ..MetaSchema::ANY
+        })),
+        MetaSchemaRef::Inline(mut schema) => {
+            schema.nullable = true;
+            MetaSchemaRef::Inline(schema)
+        }
+    }
+}
+
+/// [`nullable`] for a schema whose components are in `poem_reg`.
+///
+/// `enum` is checked before `nullable`, so a reference to a string enum, such as a unit-only
+/// Rust enum, would still reject `null`. It is replaced with a copy of the enum that lists
+/// `null` among its values.
+///
+/// OpenAPI 3.0.3 only honours `nullable` next to a `type`, and the `allOf` wrapper still
+/// applies the referenced schema to `null`, so strict validators reject it. A reference to a
+/// component with a `type` points to a nullable copy of it instead, registered as
+/// `{name}Nullable`. Components without a `type`, such as `oneOf` enums, keep the wrapper.
+fn nullable_in(schema_ref: MetaSchemaRef, poem_reg: &mut Registry) -> MetaSchemaRef {
+    let MetaSchemaRef::Reference(name) = &schema_ref else {
+        return nullable(schema_ref);
+    };
+    let Some(schema) = poem_reg.schemas.get(name) else {
+        return nullable(schema_ref);
+    };
+    if schema.ty == "string" && !schema.enum_items.is_empty() {
+        let mut schema = schema.clone();
+        schema.enum_items.push(Value::Null);
+        schema.nullable = true;
+        return MetaSchemaRef::Inline(Box::new(schema));
+    }
+    if schema.ty.is_empty() {
+        return nullable(schema_ref);
+    }
+    let copy = nullable_component(name);
+    let schema = nullable_copy(name, schema);
+    poem_reg.create_schema::<(), _>(copy.clone(), |_| schema);
+    MetaSchemaRef::Reference(copy)
+}
+
+/// The nullable copy of the component `name`, titled after it unless `nullable_title` is off.
+fn nullable_copy(name: &str, schema: &MetaSchema) -> MetaSchema {
+    let title = schema
+        .title
+        .clone()
+        .or_else(|| config::current().nullable_title.then(|| name.to_string()));
+    MetaSchema {
+        title,
+        nullable: true,
+        ..schema.clone()
+    }
+}
+
+/// The name of the nullable copy of the component `name`, see [`nullable_in`].
+fn nullable_component(name: &str) -> String {
+    format!("{name}Nullable")
+}
+
+/// Brings the nullable copy of the component `name` up to date, if there is one.
+///
+/// A container that refers to itself through an `Option` is copied while `create_schema`
+/// still holds a placeholder for it, so the copy is made again once it is built.
+fn refresh_nullable(name: &str, poem_reg: &mut Registry) {
+    let copy = nullable_component(name);
+    if poem_reg.schemas.contains_key(&copy)
+        && let Some(schema) = poem_reg.schemas.get(name)
+    {
+        let schema = nullable_copy(name, schema);
+        poem_reg.schemas.insert(copy, schema);
+    }
+}
+
+/// The schema of a value that is always `null`, such as `()` or a unit struct.
+///
+/// OpenAPI 3.0 has no `null` type, so it is a nullable schema that admits nothing else.
+fn null_schema() -> MetaSchema {
+    MetaSchema {
+        nullable: true,
+        enum_items: vec![Value::Null],
+        ..MetaSchema::ANY
+    }
 }
 
 fn format_to_schema(
@@ -23,36 +209,97 @@ fn format_to_schema(
             ty: "string",
             ..MetaSchema::ANY
         })),
-        Format::I8
-        | Format::I16
-        | Format::I32
-        | Format::I64
-        | Format::I128
-        | Format::U8
-        | Format::U16
-        | Format::U32
-        | Format::U64
-        | Format::U128 => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "integer",
-            ..MetaSchema::ANY
-        })),
-        Format::F32 | Format::F64 => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "number",
-            ..MetaSchema::ANY
-        })),
+        Format::I8 => integer(Some("int32"), Some(i8::MIN.into()), Some(i8::MAX.into())),
+        Format::I16 => integer(Some("int32"), Some(i16::MIN.into()), Some(i16::MAX.into())),
+        Format::I32 => integer(Some("int32"), Some(i32::MIN.into()), Some(i32::MAX.into())),
+        Format::U8 => integer(Some("int32"), Some(0.0), Some(u8::MAX.into())),
+        Format::U16 => integer(Some("int32"), Some(0.0), Some(u16::MAX.into())),
+        // Beyond `int32`, so clients need a 64-bit type to hold every value.
+        Format::U32 => integer(Some("int64"), Some(0.0), Some(u32::MAX.into())),
+        // The 64-bit maxima are not exactly representable as the `f64` bounds are stored.
+        Format::I64 => integer(Some("int64"), None, None),
+        Format::U64 => integer(Some("int64"), Some(0.0), None),
+        Format::I128 | Format::U128 => match config::current().int128_repr {
+            Int128Repr::String => MetaSchemaRef::Inline(Box::new(MetaSchema {
+                format: Some("int128"),
+                pattern: Some(
+                    if matches!(format, Format::I128) {
+                        "^-?[0-9]+$"
+                    } else {
+                        "^[0-9]+$"
+                    }
+                    .to_string(),
+                ),
+                ..MetaSchema::new("string")
+            })),
+            // OpenAPI has no registered format for 128-bit integers, so leave it unset rather
+            // than claim a width that generated clients would truncate to.
+            Int128Repr::Number if matches!(format, Format::I128) => integer(None, None, None),
+            Int128Repr::Number => integer(None, Some(0.0), None),
+        },
+        Format::F32 | Format::F64 => {
+            let number = MetaSchemaRef::Inline(Box::new(MetaSchema {
+                ty: "number",
+                format: Some(if matches!(format, Format::F32) {
+                    "float"
+                } else {
+                    "double"
+                }),
+                ..MetaSchema::ANY
+            }));
+            match config::current().non_finite_floats {
+                NonFiniteFloats::String => MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    one_of: vec![
+                        number,
+                        MetaSchemaRef::Inline(Box::new(MetaSchema {
+                            enum_items: vec!["NaN".into(), "Infinity".into(), "-Infinity".into()],
+                            ..MetaSchema::new("string")
+                        })),
+                    ],
+                    ..MetaSchema::ANY
+                })),
+                NonFiniteFloats::Null | NonFiniteFloats::Reject => number,
+            }
+        }
         Format::Bool => MetaSchemaRef::Inline(Box::new(MetaSchema {
             ty: "boolean",
             ..MetaSchema::ANY
         })),
+        // serde writes a `char` as a string of that one character.
         Format::Char => MetaSchemaRef::Inline(Box::new(MetaSchema {
             ty: "string",
+            min_length: Some(1),
+            max_length: Some(1),
             ..MetaSchema::ANY
         })),
-        Format::Unit => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "null",
-            ..MetaSchema::ANY
-        })),
-        Format::Option(inner) => format_to_schema(inner, serde_reg, poem_reg),
+        Format::Unit => MetaSchemaRef::Inline(Box::new(null_schema())),
+        Format::Bytes => match config::current().bytes_repr {
+            BytesRepr::Base64 => MetaSchemaRef::Inline(Box::new(MetaSchema {
+                ty: "string",
+                format: Some("byte"),
+                ..MetaSchema::ANY
+            })),
+            BytesRepr::Array => MetaSchemaRef::Inline(Box::new(MetaSchema {
+                ty: "array",
+                items: Some(Box::new(MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    ty: "integer",
+                    format: Some("int32"),
+                    minimum: Some(0.0),
+                    maximum: Some(255.0),
+                    ..MetaSchema::ANY
+                })))),
+                ..MetaSchema::ANY
+            })),
+        },
+        Format::Option(inner) => {
+            // JSON has a single `null`, so `Option<Option<T>>` is described like `Option<T>`.
+            let mut inner = inner;
+            while let Format::Option(next) = inner.as_ref() {
+                inner = next;
+            }
+            let schema_ref = format_to_schema(inner, serde_reg, poem_reg);
+            nullable_in(schema_ref, poem_reg)
+        }
         Format::Seq(inner) => {
             let items = format_to_schema(inner, serde_reg, poem_reg);
             MetaSchemaRef::Inline(Box::new(MetaSchema {
@@ -61,103 +308,325 @@ fn format_to_schema(
                 ..MetaSchema::ANY
             }))
         }
-        Format::Map { key: _, value } => {
+        Format::Map { key, value } => {
             let additional = format_to_schema(value, serde_reg, poem_reg);
+            let description = match param_kind(key, serde_reg) {
+                ParamKind::Int | ParamKind::Uint | ParamKind::Int128 | ParamKind::Uint128 => {
+                    Some("Keys are integers written as strings.")
+                }
+                ParamKind::Float => Some("Keys are numbers written as strings."),
+                ParamKind::Bool => Some("Keys are `true` or `false`."),
+                ParamKind::Str => None,
+                // Registration panics unless a key encoder is configured.
+                _ => Some("Keys are encoded into strings by the server."),
+            };
             MetaSchemaRef::Inline(Box::new(MetaSchema {
                 ty: "object",
+                description,
                 additional_properties: Some(Box::new(additional)),
                 ..MetaSchema::ANY
             }))
         }
-        Format::Tuple(formats) => {
-            let items: Vec<_> = formats
+        Format::Tuple(formats) => MetaSchemaRef::Inline(Box::new(tuple_schema(
+            formats
                 .iter()
                 .map(|f| format_to_schema(f, serde_reg, poem_reg))
-                .collect();
+                .collect(),
+        ))),
+        Format::TypeName(name) => {
+            if let Some(schema) = declared_schema(name) {
+                return schema;
+            }
+            #[cfg(feature = "bitflags")]
+            if let Some(flags) = flags::declared(name) {
+                return flags.schema();
+            }
+            #[cfg(feature = "schemars")]
+            if let Some(register) = config::current().json_schemas.get(name) {
+                return register(poem_reg);
+            }
+            if let Some(schema) = string_type(name) {
+                return schema;
+            }
+            let container = transparent_container(name, serde_reg);
+            if container != *name
+                && let Some(schema) = declared_schema(&container)
+            {
+                return schema;
+            }
+            register_type(&container, serde_reg, poem_reg);
+            MetaSchemaRef::Reference(container)
+        }
+        _ => {
+            audit::opaque_within();
             MetaSchemaRef::Inline(Box::new(MetaSchema {
-                ty: "array",
-                all_of: items,
+                ty: "object",
                 ..MetaSchema::ANY
             }))
         }
-        Format::TypeName(name) => {
-            register_type(name, serde_reg, poem_reg);
-            MetaSchemaRef::Reference(name.clone())
-        }
-        _ => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "object",
-            ..MetaSchema::ANY
-        })),
     }
 }
 
+/// Panics for a reference to the container `missing`, which the trace being registered does
+/// not describe, rather than let the spec refer to a component nothing registers.
+fn dangling(missing: &str) -> ! {
+    match audit::current_field() {
+        Some((field, root)) => panic!(
+            "jsonwrap: `{field}`, registered for `{root}`, refers to `{missing}`, which its \
+             trace does not describe; the spec would have a dangling reference"
+        ),
+        None => panic!(
+            "jsonwrap: a type refers to `{missing}`, which its trace does not describe; the \
+             spec would have a dangling reference"
+        ),
+    }
+}
+
+/// An integer schema with the given format and the bounds of its Rust type.
+fn integer(
+    format: Option<&'static str>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+) -> MetaSchemaRef {
+    MetaSchemaRef::Inline(Box::new(MetaSchema {
+        ty: "integer",
+        format,
+        minimum,
+        maximum,
+        ..MetaSchema::ANY
+    }))
+}
+
 fn variant_to_schema(
     variant_format: &VariantFormat,
     serde_reg: &SerdeRegistry,
     poem_reg: &mut Registry,
 ) -> MetaSchemaRef {
     match variant_format {
-        VariantFormat::Unit => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "null",
-            ..MetaSchema::ANY
-        })),
+        VariantFormat::Unit => MetaSchemaRef::Inline(Box::new(null_schema())),
         VariantFormat::NewType(inner) => format_to_schema(inner, serde_reg, poem_reg),
-        VariantFormat::Tuple(formats) => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "array",
-            all_of: formats
+        VariantFormat::Tuple(formats) => MetaSchemaRef::Inline(Box::new(tuple_schema(
+            formats
                 .iter()
                 .map(|f| format_to_schema(f, serde_reg, poem_reg))
                 .collect(),
+        ))),
+        VariantFormat::Struct(fields) => {
+            let (properties, required) = object_fields(fields, |field| {
+                format_to_schema(&field.value, serde_reg, poem_reg)
+            });
+            MetaSchemaRef::Inline(Box::new(MetaSchema {
+                ty: "object",
+                properties,
+                required,
+                ..MetaSchema::ANY
+            }))
+        }
+    }
+}
+
+/// Builds the `properties` and `required` lists of an object from its fields.
+///
+/// Every field is required except `Option`s, which serde accepts when missing. Each name is
+/// leaked once and shared by both lists.
+fn object_fields(
+    fields: &[Named<Format>],
+    mut schema: impl FnMut(&Named<Format>) -> MetaSchemaRef,
+) -> (Vec<(&'static str, MetaSchemaRef)>, Vec<&'static str>) {
+    let mut properties = Vec::with_capacity(fields.len());
+    let mut required = Vec::new();
+    for field in fields {
+        let name = leak_str(&field.name);
+        if !matches!(field.value, Format::Option(_)) {
+            required.push(name);
+        }
+        properties.push((name, schema(field)));
+    }
+    (properties, required)
+}
+
+/// Applies `f` to the schema, wrapping references in an `allOf` so the added keywords survive.
+fn annotate(schema_ref: MetaSchemaRef, f: impl FnOnce(&mut MetaSchema)) -> MetaSchemaRef {
+    let mut schema = match schema_ref {
+        MetaSchemaRef::Reference(name) => MetaSchema {
+            all_of: vec![MetaSchemaRef::Reference(name)],
             ..MetaSchema::ANY
-        })),
-        VariantFormat::Struct(fields) => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "object",
-            properties: fields
+        },
+        MetaSchemaRef::Inline(schema) => *schema,
+    };
+    f(&mut schema);
+    MetaSchemaRef::Inline(Box::new(schema))
+}
+
+/// Shallow check that `value` has the JSON type the schema declares.
+fn value_fits(value: &Value, schema_ref: &MetaSchemaRef, poem_reg: &Registry) -> bool {
+    let schema = match schema_ref {
+        MetaSchemaRef::Inline(schema) => schema.as_ref(),
+        MetaSchemaRef::Reference(name) => match poem_reg.schemas.get(name) {
+            Some(schema) => schema,
+            None => return true,
+        },
+    };
+    if value.is_null() && schema.nullable {
+        return true;
+    }
+    if !schema.enum_items.is_empty() && !schema.enum_items.contains(value) {
+        return false;
+    }
+    match schema.ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => schema
+            .all_of
+            .iter()
+            .all(|schema_ref| value_fits(value, schema_ref, poem_reg)),
+    }
+}
+
+/// Applies the configured overrides for one property of the container `type_name`.
+fn field_to_schema(
+    type_name: &str,
+    field: &str,
+    schema_ref: MetaSchemaRef,
+    poem_reg: &Registry,
+) -> MetaSchemaRef {
+    let config = config::current();
+    let Some(overrides) = config.field(type_name, field) else {
+        return schema_ref;
+    };
+    let mut schema_ref = schema_ref;
+    if let Some(default) = &overrides.default {
+        if !value_fits(default, &schema_ref, poem_reg) {
+            tracing::warn!(
+                "jsonwrap: default {default} for `{type_name}.{field}` does not match its schema"
+            );
+        }
+        schema_ref = annotate(schema_ref, |schema| schema.default = Some(default.clone()));
+    }
+    if let Some(example) = &overrides.example {
+        if !value_fits(example, &schema_ref, poem_reg) {
+            tracing::warn!(
+                "jsonwrap: example {example} for `{type_name}.{field}` does not match its schema"
+            );
+        }
+        schema_ref = annotate(schema_ref, |schema| schema.example = Some(example.clone()));
+    }
+    let constraints = &overrides.constraints;
+    if !constraints.enum_values.is_empty() {
+        if !value_fits(&Value::String(String::new()), &schema_ref, poem_reg) {
+            tracing::warn!(
+                "jsonwrap: `{type_name}.{field}` has enum values but is not a string property"
+            );
+        }
+        if !constraints.display_names.is_empty()
+            && constraints.display_names.len() != constraints.enum_values.len()
+        {
+            tracing::warn!(
+                "jsonwrap: `{type_name}.{field}` has {} enum values but {} display names",
+                constraints.enum_values.len(),
+                constraints.display_names.len()
+            );
+        }
+        schema_ref = annotate(schema_ref, |schema| {
+            schema.enum_items = constraints
+                .enum_values
                 .iter()
-                .map(|field| {
-                    (
-                        leak_str(&field.name),
-                        format_to_schema(&field.value, serde_reg, poem_reg),
-                    )
-                })
-                .collect(),
-            ..MetaSchema::ANY
-        })),
-        VariantFormat::Variable(_) => MetaSchemaRef::Inline(Box::new(MetaSchema {
-            ty: "object",
-            ..MetaSchema::ANY
-        })),
+                .cloned()
+                .map(Value::String)
+                .collect();
+            // `enum` is checked before `nullable`, so a nullable property has to list `null`.
+            if schema.nullable {
+                schema.enum_items.push(Value::Null);
+            }
+            if !constraints.display_names.is_empty() {
+                let labels: Vec<_> = constraints
+                    .enum_values
+                    .iter()
+                    .zip(&constraints.display_names)
+                    .map(|(value, name)| format!("`{value}`: {name}"))
+                    .collect();
+                schema.description = Some(leak_str(&labels.join("; ")));
+            }
+        });
+    }
+    if constraints.min_items.is_some() || constraints.max_items.is_some() {
+        if !value_fits(&Value::Array(Vec::new()), &schema_ref, poem_reg) {
+            tracing::warn!(
+                "jsonwrap: `{type_name}.{field}` has an item count constraint but is not an array"
+            );
+        }
+        schema_ref = annotate(schema_ref, |schema| {
+            schema.min_items = constraints.min_items;
+            schema.max_items = constraints.max_items;
+        });
+    }
+    if constraints.min_properties.is_some() || constraints.max_properties.is_some() {
+        if !value_fits(&Value::Object(Default::default()), &schema_ref, poem_reg) {
+            tracing::warn!(
+                "jsonwrap: `{type_name}.{field}` has an entry count constraint but is not a map"
+            );
+        }
+        schema_ref = annotate(schema_ref, |schema| {
+            schema.min_properties = constraints.min_properties;
+            schema.max_properties = constraints.max_properties;
+        });
     }
+    if overrides.deprecated {
+        schema_ref = annotate(schema_ref, |schema| schema.deprecated = true);
+    }
+    schema_ref
 }
 
 fn container_to_schema(
+    name: &str,
     format: &ContainerFormat,
     serde_reg: &SerdeRegistry,
     poem_reg: &mut Registry,
 ) -> MetaSchema {
     match format {
-        ContainerFormat::Struct(fields) => MetaSchema {
-            ty: "object",
-            properties: fields
-                .iter()
-                .map(|field| {
-                    (
-                        leak_str(&field.name),
-                        format_to_schema(&field.value, serde_reg, poem_reg),
-                    )
-                })
-                .collect(),
-            ..MetaSchema::ANY
-        },
+        ContainerFormat::Struct(fields) => {
+            let (mut properties, mut required) = object_fields(fields, |field| {
+                let schema = audit::field(&field.name, || {
+                    format_to_schema(&field.value, serde_reg, poem_reg)
+                });
+                field_to_schema(name, &field.name, schema, poem_reg)
+            });
+            let config = config::current();
+            if config.key_cases.contains_key(name) {
+                for (property, _) in &mut properties {
+                    *property = leak_str(&config.wire_name(name, property));
+                }
+                for property in &mut required {
+                    *property = leak_str(&config.wire_name(name, property));
+                }
+            }
+            // The entries a `#[serde(flatten)]` map collects, see `open::trace`.
+            #[cfg(feature = "trace")]
+            let additional_properties = open::additional(name)
+                .map(|format| Box::new(format_to_schema(&format, serde_reg, poem_reg)));
+            #[cfg(not(feature = "trace"))]
+            let additional_properties = None;
+            MetaSchema {
+                ty: "object",
+                properties,
+                required,
+                additional_properties,
+                ..MetaSchema::ANY
+            }
+        }
         ContainerFormat::NewTypeStruct(inner) => {
             // For newtype structs, we want to be transparent and expose the inner type's schema
-            match format_to_schema(inner, serde_reg, poem_reg) {
+            let mut schema = match format_to_schema(inner, serde_reg, poem_reg) {
                 MetaSchemaRef::Inline(schema) => *schema,
                 MetaSchemaRef::Reference(name) => {
                     // Register the inner type and return its schema
                     if let Some(inner_format) = serde_reg.get(&name) {
                         let inner_format = inner_format.clone();
-                        container_to_schema(&inner_format, serde_reg, poem_reg)
+                        container_to_schema(&name, &inner_format, serde_reg, poem_reg)
                     } else {
                         MetaSchema {
                             ty: "object",
@@ -165,118 +634,1105 @@ fn container_to_schema(
                         }
                     }
                 }
+            };
+            if name == non_empty::NAME && schema.ty == "string" {
+                schema.min_length = Some(1);
+            }
+            if let Some(format) = config::current().string_formats.get(name) {
+                if schema.ty == "string" {
+                    schema.format = Some(format);
+                } else {
+                    tracing::warn!(
+                        "jsonwrap: `{name}` has string format \"{format}\" but is not a string newtype"
+                    );
+                }
             }
+            schema
         }
-        ContainerFormat::TupleStruct(formats) => MetaSchema {
-            ty: "array",
-            all_of: formats
-                .iter()
-                .map(|f| format_to_schema(f, serde_reg, poem_reg))
-                .collect(),
-            ..MetaSchema::ANY
-        },
-        ContainerFormat::Enum(variants) => MetaSchema {
-            ty: "object",
-            any_of: variants
-                .iter()
-                .map(|(_idx, variant)| {
-                    MetaSchemaRef::Inline(Box::new(MetaSchema {
-                        ty: "object",
-                        properties: vec![(
-                            leak_str(&variant.name),
-                            variant_to_schema(&variant.value, serde_reg, poem_reg),
-                        )],
-                        ..MetaSchema::ANY
-                    }))
-                })
-                .collect(),
-            ..MetaSchema::ANY
-        },
-        ContainerFormat::UnitStruct => MetaSchema {
-            ty: "null",
+        ContainerFormat::TupleStruct(formats) => {
+            let config = config::current();
+            let hints = config.tuple_items.get(name).map(Vec::as_slice);
+            let positions = formats.iter().enumerate().map(|(index, format)| {
+                let schema_ref = format_to_schema(format, serde_reg, poem_reg);
+                match hints.and_then(|hints| hints.get(index)) {
+                    Some(hint) => annotate(schema_ref, |schema| hint.apply(schema)),
+                    None => schema_ref,
+                }
+            });
+            tuple_schema(positions.collect())
+        }
+        ContainerFormat::Enum(variants) if partially_traced(variants) => {
+            let known = variants.len();
+            let total = variant_count(variants);
+            tracing::warn!(
+                "jsonwrap: only {known} of the {total} variants of `{name}` are in its trace; \
+                 it is documented as any value. Trace the type containing it with \
+                 `Foreign::trace_sample` and a sample that uses every variant"
+            );
+            audit::opaque(name);
+            MetaSchema::ANY
+        }
+        // serde writes unit variants as bare strings.
+        ContainerFormat::Enum(variants)
+            if variants
+                .values()
+                .all(|variant| matches!(variant.value, VariantFormat::Unit)) =>
+        {
+            MetaSchema {
+                ty: "string",
+                enum_items: variants
+                    .values()
+                    .map(|variant| Value::String(variant.name.clone()))
+                    .collect(),
+                ..MetaSchema::ANY
+            }
+        }
+        ContainerFormat::Enum(variants) => {
+            // Unit variants are written as their name, the others as `{ "Variant": payload }`.
+            let (units, payloads): (Vec<_>, Vec<_>) = variants
+                .values()
+                .partition(|variant| matches!(variant.value, VariantFormat::Unit));
+            let mut one_of = Vec::with_capacity(payloads.len() + 1);
+            if !units.is_empty() {
+                one_of.push(MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    ty: "string",
+                    enum_items: units
+                        .iter()
+                        .map(|variant| Value::String(variant.name.clone()))
+                        .collect(),
+                    ..MetaSchema::ANY
+                })));
+            }
+            for variant in payloads {
+                let name = leak_str(&variant.name);
+                one_of.push(MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    ty: "object",
+                    required: vec![name],
+                    properties: vec![(
+                        name,
+                        audit::field(name, || {
+                            variant_to_schema(&variant.value, serde_reg, poem_reg)
+                        }),
+                    )],
+                    ..MetaSchema::ANY
+                })));
+            }
+            MetaSchema {
+                one_of,
+                ..MetaSchema::ANY
+            }
+        }
+        ContainerFormat::UnitStruct => null_schema(),
+    }
+}
+
+/// Whether tracing stopped before it saw every variant of an enum.
+///
+/// serde_reflection first numbers the variants it visits by name from the top of the `u32`
+/// range, and renumbers them once each has also been visited by index. Below an `Option`, a
+/// sequence or a map, exploration is cut off after the first pass, so an enum only reached
+/// that way keeps a single provisionally numbered variant until `trace_uncached` traces the
+/// others. A trace given with `Foreign::trace_from` can still hold one.
+fn partially_traced(variants: &BTreeMap<u32, Named<VariantFormat>>) -> bool {
+    variants.keys().any(|&index| index > u32::MAX / 2)
+}
+
+/// How many variants an enum has, read from the provisional numbers of a partial trace.
+fn variant_count(variants: &BTreeMap<u32, Named<VariantFormat>>) -> usize {
+    variants
+        .keys()
+        .min()
+        .map_or(variants.len(), |&first| (u32::MAX - first) as usize + 1)
+}
+
+/// Describes a fixed-length array whose positions have the given schemas.
+///
+/// OpenAPI 3.0 has no per-position `items`, so the length is pinned with `minItems` and
+/// `maxItems` and `items` accepts any of the distinct position schemas. `anyOf` rather than
+/// `oneOf`, as positions such as a latitude and a longitude overlap.
+fn tuple_schema(positions: Vec<MetaSchemaRef>) -> MetaSchema {
+    let len = positions.len();
+    let mut distinct: Vec<MetaSchemaRef> = Vec::with_capacity(len);
+    for position in positions {
+        if !distinct.contains(&position) {
+            distinct.push(position);
+        }
+    }
+    let items = match distinct.len() {
+        1 => distinct.pop(),
+        _ => Some(MetaSchemaRef::Inline(Box::new(MetaSchema {
+            any_of: distinct,
             ..MetaSchema::ANY
-        },
+        }))),
+    };
+    MetaSchema {
+        ty: "array",
+        items: items.map(Box::new),
+        min_items: Some(len),
+        max_items: Some(len),
+        ..MetaSchema::ANY
     }
 }
 
 fn register_type(name: &str, serde_reg: &SerdeRegistry, poem_reg: &mut Registry) {
-    if let Some(format) = serde_reg.get(name) {
-        let format = format.clone();
-        poem_reg.create_schema::<(), _>(name.to_string(), |poem_reg| {
-            container_to_schema(&format, serde_reg, poem_reg)
+    register_container(name, name, serde_reg, poem_reg);
+}
+
+/// The string schema the container `name` is described with instead of its traced one,
+/// from [`ForeignConfig::string_type`] or the known chrono and uuid types.
+fn string_type(name: &str) -> Option<MetaSchemaRef> {
+    let format = config::current().string_types.get(name).copied();
+    #[cfg(feature = "chrono")]
+    let format = format.or_else(|| datetime::format(name));
+    #[cfg(feature = "uuid")]
+    let format = format.or_else(|| uuid::format(name));
+    Some(MetaSchemaRef::Inline(Box::new(
+        MetaSchema::new_with_format("string", format?),
+    )))
+}
+
+/// The schema declared with [`ForeignConfig::declare_repr`] for `type_name`, if any.
+///
+/// Panics if the declared sample does not serialize to the declared shape.
+fn declared_schema(type_name: &str) -> Option<MetaSchemaRef> {
+    let config = config::current();
+    let (repr, sample) = config.reprs.get(type_name)?;
+    match sample {
+        Some(sample) if repr.matches(sample) => Some(repr.schema()),
+        Some(sample) => panic!(
+            "jsonwrap: `{type_name}` is declared as {repr:?} but serializes as {sample}; \
+             check the serde-related features enabled on its crate"
+        ),
+        None => panic!("jsonwrap: the sample declared for `{type_name}` failed to serialize"),
+    }
+}
+
+/// Registers the serde container `container` as the component `name`.
+///
+/// Components are always registered with `()` as their Rust type, since the same container
+/// is reached both from its own `Foreign` wrapper and from the types that contain it.
+/// `create_schema` reserves the name before building the schema, so a container that
+/// refers to itself, directly or through others, ends in a reference instead of recursing.
+/// A container missing from `serde_reg` panics, see [`dangling`].
+fn register_container(
+    name: &str,
+    container: &str,
+    serde_reg: &SerdeRegistry,
+    poem_reg: &mut Registry,
+) {
+    let Some(format) = serde_reg.get(container) else {
+        dangling(container);
+    };
+    let format = format.clone();
+    poem_reg.create_schema::<(), _>(name.to_string(), |poem_reg| {
+        let mut schema = audit::within(name, || {
+            container_to_schema(container, &format, serde_reg, poem_reg)
         });
+        if let Some(description) = config::current().descriptions.get(container) {
+            schema.description = Some(leak_str(description));
+        }
+        schema
+    });
+    refresh_nullable(name, poem_reg);
+}
+
+/// Replaces every reference in `schema_ref` with the schema registered under that name.
+///
+/// `stack` holds the names currently being expanded; meeting one of them again means
+/// the type is recursive, which no amount of inlining can represent.
+fn inline_refs(
+    schema_ref: MetaSchemaRef,
+    registry: &Registry,
+    stack: &mut Vec<String>,
+) -> MetaSchemaRef {
+    match schema_ref {
+        MetaSchemaRef::Reference(name) => {
+            if stack.contains(&name) {
+                panic!(
+                    "jsonwrap: `{name}` is recursive ({} -> {name}) and cannot be inlined; disable `inline_all`",
+                    stack.join(" -> ")
+                );
+            }
+            let Some(schema) = registry.schemas.get(&name) else {
+                return MetaSchemaRef::Reference(name);
+            };
+            let schema = schema.clone();
+            stack.push(name);
+            let schema = inline_schema(schema, registry, stack);
+            stack.pop();
+            MetaSchemaRef::Inline(Box::new(schema))
+        }
+        MetaSchemaRef::Inline(schema) => {
+            MetaSchemaRef::Inline(Box::new(inline_schema(*schema, registry, stack)))
+        }
     }
 }
 
-fn type_name<T: 'static>() -> String {
-    let full = std::any::type_name::<T>();
-    full.rsplit("::").next().unwrap_or(full).to_string()
+fn inline_schema(
+    mut schema: MetaSchema,
+    registry: &Registry,
+    stack: &mut Vec<String>,
+) -> MetaSchema {
+    let inline_each = |refs: Vec<MetaSchemaRef>, stack: &mut Vec<String>| -> Vec<MetaSchemaRef> {
+        refs.into_iter()
+            .map(|r| inline_refs(r, registry, stack))
+            .collect()
+    };
+    schema.rust_typename = None;
+    schema.properties = schema
+        .properties
+        .into_iter()
+        .map(|(name, prop)| (name, inline_refs(prop, registry, stack)))
+        .collect();
+    schema.items = schema
+        .items
+        .map(|items| Box::new(inline_refs(*items, registry, stack)));
+    schema.additional_properties = schema
+        .additional_properties
+        .map(|additional| Box::new(inline_refs(*additional, registry, stack)));
+    schema.any_of = inline_each(schema.any_of, stack);
+    schema.one_of = inline_each(schema.one_of, stack);
+    schema.all_of = inline_each(schema.all_of, stack);
+    schema
 }
 
-fn trace_type<T: DeserializeOwned>() -> Option<SerdeRegistry> {
-    let mut tracer = Tracer::new(TracerConfig::default());
-    tracer.trace_simple_type::<T>().ok()?;
-    tracer.registry().ok()
+/// The unqualified name of `T` without its generic arguments, `Page` for `api::Page<Item>`.
+pub(crate) fn type_name<T: 'static>() -> String {
+    short_name(std::any::type_name::<T>())
 }
 
-impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> Type for Foreign<T> {
-    const IS_REQUIRED: bool = true;
-    type RawValueType = Self;
-    type RawElementValueType = Self;
+/// [`type_name`] for a full type path.
+fn short_name(full: &str) -> String {
+    let path = full.split('<').next().unwrap_or(full);
+    path.rsplit("::").next().unwrap_or(path).to_string()
+}
 
-    fn name() -> std::borrow::Cow<'static, str> {
-        let name = type_name::<T>();
-        // For newtype structs, expose the inner type's name
-        if let Some(serde_reg) = trace_type::<T>() {
-            if let Some(ContainerFormat::NewTypeStruct(inner_format)) = serde_reg.get(&name) {
-                if let Format::TypeName(inner_name) = inner_format.as_ref() {
-                    return inner_name.clone().into();
-                }
+/// The full path of `T` when `full` names `Wrapper<T>`, given the path `probe` of
+/// `Wrapper<()>`.
+fn wrapped<'a>(full: &'a str, probe: &str) -> Option<&'a str> {
+    let prefix = probe.strip_suffix("()>")?;
+    full.strip_prefix(prefix)?.strip_suffix('>')
+}
+
+/// The full paths of the positions when `full` names a tuple of at least one type.
+fn tuple_positions(full: &str) -> Option<Vec<&str>> {
+    let inner = full.strip_prefix('(')?.strip_suffix(')')?;
+    let mut positions = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (at, c) in inner.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                positions.push(inner[start..at].trim());
+                start = at + 1;
             }
+            _ => {}
         }
-        name.into()
     }
+    positions.push(inner[start..].trim());
+    // A one-element tuple ends in a comma.
+    positions.retain(|position| !position.is_empty());
+    (!positions.is_empty()).then_some(positions)
+}
 
-    fn schema_ref() -> MetaSchemaRef {
-        let name = type_name::<T>();
-        // For newtype structs, reference the inner type's schema
-        if let Some(serde_reg) = trace_type::<T>() {
-            if let Some(ContainerFormat::NewTypeStruct(inner_format)) = serde_reg.get(&name) {
-                if let Format::TypeName(inner_name) = inner_format.as_ref() {
-                    return MetaSchemaRef::Reference(inner_name.clone());
-                }
-            }
+/// Finds a map key format that JSON cannot use as an object key without an encoder.
+fn composite_map_key(root: &Format, serde_reg: &SerdeRegistry) -> Option<Format> {
+    let mut found = None;
+    let mut check = |format: &Format| {
+        if let Format::Map { key, .. } = format
+            && found.is_none()
+            && !param_kind(key, serde_reg).is_scalar()
+        {
+            found = Some(key.as_ref().clone());
         }
-        MetaSchemaRef::Reference(name)
+    };
+    root.visit(&mut check);
+    for container in serde_reg.values() {
+        container.visit(&mut check);
     }
+    found
+}
 
-    fn register(poem_reg: &mut Registry) {
-        let name = type_name::<T>();
-        let Some(serde_reg) = trace_type::<T>() else {
-            poem_reg.create_schema::<Self, _>(name, |_| MetaSchema {
-                ty: "object",
-                ..MetaSchema::ANY
-            });
-            return;
+/// The result of tracing a type: its root format and the registry of named containers.
+///
+/// The formats are copies without the variables tracing uses, see [`format`], so a trace can
+/// be shared between threads.
+#[derive(Debug)]
+pub(crate) struct Trace {
+    root: Format,
+    registry: SerdeRegistry,
+    /// Whether byte buffers appear anywhere in the type, which bodies then need decoding for.
+    has_bytes: bool,
+    /// Whether 128-bit integers appear anywhere in the type, likewise.
+    has_int128: bool,
+}
+
+impl Trace {
+    /// Copies a finished trace, failing if any format in it, containers included, is
+    /// still unknown.
+    #[cfg(feature = "trace")]
+    fn new(
+        root: serde_reflection::Format,
+        registry: serde_reflection::Registry,
+    ) -> serde_reflection::Result<Self> {
+        let root = Format::from_traced(root)?;
+        let registry = format::registry_from_traced(registry)?;
+        Ok(Self::from_formats(root, registry))
+    }
+
+    fn from_formats(root: Format, registry: SerdeRegistry) -> Self {
+        let mut has_bytes = false;
+        let mut has_int128 = false;
+        let mut find = |format: &Format| {
+            has_bytes |= matches!(format, Format::Bytes);
+            has_int128 |= matches!(format, Format::I128 | Format::U128);
         };
+        root.visit(&mut find);
+        for container in registry.values() {
+            container.visit(&mut find);
+        }
+        Trace {
+            root,
+            registry,
+            has_bytes,
+            has_int128,
+        }
+    }
 
-        if let Some(format) = serde_reg.get(&name) {
-            let format = format.clone();
-            // For newtype structs, use the inner type's name
-            let schema_name = match &format {
-                ContainerFormat::NewTypeStruct(inner_format) => {
-                    if let Format::TypeName(inner_name) = inner_format.as_ref() {
-                        inner_name.clone()
-                    } else {
-                        name
+    pub(crate) fn root(&self) -> &Format {
+        &self.root
+    }
+
+    pub(crate) fn registry(&self) -> &SerdeRegistry {
+        &self.registry
+    }
+
+    pub(crate) fn has_bytes(&self) -> bool {
+        self.has_bytes
+    }
+
+    pub(crate) fn has_int128(&self) -> bool {
+        self.has_int128
+    }
+
+    /// The trace of the type below the root, such as `T` in `Option<T>`, sharing the
+    /// registry.
+    fn inner(&self, below: impl FnOnce(&Format) -> Option<&Box<Format>>) -> Option<Arc<Trace>> {
+        Some(Arc::new(Trace {
+            root: below(&self.root)?.as_ref().clone(),
+            registry: self.registry.clone(),
+            has_bytes: self.has_bytes,
+            has_int128: self.has_int128,
+        }))
+    }
+}
+
+/// The traces of the types seen so far, including failed ones.
+///
+/// A lock that readers share, as every spec generation looks the traces up again, and poem
+/// can build several specs at once.
+static TRACES: LazyLock<RwLock<HashMap<TypeId, Option<Arc<Trace>>>>> =
+    LazyLock::new(Default::default);
+
+/// What `Foreign<T>::name()` returned for each `T`, as poem asks for it on every operation
+/// using the type, while routing as well as for the spec.
+static NAMES: LazyLock<RwLock<HashMap<TypeId, &'static str>>> = LazyLock::new(Default::default);
+
+/// What `Foreign<T>::schema_ref()` returned for each `T`.
+static SCHEMA_REFS: LazyLock<RwLock<HashMap<TypeId, MetaSchemaRef>>> =
+    LazyLock::new(Default::default);
+
+/// Looks `key` up in `cache`, computing and storing its value the first time.
+///
+/// The value is computed without holding the lock, as resolving one type can resolve
+/// others. The configuration is frozen once read, so a value never goes stale.
+fn cached<V: Clone>(
+    cache: &RwLock<HashMap<TypeId, V>>,
+    key: TypeId,
+    compute: impl FnOnce() -> V,
+) -> V {
+    if let Some(value) = cache.read().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return value.clone();
+    }
+    let value = compute();
+    cache
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key)
+        .or_insert(value)
+        .clone()
+}
+
+/// The traces given with `trace_sample`, `trace_from` or `trace_from_json`, by the full path
+/// of their type, for wrappers such as `Vec<T>` whose own trace fails without them.
+static GIVEN: LazyLock<RwLock<HashMap<&'static str, Arc<Trace>>>> = LazyLock::new(Default::default);
+
+/// Stores `trace` as the trace of `T`, forgetting what was resolved from an earlier one.
+fn replace_trace<T: 'static>(trace: Trace) {
+    let key = TypeId::of::<T>();
+    let trace = Arc::new(trace);
+    GIVEN
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(std::any::type_name::<T>(), trace.clone());
+    TRACES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, Some(trace));
+    NAMES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&key);
+    SCHEMA_REFS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&key);
+}
+
+/// Feeds sample values to a tracer before the type is traced.
+#[cfg(feature = "trace")]
+type Prime<'a> = &'a dyn Fn(&mut Tracer, &mut Samples) -> serde_reflection::Result<()>;
+
+/// Traces `T`, running the tracer at most once per type.
+///
+/// `name()`, `schema_ref()` and `register()` all need the trace, and every spec generation
+/// calls them again. Failures are cached too, so an untraceable type is not retried.
+pub(crate) fn trace_type<T: DeserializeOwned + 'static>() -> Option<Arc<Trace>> {
+    let key = TypeId::of::<T>();
+    if let Some(trace) = TRACES.read().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return trace.clone();
+    }
+    // Trace without holding the lock, in case deserializing `T` touches another wrapper.
+    #[cfg(feature = "trace")]
+    let trace = trace_fresh::<T>().map(Arc::new);
+    // Without tracing, the only traces are those loaded with `Foreign::trace_from_json`.
+    #[cfg(not(feature = "trace"))]
+    let trace = None;
+    TRACES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key)
+        .or_insert(trace)
+        .clone()
+}
+
+/// Traces `T` from its `Deserialize` impl, logging why it failed.
+#[cfg(feature = "trace")]
+fn trace_fresh<T: DeserializeOwned + 'static>() -> Option<Trace> {
+    trace_uncached::<T>(config::current().tracer_config, &|_, _| Ok(()))
+        .inspect_err(|err| {
+            tracing::debug!(
+                "jsonwrap: `{}` could not be traced: {err}; see `Foreign::trace_sample`",
+                type_name::<T>()
+            )
+        })
+        .ok()
+}
+
+#[cfg(feature = "trace")]
+fn trace_uncached<T: DeserializeOwned>(
+    tracer_config: fn() -> TracerConfig,
+    prime: Prime,
+) -> serde_reflection::Result<Trace> {
+    let Trace {
+        root, mut registry, ..
+    } = trace_pass::<T>(tracer_config, prime)?;
+    // Below an `Option`, a sequence or a map, serde_reflection stops exploring once the
+    // wrapper's format is known, so an enum only reached that way keeps the one variant its
+    // first pass visited, however often the trace restarts. Each further trace starts the
+    // variants of those enums one later, until every variant has been visited once.
+    let mut partial: BTreeMap<String, (usize, BTreeMap<u32, Named<VariantFormat>>)> = registry
+        .iter()
+        .filter_map(|(name, format)| match format {
+            ContainerFormat::Enum(variants) if partially_traced(variants) => {
+                let first = variants.values().next()?.clone();
+                let found = BTreeMap::from([(0, first)]);
+                Some((name.clone(), (variant_count(variants), found)))
+            }
+            _ => None,
+        })
+        .collect();
+    let most = partial.values().map(|(count, _)| *count).max().unwrap_or(0);
+    for skip in 1..most {
+        let enums: Vec<String> = partial
+            .iter()
+            .filter(|(_, (count, _))| skip < *count)
+            .map(|(name, _)| name.clone())
+            .collect();
+        let trace = stand_in::skipping(enums.clone(), skip, || {
+            trace_pass::<T>(tracer_config, prime)
+        });
+        // Leave the enums partial rather than fail a type the first trace could describe.
+        let Ok(trace) = trace else { continue };
+        for (name, format) in trace.registry {
+            match (partial.get_mut(&name), format) {
+                (Some((count, found)), ContainerFormat::Enum(mut variants))
+                    if enums.contains(&name) =>
+                {
+                    // The provisional number of the first of the `count - skip` variants left.
+                    let first = u32::MAX - (*count - skip - 1) as u32;
+                    if let Some(variant) = variants.remove(&first) {
+                        found.insert(skip as u32, variant);
                     }
                 }
-                _ => name,
+                (_, format) => {
+                    registry.entry(name).or_insert(format);
+                }
+            }
+        }
+    }
+    for (name, (count, found)) in partial {
+        if found.len() == count {
+            registry.insert(name, ContainerFormat::Enum(found));
+        }
+    }
+    Ok(Trace::from_formats(root, registry))
+}
+
+/// Traces `T` once, restarting while enums nested in it are missing variants.
+#[cfg(feature = "trace")]
+fn trace_pass<T: DeserializeOwned>(
+    tracer_config: fn() -> TracerConfig,
+    prime: Prime,
+) -> serde_reflection::Result<Trace> {
+    // `trace_simple_type` only walks every variant of `T` itself. Enums nested inside it are
+    // explored one variant per pass, and only while their names are known, so their names
+    // are collected from failed attempts and the trace restarted until none are missing.
+    let mut enums: Vec<String> = Vec::new();
+    loop {
+        let mut tracer = Tracer::new(tracer_config());
+        let mut samples = Samples::new();
+        prime(&mut tracer, &mut samples)?;
+        let root = loop {
+            let (root, _) = tracer.trace_type_with_seed(&samples, stand_in::Seed::<T>::new())?;
+            let mut pending = false;
+            for name in &enums {
+                pending |= tracer.check_incomplete_enum(name).is_some();
+            }
+            if !pending {
+                break root;
+            }
+        };
+        match tracer.registry() {
+            Ok(registry) => return Trace::new(root, registry),
+            Err(serde_reflection::Error::MissingVariants(missing))
+                if missing.iter().any(|name| !enums.contains(name)) =>
+            {
+                enums.extend(missing);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Traces the `Serialize` impl of `T` from `sample`.
+///
+/// Only what `sample` contains is seen, so a `None`, an empty sequence or map, or an enum
+/// variant it does not use leaves part of the format unknown, which fails.
+#[cfg(feature = "trace")]
+pub(crate) fn trace_serialized<T: Serialize>(
+    tracer_config: fn() -> TracerConfig,
+    sample: &T,
+) -> serde_reflection::Result<Trace> {
+    let mut tracer = Tracer::new(tracer_config());
+    let mut samples = Samples::new();
+    let (root, _) = tracer.trace_value(&mut samples, &stand_in::Sample(sample))?;
+    Trace::new(root, tracer.registry()?)
+}
+
+/// The container a type named `name` is documented as.
+///
+/// Newtypes are transparent: `Id(Inner)` is documented as `Inner` itself. Newtypes around
+/// anything but a named container, such as `UserId(u64)`, are their own container.
+fn transparent_container(name: &str, serde_reg: &SerdeRegistry) -> String {
+    let mut container = name.to_string();
+    let mut seen = vec![container.clone()];
+    while let Some(ContainerFormat::NewTypeStruct(inner)) = serde_reg.get(&container)
+        && let Format::TypeName(inner) = inner.as_ref()
+        && !seen.contains(inner)
+    {
+        container = inner.clone();
+        seen.push(container.clone());
+    }
+    container
+}
+
+/// The name per-type settings for `T` are stored under: the serde container `T` is
+/// documented as, or its Rust name if it cannot be traced.
+///
+/// Settings given for a transparent newtype thereby apply to the component it resolves to,
+/// the same one `Foreign<Inner>` registers.
+///
+/// Runs before the configuration is installed, so it traces with the `tracer_config` being
+/// built rather than through the cache.
+#[cfg(feature = "trace")]
+pub(crate) fn metadata_key<T: DeserializeOwned + 'static>(config: &ForeignConfig) -> String {
+    let name = type_name::<T>();
+    let Ok(trace) = trace_uncached::<T>(config.tracer_config, &|_, _| Ok(())) else {
+        return name;
+    };
+    let Format::TypeName(root) = &trace.root else {
+        return name;
+    };
+    let container = transparent_container(root, &trace.registry);
+    if container != *root {
+        tracing::debug!(
+            "jsonwrap: settings for `{name}` apply to `{container}`, which it wraps transparently"
+        );
+    }
+    container
+}
+
+/// Without the `trace` feature, the Rust name of `T`.
+#[cfg(not(feature = "trace"))]
+pub(crate) fn metadata_key<T: 'static>(_config: &ForeignConfig) -> String {
+    type_name::<T>()
+}
+
+/// How a foreign type appears in the spec.
+///
+/// `name()`, `schema_ref()` and `register()` all go through this, so the name that is
+/// referenced is always the one that gets registered.
+enum Resolution {
+    /// A named container, registered as the component `name` and built from the
+    /// serde container `container`.
+    Component {
+        name: String,
+        container: String,
+        trace: Arc<Trace>,
+    },
+    /// A type without a container of its own, such as `Vec<Inner>` or `u64`, described inline.
+    Inline { name: String, trace: Arc<Trace> },
+    /// A type with a representation declared in the config, described inline.
+    Declared { name: String, schema: MetaSchemaRef },
+    /// A type serde_reflection could not trace, registered under its Rust name and built
+    /// from the samples configured for the type whose full path is `full`.
+    Untraced { name: String, full: String },
+    /// A type described by its `JsonSchema` impl, see [`ForeignConfig::schemars`].
+    #[cfg(feature = "schemars")]
+    Schemars {
+        name: String,
+        register: fn(&mut Registry) -> MetaSchemaRef,
+    },
+    /// `Option<T>`, named after `T` and described as its nullable schema, just like
+    /// `ForeignOpt<T>`.
+    Nullable(Box<Resolution>),
+    /// `Vec<T>`, an array whose items are described like `T` on its own, so that they
+    /// reference the same component `Foreign<T>` registers.
+    Array {
+        name: String,
+        items: Box<Resolution>,
+    },
+}
+
+impl Resolution {
+    fn of<T: DeserializeOwned + 'static>() -> Self {
+        let full = std::any::type_name::<T>();
+        match config::current().docs_sides.get(&TypeId::of::<T>()) {
+            Some(docs) if docs.side != ForeignDocsSide::Deserialize => {
+                Self::resolve(full, &|| Some(docs.trace.clone()))
+            }
+            _ => Self::resolve(full, &trace_type::<T>),
+        }
+    }
+
+    /// How `T` is documented as a request body read through [`ForeignJson`].
+    ///
+    /// The same as [`of`](Self::of), except with [`ForeignDocsSide::Both`], where `T` is
+    /// traced from its `Deserialize` impl and its component gets an `Input` suffix.
+    fn input_of<T: DeserializeOwned + 'static>() -> Self {
+        let both = config::current()
+            .docs_sides
+            .get(&TypeId::of::<T>())
+            .is_some_and(|docs| docs.side == ForeignDocsSide::Both);
+        if !both {
+            return Self::of::<T>();
+        }
+        match Self::resolve(std::any::type_name::<T>(), &trace_type::<T>) {
+            Resolution::Component {
+                name,
+                container,
+                trace,
+            } => Resolution::Component {
+                name: format!("{name}Input"),
+                container,
+                trace,
+            },
+            resolution => resolution,
+        }
+    }
+
+    /// Resolves the type whose full path is `full`, tracing it only if needed.
+    fn resolve(full: &str, trace: &dyn Fn() -> Option<Arc<Trace>>) -> Self {
+        if let Some(inner) = wrapped(full, std::any::type_name::<Option<()>>()) {
+            let trace = || {
+                trace()?.inner(|root| match root {
+                    Format::Option(inner) => Some(inner),
+                    _ => None,
+                })
+            };
+            return match Self::resolve(inner, &trace) {
+                // `Option<Option<T>>` collapses into one nullable `T`, see `format_to_schema`.
+                nullable @ Resolution::Nullable(_) => nullable,
+                inner => Resolution::Nullable(Box::new(inner)),
+            };
+        }
+        if let Some(inner) = wrapped(full, std::any::type_name::<Vec<()>>()) {
+            let trace = || {
+                trace()?.inner(|root| match root {
+                    Format::Seq(inner) => Some(inner),
+                    _ => None,
+                })
+            };
+            let items = Self::resolve(inner, &trace);
+            return Resolution::Array {
+                name: format!("[{}]", items.name()),
+                items: Box::new(items),
+            };
+        }
+
+        if let Some(positions) = tuple_positions(full) {
+            // Named after its positions, as the path-based names below only fit named types.
+            let names: Vec<String> = positions
+                .into_iter()
+                .map(|position| Self::resolve(position, &|| None).name().to_string())
+                .collect();
+            let name = format!("({})", names.join(", "));
+            return match trace() {
+                Some(trace) => Resolution::Inline { name, trace },
+                None => Resolution::Untraced {
+                    name,
+                    full: full.to_string(),
+                },
+            };
+        }
+
+        let type_name = short_name(full);
+        let suffix = generic_suffix(full);
+        let rust_name = format!("{type_name}{suffix}");
+        // Declared settings are stored under the container name the type is traced as, so
+        // the root finds its own through its full path rather than its short name.
+        let key = config::current().declared_key(full);
+        if let Some(schema) = key.and_then(declared_schema) {
+            return Resolution::Declared {
+                name: rust_name,
+                schema,
             };
-            poem_reg.create_schema::<Self, _>(schema_name, |poem_reg| {
-                container_to_schema(&format, &serde_reg, poem_reg)
-            });
         }
+        #[cfg(feature = "bitflags")]
+        if let Some(flags) = key.and_then(flags::declared) {
+            return Resolution::Declared {
+                name: rust_name,
+                schema: flags.schema(),
+            };
+        }
+        #[cfg(feature = "schemars")]
+        if let Some(&register) = key.and_then(|key| config::current().json_schemas.get(key)) {
+            return Resolution::Schemars {
+                name: rust_name,
+                register,
+            };
+        }
+        let given = || {
+            GIVEN
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(full)
+                .cloned()
+        };
+        let Some(trace) = trace().or_else(given) else {
+            return Resolution::Untraced {
+                name: rust_name,
+                full: full.to_string(),
+            };
+        };
+        let Format::TypeName(root) = &trace.root else {
+            return Resolution::Inline {
+                name: rust_name,
+                trace,
+            };
+        };
+        if let Some(schema) = string_type(root) {
+            return Resolution::Declared {
+                name: rust_name,
+                schema,
+            };
+        }
+
+        let container = transparent_container(root, &trace.registry);
+
+        // serde names a generic container after the bare type, so the root one carries
+        // its arguments to keep different instantiations apart.
+        let name = if container == *root {
+            format!("{root}{suffix}")
+        } else {
+            container.clone()
+        };
+        Resolution::Component {
+            name,
+            container,
+            trace,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Resolution::Component { name, .. }
+            | Resolution::Inline { name, .. }
+            | Resolution::Declared { name, .. }
+            | Resolution::Untraced { name, .. } => name,
+            #[cfg(feature = "schemars")]
+            Resolution::Schemars { name, .. } => name,
+            Resolution::Nullable(inner) => inner.name(),
+            Resolution::Array { name, .. } => name,
+        }
+    }
+
+    /// The trace the schema is built from, if it was traced.
+    fn trace(&self) -> Option<&Trace> {
+        match self {
+            Resolution::Component { trace, .. } | Resolution::Inline { trace, .. } => Some(trace),
+            Resolution::Declared { .. } | Resolution::Untraced { .. } => None,
+            #[cfg(feature = "schemars")]
+            Resolution::Schemars { .. } => None,
+            Resolution::Nullable(inner) | Resolution::Array { items: inner, .. } => inner.trace(),
+        }
+    }
+
+    /// Registers whatever the schema refers to and returns the schema itself.
+    fn register(&self, poem_reg: &mut Registry) -> MetaSchemaRef {
+        match self {
+            Resolution::Component {
+                name,
+                container,
+                trace,
+            } => {
+                register_container(name, container, &trace.registry, poem_reg);
+                MetaSchemaRef::Reference(name.clone())
+            }
+            Resolution::Inline { trace, .. } => {
+                format_to_schema(&trace.root, &trace.registry, poem_reg)
+            }
+            Resolution::Declared { schema, .. } => schema.clone(),
+            #[cfg(feature = "schemars")]
+            Resolution::Schemars { register, .. } => register(poem_reg),
+            Resolution::Untraced { name, full } => {
+                let config = config::current();
+                let samples = config.samples_for(full);
+                poem_reg.create_schema::<(), _>(name.clone(), |poem_reg| match samples {
+                    Some((repr, samples)) => enums::sample_schema(name, &repr, samples, poem_reg),
+                    None => {
+                        audit::opaque(name);
+                        MetaSchema {
+                            ty: "object",
+                            ..MetaSchema::ANY
+                        }
+                    }
+                });
+                MetaSchemaRef::Reference(name.clone())
+            }
+            Resolution::Nullable(inner) => {
+                let schema_ref = inner.register(poem_reg);
+                nullable_in(schema_ref, poem_reg)
+            }
+            Resolution::Array { items, .. } => MetaSchemaRef::Inline(Box::new(MetaSchema {
+                ty: "array",
+                items: Some(Box::new(items.register(poem_reg))),
+                ..MetaSchema::ANY
+            })),
+        }
+    }
+}
+
+/// Renders the generic arguments of the type path `full` as a component name suffix,
+/// `Page<Item>` giving `_Item`. Empty for non-generic types.
+fn generic_suffix(full: &str) -> String {
+    let (Some(start), Some(end)) = (full.find('<'), full.rfind('>')) else {
+        return String::new();
+    };
+    full[start + 1..end]
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .filter(|token| !token.is_empty())
+        .map(|path| format!("_{}", path.rsplit("::").next().unwrap_or(path)))
+        .collect()
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> Foreign<T> {
+    /// Traces `T` from a sample value, for types that cannot be traced from the type alone.
+    ///
+    /// Tracing deserializes `T` from placeholder values: zeros, empty strings and the first
+    /// variant tried of each enum. Types whose `Deserialize` rejects those fail with
+    /// `serde_reflection::Error::Custom` carrying their own message, for example
+    /// `NonZeroU16` inside a newtype. `T` then has an opaque schema, and the error is logged
+    /// at debug level. Given a sample, newtypes and tuple structs in it are deserialized from their
+    /// sampled values instead.
+    ///
+    /// Call it at startup, before the spec is built, for each such type. Later uses of
+    /// `Foreign<T>` reuse this trace. Enums deserialized through `deserialize_any`, such as
+    /// untagged enums, fail with `NotSupported("deserialize_any")` even with a sample; use
+    /// [`ForeignConfig::enum_samples`] for those.
+    ///
+    /// A struct with a `#[serde(flatten)]` map, such as `extra: HashMap<String, Foo>`, is
+    /// told apart from the sample: the keys it declares become its properties, and the
+    /// entries of the map its `additionalProperties`. The sample needs at least one entry.
+    #[cfg(feature = "trace")]
+    pub fn trace_sample(sample: &T) -> Result<(), serde_reflection::Error> {
+        let tracer_config = config::current().tracer_config;
+        let trace = trace_uncached::<T>(tracer_config, &|tracer, samples| {
+            tracer
+                .trace_value(samples, &stand_in::Sample(sample))
+                .map(|_| ())
+        })
+        .or_else(|err| open::trace(tracer_config, &type_name::<T>(), sample).map_err(|_| err))?;
+        replace_trace::<T>(trace);
+        Ok(())
+    }
+
+    /// Uses a trace recorded elsewhere for `T`: its root format and the registry of the
+    /// containers it refers to, for example a registry saved by a serde-generate build step.
+    ///
+    /// Like [`trace_sample`](Self::trace_sample), call it at startup; later uses of
+    /// `Foreign<T>` reuse this trace. The registry has to describe every container reachable
+    /// from `root`: registering `T` panics at the first field that refers to one it lacks,
+    /// rather than produce a spec with a reference to a component nothing registers.
+    #[cfg(feature = "trace")]
+    pub fn trace_from(
+        root: serde_reflection::Format,
+        registry: serde_reflection::Registry,
+    ) -> Result<(), serde_reflection::Error> {
+        replace_trace::<T>(Trace::new(root, registry)?);
+        Ok(())
+    }
+
+    /// Uses a trace saved as JSON for `T`: its root format and the registry of the containers
+    /// it refers to, as serde_reflection writes them, such as `{ "TYPENAME": "User" }` and
+    /// `{ "User": { "STRUCT": [{ "id": "U64" }] } }`.
+    ///
+    /// Like `trace_from`, but needing no `trace` feature, so builds without it can still
+    /// describe types from a trace saved by another build. Fails on JSON that is not a format
+    /// or a registry.
+    pub fn trace_from_json(root: Value, registry: Value) -> Result<(), serde_json::Error> {
+        let trace = Trace::from_formats(
+            serde_json::from_value(root)?,
+            serde_json::from_value(registry)?,
+        );
+        replace_trace::<T>(trace);
+        Ok(())
+    }
+
+    /// Parses `value` like a `Json<Foreign<T>>` body, enforcing the configured constraints.
+    ///
+    /// The error says where in `value` parsing failed and converts into a 400 response.
+    pub fn from_json_value(mut value: Value) -> Result<Self, ForeignParseError> {
+        let config = config::current();
+        if let Some(trace) = trace_type::<T>() {
+            if config.enforces_constraints() {
+                validate::check(&value, &trace.root, &trace.registry, config)?;
+            }
+            let full = std::any::type_name::<T>();
+            if decode::needed(&trace, full) {
+                decode::decode(&mut value, &trace, full)?;
+            }
+        }
+        error::from_value(&value).map(Foreign)
+    }
+
+    /// Parses a JSON body straight from its bytes, like [`from_json_value`](Self::from_json_value).
+    ///
+    /// Unless constraints are enforced, or base64 bytes or flags need decoding, which all work
+    /// on a `Value`, `T` is deserialized without building one. The `Value` is only built on
+    /// failure, to locate the error.
+    pub fn from_json_slice(bytes: &[u8]) -> Result<Self, ForeignParseError> {
+        let config = config::current();
+        let direct = !config.enforces_constraints()
+            && trace_type::<T>()
+                .is_none_or(|trace| !decode::needed(&trace, std::any::type_name::<T>()));
+        if direct
+            && !bytes.is_empty()
+            && let Ok(value) = serde_json::from_slice(bytes)
+        {
+            return Ok(Foreign(value));
+        }
+        let value = match bytes {
+            [] => Value::Null,
+            bytes => serde_json::from_slice(bytes)
+                .map_err(|err| ForeignParseError::new(String::new(), err))?,
+        };
+        Self::from_json_value(value)
+    }
+
+    fn register_components(resolution: Resolution, poem_reg: &mut Registry) -> MetaSchemaRef {
+        if let Some(trace) = resolution.trace()
+            && let Some(key) = composite_map_key(&trace.root, &trace.registry)
+            && config::current().ser_options::<T>().key_encoder.is_none()
+        {
+            let name = type_name::<T>();
+            panic!(
+                "jsonwrap: `{name}` contains a map keyed by {key:?}, which JSON objects cannot \
+                 represent; register an encoder with `ForeignConfig::map_key_encoder::<{name}>()`"
+            );
+        }
+        let schema_ref = resolution.register(poem_reg);
+        #[cfg(feature = "arrayvec")]
+        if let Some(capacity) = array_string::capacity::<T>() {
+            return annotate(schema_ref, |schema| schema.max_length = Some(capacity));
+        }
+        schema_ref
+    }
+
+    fn resolved_schema_ref(resolution: Resolution) -> MetaSchemaRef {
+        let mut scratch = Registry::new();
+        let schema_ref = Self::register_components(resolution, &mut scratch);
+        if config::current().inline_all {
+            return inline_refs(schema_ref, &scratch, &mut Vec::new());
+        }
+        schema_ref
+    }
+
+    /// The schema of `T` as a request body, see [`Resolution::input_of`].
+    pub(crate) fn input_schema_ref() -> MetaSchemaRef {
+        Self::resolved_schema_ref(Resolution::input_of::<T>())
+    }
+
+    /// Registers the components of `T` as a request body, see [`Resolution::input_of`].
+    pub(crate) fn register_input(poem_reg: &mut Registry) {
+        if config::current().inline_all {
+            return;
+        }
+        schemas::record(TypeId::of::<T>(), |poem_reg| {
+            Self::register_components(Resolution::of::<T>(), poem_reg);
+        });
+        Self::register_components(Resolution::input_of::<T>(), poem_reg);
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> Type for Foreign<T> {
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> std::borrow::Cow<'static, str> {
+        cached(&NAMES, TypeId::of::<T>(), || {
+            leak_str(Resolution::of::<T>().name())
+        })
+        .into()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        cached(&SCHEMA_REFS, TypeId::of::<T>(), || {
+            Self::resolved_schema_ref(Resolution::of::<T>())
+        })
+    }
+
+    fn register(poem_reg: &mut Registry) {
+        if config::current().inline_all {
+            return;
+        }
+        schemas::record(TypeId::of::<T>(), |poem_reg| {
+            Self::register_components(Resolution::of::<T>(), poem_reg);
+        });
+        Self::register_components(Resolution::of::<T>(), poem_reg);
     }
 
     fn as_raw_value(&self) -> Option<&Self::RawValueType> {
@@ -290,13 +1746,48 @@ impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> Type for Foreign<T
     }
 }
 
-impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> ToJSON for Foreign<T> {
+/// Serializes a foreign value, logging failures instead of silently producing no body.
+pub(crate) fn foreign_to_json<T: Serialize + 'static>(value: &T) -> Option<Value> {
+    let options = config::current().ser_options::<T>();
+    let value = ser::to_value(value, options);
+    // A flags type serialized transparently never reaches `serialize_newtype_struct`.
+    #[cfg(feature = "bitflags")]
+    let value = match config::current()
+        .declared_key(std::any::type_name::<T>())
+        .and_then(flags::declared)
+    {
+        Some(flags) => value.map(|value| flags.encode(value)),
+        None => value,
+    };
+    value
+        .inspect_err(|err| {
+            tracing::error!(
+                "jsonwrap: failed to serialize `{}`: {err}",
+                type_name::<T>()
+            )
+        })
+        .ok()
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ToJSON for Foreign<T> {
     fn to_json(&self) -> Option<Value> {
-        serde_json::to_value(&self.0).ok()
+        foreign_to_json(&self.0)
     }
 }
 
-impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> Type for ForeignOpt<T> {
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ParseFromJSON
+    for Foreign<T>
+{
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        Self::from_json_value(value.unwrap_or_default()).map_err(ParseError::custom)
+    }
+}
+
+impl<T> Type for ForeignOpt<T>
+where
+    T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static,
+    Option<T>: SchemaSource,
+{
     const IS_REQUIRED: bool = false;
     type RawValueType = Self;
     type RawElementValueType = Self;
@@ -306,26 +1797,14 @@ impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> Type for ForeignOp
     }
 
     fn schema_ref() -> MetaSchemaRef {
-        // Return an inline schema that marks the type as nullable
-        let base_ref = Foreign::<T>::schema_ref();
-        match base_ref {
-            MetaSchemaRef::Reference(name) => {
-                MetaSchemaRef::Inline(Box::new(MetaSchema {
-                    title: Some(name.clone()),
-                    nullable: true,
-                    all_of: vec![MetaSchemaRef::Reference(name)],
-                    ..MetaSchema::ANY
-                }))
-            }
-            MetaSchemaRef::Inline(mut schema) => {
-                schema.nullable = true;
-                MetaSchemaRef::Inline(schema)
-            }
-        }
+        // The same nullable schema as `Foreign<Option<T>>`.
+        Foreign::<Option<T>>::schema_ref()
     }
 
     fn register(poem_reg: &mut Registry) {
         Foreign::<T>::register(poem_reg);
+        // For the nullable copy `schema_ref` may point to.
+        Foreign::<Option<T>>::register(poem_reg);
     }
 
     fn as_raw_value(&self) -> Option<&Self::RawValueType> {
@@ -339,8 +1818,33 @@ impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> Type for ForeignOp
     }
 }
 
-impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> ToJSON for ForeignOpt<T> {
+impl<T> ParseFromJSON for ForeignOpt<T>
+where
+    T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static,
+    Option<T>: SchemaSource,
+{
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        // Only an explicit `null` or a missing value maps to `None`; anything else must parse.
+        match value.unwrap_or_default() {
+            Value::Null => Ok(ForeignOpt(None)),
+            value => Foreign::<T>::parse_from_json(Some(value))
+                .map(|Foreign(v)| ForeignOpt(Some(v)))
+                .map_err(ParseError::propagate),
+        }
+    }
+}
+
+impl<T> ToJSON for ForeignOpt<T>
+where
+    T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static,
+    Option<T>: SchemaSource,
+{
     fn to_json(&self) -> Option<Value> {
-        self.0.as_ref().and_then(|v| serde_json::to_value(v).ok())
+        // `None` is the `null` the nullable schema promises. poem reads a missing value as
+        // nothing to write, so a `Vec<ForeignOpt<T>>` would otherwise drop its `None` items.
+        match &self.0 {
+            Some(value) => foreign_to_json(value),
+            None => Some(Value::Null),
+        }
     }
 }
@@ -0,0 +1,65 @@
+//! A string that is never empty, documented with `minLength: 1`.
+
+use std::fmt;
+use std::ops::Deref;
+
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// The serde container name, which is also the component the type is registered as.
+pub(crate) const NAME: &str = "NonEmptyString";
+
+/// A `String` that deserialization rejects when empty.
+///
+/// It serializes as a plain string. As a field of a foreign type, or as `Foreign<NonEmptyString>`,
+/// it is registered as the `NonEmptyString` component with `minLength: 1`, and parsing a
+/// `Foreign` body fails on `""`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NonEmptyString(String);
+
+impl NonEmptyString {
+    /// Wraps `value`, or returns `None` if it is empty.
+    pub fn new(value: impl Into<String>) -> Option<Self> {
+        let value = value.into();
+        (!value.is_empty()).then_some(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for NonEmptyString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NonEmptyString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for NonEmptyString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(NAME, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for NonEmptyString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "NonEmptyString")]
+        struct Inner(String);
+
+        let Inner(value) = Inner::deserialize(deserializer)?;
+        Self::new(value).ok_or_else(|| de::Error::invalid_length(0, &"a non-empty string"))
+    }
+}
@@ -0,0 +1,389 @@
+//! Structs that keep the keys they do not declare in a `#[serde(flatten)]` map.
+//!
+//! serde derives such a struct as a map, which serde_reflection cannot trace: the declared
+//! fields and the collected entries share one map, so their formats do not unify, and
+//! reading it back needs `deserialize_any`. [`trace`] tells the two apart from a sample
+//! instead, and the value format of the entries is documented as `additionalProperties`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{LazyLock, RwLock};
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, MapAccess, Visitor};
+use serde::ser::{self, Impossible, Serialize};
+use serde_json::Value;
+use serde_reflection::{
+    ContainerFormat, Error, Format, FormatHolder, Named, Result, Samples, Tracer, TracerConfig,
+};
+
+use crate::Trace;
+use crate::format;
+
+/// The format of the flattened entries of each open struct, by container name.
+static ADDITIONAL: LazyLock<RwLock<HashMap<String, format::Format>>> =
+    LazyLock::new(Default::default);
+
+/// The format of the entries the open struct `container` collects, if it is one.
+pub(crate) fn additional(container: &str) -> Option<format::Format> {
+    ADDITIONAL
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(container)
+        .cloned()
+}
+
+/// Traces `sample` as the struct `name` with a flattened map.
+///
+/// The declared fields are the keys `T` reads on its own; the others are handed to the
+/// flattened map through `deserialize_any`. Like any trace from a sample, every field has to
+/// be filled in, and the map needs at least one entry. Fails if `sample` is not written as a
+/// map, collects no entries, or collects entries of different formats, as a struct with
+/// several flattened fields does.
+pub(crate) fn trace<T: Serialize + DeserializeOwned>(
+    tracer_config: fn() -> TracerConfig,
+    name: &str,
+    sample: &T,
+) -> Result<Trace> {
+    let written = serde_json::to_value(sample).map_err(|err| Error::Custom(err.to_string()))?;
+    let Value::Object(written) = written else {
+        return Err(Error::Custom(format!("`{name}` is not written as a map")));
+    };
+    let mut collected = BTreeSet::new();
+    T::deserialize(Reader {
+        entries: &written,
+        collected: &mut collected,
+    })
+    .map_err(|err| Error::Custom(err.to_string()))?;
+    if collected.is_empty() {
+        return Err(Error::Custom(format!(
+            "`{name}` collects none of the entries of its sample into a flattened map"
+        )));
+    }
+
+    let mut tracer = Tracer::new(tracer_config());
+    let mut samples = Samples::new();
+    let mut entries = Vec::new();
+    sample.serialize(Writer {
+        tracer: &mut tracer,
+        samples: &mut samples,
+        entries: &mut entries,
+    })?;
+    let mut fields = Vec::new();
+    let mut additional: Option<Format> = None;
+    for (key, format) in entries {
+        if !collected.contains(&key) {
+            fields.push(Named {
+                name: key,
+                value: format,
+            });
+            continue;
+        }
+        match &mut additional {
+            Some(additional) => additional.unify(format)?,
+            None => additional = Some(format),
+        }
+    }
+    let additional = format::Format::from_traced(additional.unwrap_or(Format::unknown()))?;
+
+    let mut registry = tracer.registry()?;
+    registry.insert(name.to_string(), ContainerFormat::Struct(fields));
+    ADDITIONAL
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_string(), additional);
+    Trace::new(Format::TypeName(name.to_string()), registry)
+}
+
+/// Deserializes a struct from its written entries, noting those read with `deserialize_any`.
+struct Reader<'a> {
+    entries: &'a serde_json::Map<String, Value>,
+    collected: &'a mut BTreeSet<String>,
+}
+
+impl<'de> de::Deserializer<'de> for Reader<'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> serde_json::Result<V::Value> {
+        Err(de::Error::custom("not a struct with a flattened map"))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        visitor.visit_map(Entries {
+            entries: self.entries.iter(),
+            value: None,
+            collected: self.collected,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct struct enum identifier
+        ignored_any
+    }
+}
+
+struct Entries<'a> {
+    entries: serde_json::map::Iter<'a>,
+    value: Option<(&'a String, &'a Value)>,
+    collected: &'a mut BTreeSet<String>,
+}
+
+impl<'de> MapAccess<'de> for Entries<'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> serde_json::Result<Option<K::Value>> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some((key, value));
+        seed.deserialize(de::value::StrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> serde_json::Result<V::Value> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before its key"))?;
+        seed.deserialize(Noting {
+            key,
+            value,
+            collected: self.collected,
+        })
+    }
+}
+
+/// The value of the entry `key`, noted as collected if it is read with `deserialize_any`,
+/// which is how serde buffers the entries a struct does not declare for its flattened fields.
+struct Noting<'a, 'c> {
+    key: &'a String,
+    value: &'a Value,
+    collected: &'c mut BTreeSet<String>,
+}
+
+macro_rules! forward {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+            self.value.$method(visitor)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Noting<'de, '_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        self.collected.insert(self.key.clone());
+        self.value.deserialize_any(visitor)
+    }
+
+    forward! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_option
+        deserialize_unit deserialize_seq deserialize_map deserialize_identifier
+        deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+}
+
+/// Serializes a struct written as a map, tracing the format of each entry on its own.
+struct Writer<'a> {
+    tracer: &'a mut Tracer,
+    samples: &'a mut Samples,
+    entries: &'a mut Vec<(String, Format)>,
+}
+
+fn not_a_map() -> Error {
+    Error::Custom("not a struct with a flattened map".to_string())
+}
+
+macro_rules! not_a_map {
+    ($($method:ident($($ty:ty),*);)*) => {$(
+        fn $method(self, $(_: $ty),*) -> Result<()> {
+            Err(not_a_map())
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for Writer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Self;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    not_a_map! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<()> {
+        Err(not_a_map())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, _: &T) -> Result<()> {
+        Err(not_a_map())
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<()> {
+        Err(not_a_map())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_a_map())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple> {
+        Err(not_a_map())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_a_map())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_a_map())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct> {
+        Err(not_a_map())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_a_map())
+    }
+}
+
+impl ser::SerializeMap for Writer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        let key = match serde_json::to_value(key) {
+            Ok(Value::String(key)) => key,
+            _ => return Err(Error::Custom("map keys must be strings".to_string())),
+        };
+        self.entries.push((key, Format::unknown()));
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let (format, _) = self
+            .tracer
+            .trace_value(self.samples, &crate::stand_in::Sample(value))?;
+        match self.entries.last_mut() {
+            Some((_, slot)) => *slot = format,
+            None => return Err(Error::Custom("value written before its key".to_string())),
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
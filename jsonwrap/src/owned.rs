@@ -0,0 +1,149 @@
+//! Borrowing foreign types, documented and transported through an owned projection.
+
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use poem_openapi::registry::{MetaSchemaRef, Registry};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{Foreign, SchemaSource};
+
+/// A foreign type `T` that borrows, such as `Event<'a>`, carried as its owned counterpart `O`.
+///
+/// `Foreign<T>` needs `T: DeserializeOwned + 'static`, which types holding `&'a str` fields
+/// cannot satisfy. `ForeignOwned` is documented, serialized and parsed as `O`, usually the
+/// owned variant the foreign crate provides, and only converts from `T` at the boundary.
+/// Name `T` with its `'static` lifetime in handler signatures, `ForeignOwned<Event<'static>,
+/// OwnedEvent>`; the marker does not hold a `T`.
+///
+/// When `O` belongs to another crate, `From<T>` cannot be implemented for it; wrap it with
+/// [`owned_projection!`](crate::owned_projection) instead.
+pub struct ForeignOwned<T, O>(pub O, PhantomData<fn() -> T>);
+
+impl<T, O> ForeignOwned<T, O> {
+    /// Converts a borrowed value into its projection.
+    pub fn new(value: T) -> Self
+    where
+        O: From<T>,
+    {
+        Self(O::from(value), PhantomData)
+    }
+
+    pub fn from_owned(value: O) -> Self {
+        Self(value, PhantomData)
+    }
+
+    /// The owned projection, for example a parsed request body, to borrow a `T` from with
+    /// the foreign crate's own API.
+    pub fn into_owned(self) -> O {
+        self.0
+    }
+}
+
+impl<T, O> Deref for ForeignOwned<T, O> {
+    type Target = O;
+
+    fn deref(&self) -> &O {
+        &self.0
+    }
+}
+
+impl<T, O: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> Type
+    for ForeignOwned<T, O>
+{
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> std::borrow::Cow<'static, str> {
+        Foreign::<O>::name()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        Foreign::<O>::schema_ref()
+    }
+
+    fn register(poem_reg: &mut Registry) {
+        Foreign::<O>::register(poem_reg);
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl<T, O: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ToJSON
+    for ForeignOwned<T, O>
+{
+    fn to_json(&self) -> Option<Value> {
+        crate::foreign_to_json(&self.0)
+    }
+}
+
+impl<T, O: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ParseFromJSON
+    for ForeignOwned<T, O>
+{
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        Foreign::<O>::parse_from_json(value)
+            .map(|Foreign(value)| Self::from_owned(value))
+            .map_err(ParseError::propagate)
+    }
+}
+
+/// Defines a local wrapper around the owned variant of a borrowing foreign type, convertible
+/// from the borrowed type through one of its methods.
+///
+/// ```ignore
+/// jsonwrap::owned_projection! {
+///     /// `events::OwnedEvent`, built with `Event::to_owned`.
+///     pub struct EventProjection(events::OwnedEvent) from events::Event via to_owned;
+/// }
+///
+/// let body: ForeignOwned<events::Event<'static>, EventProjection> = ForeignOwned::new(event);
+/// ```
+///
+/// The wrapper serializes exactly like the owned type and is documented as its component.
+/// The borrowed type is written without its lifetime, which must be its only generic
+/// parameter.
+#[macro_export]
+macro_rules! owned_projection {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($owned:ty) from $($borrowed:ident)::+ via $method:ident;
+    ) => {
+        $(#[$attr])*
+        $vis struct $name(pub $owned);
+
+        impl<'a> ::core::convert::From<$($borrowed)::+<'a>> for $name {
+            fn from(value: $($borrowed)::+<'a>) -> Self {
+                Self(value.$method())
+            }
+        }
+
+        impl $crate::__private::serde::Serialize for $name {
+            fn serialize<S: $crate::__private::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::core::result::Result<S::Ok, S::Error> {
+                $crate::__private::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D: $crate::__private::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> ::core::result::Result<Self, D::Error> {
+                <$owned as $crate::__private::serde::Deserialize<'de>>::deserialize(deserializer)
+                    .map(Self)
+            }
+        }
+    };
+}
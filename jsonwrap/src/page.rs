@@ -0,0 +1,107 @@
+//! The list envelope shared by paginated endpoints, around foreign items.
+
+use poem_openapi::registry::{MetaSchemaRef, Registry};
+use poem_openapi::types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{Foreign, SchemaSource};
+
+/// One page of foreign items, `{ "items": [...], "next_cursor": ..., "total": ... }`.
+///
+/// It is documented like `Foreign<ForeignPage<T>>`: a component named after the item type,
+/// `ForeignPage_User` for `ForeignPage<User>`, whose `items` reference the component
+/// `Foreign<T>` registers. `next_cursor` and `total` are written as `null` when unset.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignPage<T> {
+    pub items: Vec<T>,
+    /// The cursor to request the next page with, `None` on the last page.
+    pub next_cursor: Option<String>,
+    /// The number of items across all pages, if the source knows it.
+    pub total: Option<u64>,
+}
+
+impl<T> ForeignPage<T> {
+    /// A page of `items`, without a cursor or total.
+    pub fn new(items: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+            next_cursor: None,
+            total: None,
+        }
+    }
+
+    pub fn next_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.next_cursor = Some(cursor.into());
+        self
+    }
+
+    pub fn total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Converts every item, keeping the cursor and total, for example from a foreign crate's
+    /// type to the one the API documents.
+    pub fn map_items<U>(self, f: impl FnMut(T) -> U) -> ForeignPage<U> {
+        ForeignPage {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ForeignPage<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        Self::new(items)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> Type
+    for ForeignPage<T>
+{
+    const IS_REQUIRED: bool = true;
+    type RawValueType = Self;
+    type RawElementValueType = Self;
+
+    fn name() -> std::borrow::Cow<'static, str> {
+        Foreign::<Self>::name()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        Foreign::<Self>::schema_ref()
+    }
+
+    fn register(poem_reg: &mut Registry) {
+        Foreign::<Self>::register(poem_reg);
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(std::iter::once(self))
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ToJSON
+    for ForeignPage<T>
+{
+    fn to_json(&self) -> Option<Value> {
+        crate::foreign_to_json(self)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ParseFromJSON
+    for ForeignPage<T>
+{
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        Foreign::<Self>::parse_from_json(value)
+            .map(|Foreign(page)| page)
+            .map_err(ParseError::propagate)
+    }
+}
@@ -0,0 +1,171 @@
+use poem::http::HeaderValue;
+use poem_openapi::types::{ParseError, ParseFromParameter, ParseResult, ToHeader};
+use serde::de::{DeserializeOwned, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Number, Value};
+
+use crate::format::{ContainerFormat, Format, Registry as SerdeRegistry, VariantFormat};
+use crate::{Foreign, SchemaSource, foreign_to_json, trace_type};
+
+/// How a raw parameter string is turned into the JSON value `T` deserializes from.
+pub(crate) enum ParamKind {
+    Str,
+    Bool,
+    Int,
+    Uint,
+    /// Read into an `i128` or `u128` directly, as `Value` only holds 64-bit integers.
+    Int128,
+    Uint128,
+    Float,
+    /// Parsed as JSON, falling back to a plain string.
+    Json,
+    /// Object- or array-shaped, which has no sensible single-string encoding.
+    Unsupported(&'static str),
+}
+
+impl ParamKind {
+    /// Whether values of this kind serialize to a JSON string, number or bool.
+    pub(crate) fn is_scalar(&self) -> bool {
+        matches!(
+            self,
+            ParamKind::Str
+                | ParamKind::Bool
+                | ParamKind::Int
+                | ParamKind::Uint
+                | ParamKind::Int128
+                | ParamKind::Uint128
+                | ParamKind::Float
+        )
+    }
+}
+
+pub(crate) fn param_kind(format: &Format, serde_reg: &SerdeRegistry) -> ParamKind {
+    match format {
+        Format::Str | Format::Char => ParamKind::Str,
+        Format::Bool => ParamKind::Bool,
+        Format::I8 | Format::I16 | Format::I32 | Format::I64 => ParamKind::Int,
+        Format::U8 | Format::U16 | Format::U32 | Format::U64 => ParamKind::Uint,
+        Format::I128 => ParamKind::Int128,
+        Format::U128 => ParamKind::Uint128,
+        Format::F32 | Format::F64 => ParamKind::Float,
+        Format::Option(inner) => param_kind(inner, serde_reg),
+        Format::Seq(_) | Format::Tuple(_) | Format::TupleArray { .. } | Format::Bytes => {
+            ParamKind::Unsupported("an array")
+        }
+        Format::Map { .. } => ParamKind::Unsupported("an object"),
+        Format::TypeName(name) => match serde_reg.get(name) {
+            Some(ContainerFormat::NewTypeStruct(inner)) => param_kind(inner, serde_reg),
+            Some(ContainerFormat::Struct(_)) => ParamKind::Unsupported("an object"),
+            Some(ContainerFormat::TupleStruct(_)) => ParamKind::Unsupported("an array"),
+            Some(ContainerFormat::Enum(variants))
+                if variants
+                    .values()
+                    .all(|variant| matches!(variant.value, VariantFormat::Unit)) =>
+            {
+                ParamKind::Str
+            }
+            _ => ParamKind::Json,
+        },
+        Format::Unit => ParamKind::Json,
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ParseFromParameter
+    for Foreign<T>
+{
+    fn parse_from_parameter(value: &str) -> ParseResult<Self> {
+        let kind = trace_type::<T>()
+            .map(|trace| param_kind(trace.root(), trace.registry()))
+            .unwrap_or(ParamKind::Json);
+        let json = match kind {
+            ParamKind::Str => Value::String(value.to_string()),
+            ParamKind::Bool => Value::Bool(value.parse()?),
+            ParamKind::Int => Value::from(value.parse::<i64>()?),
+            ParamKind::Uint => Value::from(value.parse::<u64>()?),
+            // The decimal digits either `Int128Repr` documents, in the full 128-bit range.
+            ParamKind::Int128 | ParamKind::Uint128 => {
+                let wide = match kind {
+                    ParamKind::Int128 => Wide::Signed(value.parse()?),
+                    _ => Wide::Unsigned(value.parse()?),
+                };
+                return T::deserialize(wide)
+                    .map(Foreign)
+                    .map_err(ParseError::custom);
+            }
+            ParamKind::Float => Number::from_f64(value.parse()?)
+                .map(Value::Number)
+                .ok_or_else(|| ParseError::custom("expected a finite number"))?,
+            ParamKind::Json => {
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+            }
+            ParamKind::Unsupported(shape) => {
+                return Err(ParseError::custom(format!(
+                    "the type is {shape} and cannot be used as a query, path or header parameter"
+                )));
+            }
+        };
+        serde_json::from_value(json)
+            .map(Foreign)
+            .map_err(ParseError::custom)
+    }
+}
+
+/// A 128-bit integer parameter, handed to `T` as it is.
+enum Wide {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl<'de> Deserializer<'de> for Wide {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Wide::Signed(value) => visitor.visit_i128(value),
+            Wide::Unsigned(value) => visitor.visit_u128(value),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Deserializes as `T`, so plain poem extractors that go through serde compose with
+/// `Foreign`, such as `poem::web::Path<(Foreign<A>, Foreign<B>)>` for several segments.
+///
+/// poem-openapi's `Path` takes one parameter per segment and uses `ParseFromParameter`
+/// instead.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Foreign<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Foreign)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static> ToHeader
+    for Foreign<T>
+{
+    fn to_header(&self) -> Option<HeaderValue> {
+        // Scalars are written as plain text, so string newtypes don't come out quoted.
+        let text = match foreign_to_json(&self.0)? {
+            Value::Null => return None,
+            Value::String(s) => s,
+            value @ (Value::Number(_) | Value::Bool(_)) => value.to_string(),
+            value => serde_json::to_string(&value).ok()?,
+        };
+        HeaderValue::from_str(&text).ok()
+    }
+}
@@ -0,0 +1,262 @@
+//! Markdown documentation for foreign types, for portals that do not run Swagger UI.
+//!
+//! The schemas come from the same resolution as the spec, so a page documents exactly the
+//! components `Foreign<T>` registers.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{Foreign, Resolution, SchemaSource};
+
+/// Renders `T` and every component it refers to as Markdown.
+///
+/// Each component gets a `##` section, `T` first and the others in the order they are
+/// first referenced. Objects are rendered as a table of their properties, and references
+/// link to the section of the component they name.
+pub fn markdown_for<T: Serialize + DeserializeOwned + SchemaSource + Send + Sync + 'static>()
+-> String {
+    let mut registry = Registry::new();
+    let root = Foreign::<T>::register_components(Resolution::of::<T>(), &mut registry);
+
+    let mut out = String::new();
+    let mut queue = VecDeque::new();
+    let mut seen = HashSet::new();
+    match &root {
+        MetaSchemaRef::Reference(name) => {
+            seen.insert(name.clone());
+            queue.push_back(name.clone());
+        }
+        MetaSchemaRef::Inline(schema) => {
+            let name = <Foreign<T> as poem_openapi::types::Type>::name();
+            section(&mut out, &name, schema, &registry);
+            enqueue(&root, &mut seen, &mut queue);
+        }
+    }
+    while let Some(name) = queue.pop_front() {
+        let Some(schema) = registry.schemas.get(&name) else {
+            continue;
+        };
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        section(&mut out, &name, schema, &registry);
+        enqueue(
+            &MetaSchemaRef::Inline(Box::new(schema.clone())),
+            &mut seen,
+            &mut queue,
+        );
+    }
+    out
+}
+
+/// Writes the section documenting the component `name`.
+fn section(out: &mut String, name: &str, schema: &MetaSchema, registry: &Registry) {
+    let _ = writeln!(out, "## {name}\n");
+    if let Some(description) = schema.description {
+        let _ = writeln!(out, "{description}\n");
+    }
+
+    if !schema.properties.is_empty() {
+        out.push_str("| Property | Type | Required | Nullable | Constraints | Description |\n");
+        out.push_str("|----------|------|----------|----------|-------------|-------------|\n");
+        for (property, schema_ref) in &schema.properties {
+            let inline = inline(schema_ref);
+            let _ = writeln!(
+                out,
+                "| `{property}` | {} | {} | {} | {} | {} |",
+                type_label(schema_ref),
+                yes_no(schema.required.contains(property)),
+                yes_no(nullable(schema_ref, registry)),
+                inline.map(constraints).unwrap_or_default(),
+                inline.and_then(|schema| schema.description).unwrap_or(""),
+            );
+        }
+        return;
+    }
+
+    let branches = if schema.one_of.is_empty() {
+        &schema.any_of
+    } else {
+        &schema.one_of
+    };
+    if !branches.is_empty() {
+        match &schema.discriminator {
+            Some(discriminator) => {
+                let _ = writeln!(
+                    out,
+                    "One of the following, told apart by `{}`:\n",
+                    discriminator.property_name
+                );
+            }
+            None => out.push_str("One of the following:\n\n"),
+        }
+        for branch in branches {
+            let _ = writeln!(out, "- {}", branch_label(branch));
+        }
+        return;
+    }
+
+    let _ = writeln!(
+        out,
+        "Type: {}",
+        type_label(&MetaSchemaRef::Inline(Box::new(schema.clone())))
+    );
+    let constraints = constraints(schema);
+    if !constraints.is_empty() {
+        let _ = writeln!(out, "\nConstraints: {constraints}");
+    }
+}
+
+/// Describes a `oneOf`/`anyOf` branch, spelling out `{ "Variant": payload }` objects.
+fn branch_label(branch: &MetaSchemaRef) -> String {
+    if let Some(schema) = inline(branch)
+        && let [(variant, payload)] = schema.properties.as_slice()
+        && schema.required == [*variant]
+    {
+        return format!("`{{ \"{variant}\": … }}` with {}", type_label(payload));
+    }
+    let label = type_label(branch);
+    match inline(branch).map(constraints) {
+        Some(constraints) if !constraints.is_empty() => format!("{label}, {constraints}"),
+        _ => label,
+    }
+}
+
+/// A short, linked description of the type a schema accepts.
+fn type_label(schema_ref: &MetaSchemaRef) -> String {
+    let schema = match schema_ref {
+        MetaSchemaRef::Reference(name) => return format!("[`{name}`](#{})", anchor(name)),
+        MetaSchemaRef::Inline(schema) => schema,
+    };
+    if let [single] = schema.all_of.as_slice() {
+        return type_label(single);
+    }
+    match schema.ty {
+        "array" => match &schema.items {
+            Some(items) => format!("array of {}", type_label(items)),
+            None => "array".to_string(),
+        },
+        "object" if schema.additional_properties.is_some() => {
+            let values = schema.additional_properties.as_deref().map(type_label);
+            format!("map of {}", values.unwrap_or_default())
+        }
+        "object" if !schema.properties.is_empty() => {
+            let properties: Vec<_> = schema
+                .properties
+                .iter()
+                .map(|(property, _)| format!("`{property}`"))
+                .collect();
+            format!("`object` with {}", properties.join(", "))
+        }
+        "" if !schema.one_of.is_empty() || !schema.any_of.is_empty() => schema
+            .one_of
+            .iter()
+            .chain(&schema.any_of)
+            .map(type_label)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        "" => "any".to_string(),
+        ty => match schema.format {
+            Some(format) => format!("`{ty}` ({format})"),
+            None => format!("`{ty}`"),
+        },
+    }
+}
+
+/// The validation keywords of a schema, as a comma separated list.
+fn constraints(schema: &MetaSchema) -> String {
+    let mut parts = Vec::new();
+    if !schema.enum_items.is_empty() {
+        let values: Vec<_> = schema.enum_items.iter().map(literal).collect();
+        parts.push(format!("one of {}", values.join(", ")));
+    }
+    if let Some(minimum) = schema.minimum {
+        parts.push(format!("≥ {minimum}"));
+    }
+    if let Some(maximum) = schema.maximum {
+        parts.push(format!("≤ {maximum}"));
+    }
+    let bounds = [
+        (schema.min_length, "at least", "characters"),
+        (schema.max_length, "at most", "characters"),
+        (schema.min_items, "at least", "items"),
+        (schema.max_items, "at most", "items"),
+        (schema.min_properties, "at least", "entries"),
+        (schema.max_properties, "at most", "entries"),
+    ];
+    for (bound, relation, unit) in bounds {
+        if let Some(bound) = bound {
+            parts.push(format!("{relation} {bound} {unit}"));
+        }
+    }
+    if let Some(pattern) = &schema.pattern {
+        parts.push(format!("matches `{pattern}`"));
+    }
+    if let Some(default) = &schema.default {
+        parts.push(format!("default {}", literal(default)));
+    }
+    parts.join(", ")
+}
+
+/// Queues the components `schema_ref` refers to that have not been queued yet.
+fn enqueue(schema_ref: &MetaSchemaRef, seen: &mut HashSet<String>, queue: &mut VecDeque<String>) {
+    let schema = match schema_ref {
+        MetaSchemaRef::Reference(name) => {
+            if seen.insert(name.clone()) {
+                queue.push_back(name.clone());
+            }
+            return;
+        }
+        MetaSchemaRef::Inline(schema) => schema,
+    };
+    let nested = schema
+        .properties
+        .iter()
+        .map(|(_, schema_ref)| schema_ref)
+        .chain(schema.items.as_deref())
+        .chain(schema.additional_properties.as_deref())
+        .chain(&schema.one_of)
+        .chain(&schema.any_of)
+        .chain(&schema.all_of);
+    for schema_ref in nested {
+        enqueue(schema_ref, seen, queue);
+    }
+}
+
+fn inline(schema_ref: &MetaSchemaRef) -> Option<&MetaSchema> {
+    match schema_ref {
+        MetaSchemaRef::Inline(schema) => Some(schema),
+        MetaSchemaRef::Reference(_) => None,
+    }
+}
+
+/// Whether `schema_ref` accepts `null`, looking through references to nullable components.
+fn nullable(schema_ref: &MetaSchemaRef, registry: &Registry) -> bool {
+    match schema_ref {
+        MetaSchemaRef::Inline(schema) => schema.nullable,
+        MetaSchemaRef::Reference(name) => registry
+            .schemas
+            .get(name)
+            .is_some_and(|schema| schema.nullable),
+    }
+}
+
+fn literal(value: &Value) -> String {
+    format!("`{value}`")
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+/// The anchor GitHub-flavoured Markdown gives the heading `## {name}`.
+fn anchor(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
@@ -0,0 +1,84 @@
+//! Declared wire representations for types whose serde output tracing cannot see.
+//!
+//! Crates such as uuid and chrono serialize differently depending on their own feature flags
+//! and on whether the serializer is human-readable, and their deserializers reject the
+//! placeholder values serde_reflection feeds them. A [`Repr`] declared through
+//! [`ForeignConfig::declare_repr`](crate::ForeignConfig::declare_repr) replaces the traced
+//! schema, and is checked against a serialized sample when the type is registered.
+
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef};
+use serde_json::Value;
+
+/// The JSON shape a type is declared to serialize as.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Repr {
+    /// Any string.
+    String,
+    /// A string in the given OpenAPI format, such as `"date-time"` or `"uri"`.
+    FormattedString(&'static str),
+    /// A hyphenated UUID, `"67e55044-10b1-426f-9247-bb680e5fe0c8"`.
+    HyphenatedString,
+    /// A UUID as 32 hex digits without hyphens.
+    SimpleString,
+    /// An integer, such as a Unix timestamp.
+    Integer,
+    /// Any JSON number.
+    Number,
+    /// An array of byte values, optionally of a fixed length. This is what uuid writes when
+    /// its `serde` support is asked for the compact form.
+    ByteArray { len: Option<usize> },
+}
+
+impl Repr {
+    pub(crate) fn schema(&self) -> MetaSchemaRef {
+        let schema = match self {
+            Repr::String => MetaSchema::new("string"),
+            Repr::FormattedString(format) => MetaSchema::new_with_format("string", format),
+            Repr::HyphenatedString => MetaSchema::new_with_format("string", "uuid"),
+            Repr::SimpleString => MetaSchema {
+                pattern: Some("^[0-9a-fA-F]{32}$".to_string()),
+                ..MetaSchema::new("string")
+            },
+            Repr::Integer => MetaSchema::new("integer"),
+            Repr::Number => MetaSchema::new("number"),
+            Repr::ByteArray { len } => MetaSchema {
+                items: Some(Box::new(MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    minimum: Some(0.0),
+                    maximum: Some(255.0),
+                    ..MetaSchema::new("integer")
+                })))),
+                min_items: *len,
+                max_items: *len,
+                ..MetaSchema::new("array")
+            },
+        };
+        MetaSchemaRef::Inline(Box::new(schema))
+    }
+
+    /// Whether `value` has the declared shape.
+    pub(crate) fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Repr::String | Repr::FormattedString(_), Value::String(_)) => true,
+            (Repr::HyphenatedString, Value::String(s)) => {
+                s.len() == 36
+                    && s.char_indices().all(|(i, c)| match i {
+                        8 | 13 | 18 | 23 => c == '-',
+                        _ => c.is_ascii_hexdigit(),
+                    })
+            }
+            (Repr::SimpleString, Value::String(s)) => {
+                s.len() == 32 && s.chars().all(|c| c.is_ascii_hexdigit())
+            }
+            (Repr::Integer, Value::Number(n)) => n.is_i64() || n.is_u64(),
+            (Repr::Number, Value::Number(_)) => true,
+            (Repr::ByteArray { len }, Value::Array(items)) => {
+                len.is_none_or(|len| items.len() == len)
+                    && items
+                        .iter()
+                        .all(|item| item.as_u64().is_some_and(|byte| byte <= 255))
+            }
+            _ => false,
+        }
+    }
+}
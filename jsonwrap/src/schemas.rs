@@ -0,0 +1,82 @@
+//! A poem endpoint serving the components of the foreign types in the spec, one at a time.
+//!
+//! Every `Foreign<T>` records itself when poem-openapi registers it while building the
+//! spec. The endpoint registers the recorded types again into a fresh registry on each
+//! request, so it serves the same schemas under the same names as the spec.
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+
+use poem::error::NotFoundError;
+use poem::web::{Json, Path};
+use poem::{Route, get, handler};
+use poem_openapi::registry::{MetaSchema, Registry};
+
+type Registrar = fn(&mut Registry);
+
+/// The foreign types registered so far, in registration order.
+static REGISTERED: LazyLock<Mutex<Vec<(TypeId, Registrar)>>> = LazyLock::new(Default::default);
+
+/// Records that the components of the type `id` are registered by `registrar`.
+pub(crate) fn record(id: TypeId, registrar: Registrar) {
+    let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+    if !registered.iter().any(|(seen, _)| *seen == id) {
+        registered.push((id, registrar));
+    }
+}
+
+/// The components of every recorded foreign type, by name.
+fn components() -> BTreeMap<String, MetaSchema> {
+    let registrars: Vec<Registrar> = REGISTERED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(_, registrar)| *registrar)
+        .collect();
+    let mut registry = Registry::new();
+    for registrar in registrars {
+        registrar(&mut registry);
+    }
+    registry.schemas
+}
+
+/// A route serving the component schemas of the foreign types in the spec, as JSON.
+///
+/// - `GET /` lists the component names.
+/// - `GET /_all` returns every component, keyed by name, like `components.schemas`.
+/// - `GET /{name}` returns one component, or `404` if there is none by that name.
+///
+/// Types are recorded when a spec is built, for example by `OpenApiService::spec` or the
+/// Swagger UI endpoint, so nest it next to those:
+///
+/// ```ignore
+/// let app = Route::new()
+///     .nest("/", service)
+///     .nest("/docs", ui)
+///     .nest("/schemas", jsonwrap::schema_endpoint());
+/// ```
+pub fn schema_endpoint() -> Route {
+    Route::new()
+        .at("/", get(index))
+        .at("/_all", get(all))
+        .at("/:name", get(component))
+}
+
+#[handler]
+fn index() -> Json<Vec<String>> {
+    Json(components().into_keys().collect())
+}
+
+#[handler]
+fn all() -> Json<BTreeMap<String, MetaSchema>> {
+    Json(components())
+}
+
+#[handler]
+fn component(Path(name): Path<String>) -> poem::Result<Json<MetaSchema>> {
+    components()
+        .remove(&name)
+        .map(Json)
+        .ok_or_else(|| NotFoundError.into())
+}
@@ -0,0 +1,405 @@
+//! Serialization of foreign values into `serde_json::Value`.
+//!
+//! This mirrors `serde_json::to_value`, except that map keys which JSON cannot express
+//! directly are coerced according to the active [`ForeignConfig`](crate::ForeignConfig)
+//! instead of failing the whole response.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde::ser::{self, Error as _};
+use serde_json::{Error, Map, Value};
+
+use crate::case::KeyCasing;
+use crate::config::{BytesRepr, Int128Repr, NonFiniteFloats};
+
+/// Turns a map key that serialized to a non-scalar JSON value into an object key.
+pub type KeyEncoder = fn(&Value) -> String;
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Options {
+    pub(crate) key_encoder: Option<KeyEncoder>,
+    /// The casings of struct containers, when any are set.
+    pub(crate) key_cases: Option<&'static HashMap<String, KeyCasing>>,
+    pub(crate) bytes_repr: BytesRepr,
+    pub(crate) int128_repr: Int128Repr,
+    pub(crate) non_finite_floats: NonFiniteFloats,
+}
+
+pub(crate) fn to_value<T: Serialize + ?Sized>(value: &T, options: Options) -> Result<Value, Error> {
+    value.serialize(ValueSerializer { options })
+}
+
+#[derive(Clone, Copy)]
+struct ValueSerializer {
+    options: Options,
+}
+
+impl ValueSerializer {
+    fn map_key(self, key: Value) -> Result<String, Error> {
+        match key {
+            Value::String(key) => Ok(key),
+            Value::Number(key) => Ok(key.to_string()),
+            Value::Bool(key) => Ok(key.to_string()),
+            key => match self.options.key_encoder {
+                Some(encode) => Ok(encode(&key)),
+                None => Err(Error::custom(format!(
+                    "map key {key} is not a string, number or bool; register a key encoder with `ForeignConfig::map_key_encoder`"
+                ))),
+            },
+        }
+    }
+
+    /// Writes a NaN or infinite float as configured, as JSON has no number for it.
+    fn non_finite(self, v: f64) -> Result<Value, Error> {
+        let text = match v {
+            f64::INFINITY => "Infinity",
+            f64::NEG_INFINITY => "-Infinity",
+            _ => "NaN",
+        };
+        match self.options.non_finite_floats {
+            NonFiniteFloats::Null => Ok(Value::Null),
+            NonFiniteFloats::Reject => Err(Error::custom(format!(
+                "the float {text} has no JSON representation; choose one with `ForeignConfig::non_finite_floats`"
+            ))),
+            NonFiniteFloats::String => Ok(Value::String(text.to_string())),
+        }
+    }
+}
+
+macro_rules! delegate_primitive {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Value, Error> {
+                serde_json::value::Serializer.$method(v)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = MapBuilder;
+    type SerializeStructVariant = MapBuilder;
+
+    delegate_primitive!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_char(char),
+        serialize_str(&str),
+    );
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        if v.is_finite() {
+            return serde_json::value::Serializer.serialize_f32(v);
+        }
+        self.non_finite(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        if v.is_finite() {
+            return serde_json::value::Serializer.serialize_f64(v);
+        }
+        self.non_finite(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        match self.options.int128_repr {
+            Int128Repr::String => Ok(Value::String(v.to_string())),
+            Int128Repr::Number => serde_json::value::Serializer.serialize_i128(v),
+        }
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        match self.options.int128_repr {
+            Int128Repr::String => Ok(Value::String(v.to_string())),
+            Int128Repr::Number => serde_json::value::Serializer.serialize_u128(v),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        match self.options.bytes_repr {
+            BytesRepr::Base64 => Ok(crate::bytes::encode(v)),
+            BytesRepr::Array => serde_json::value::Serializer.serialize_bytes(v),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        #[cfg_attr(not(feature = "bitflags"), allow(unused_variables))] name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        let value = value.serialize(self)?;
+        #[cfg(feature = "bitflags")]
+        if let Some(flags) = crate::flags::declared(name) {
+            return Ok(flags.encode(value));
+        }
+        Ok(value)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        let mut map = Map::new();
+        map.insert(variant.to_string(), value.serialize(self)?);
+        Ok(Value::Object(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder, Error> {
+        Ok(SeqBuilder {
+            ser: self,
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqBuilder, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqBuilder, Error> {
+        let mut builder = self.serialize_seq(Some(len))?;
+        builder.variant = Some(variant);
+        Ok(builder)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapBuilder, Error> {
+        Ok(MapBuilder {
+            ser: self,
+            variant: None,
+            casing: None,
+            map: Map::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<MapBuilder, Error> {
+        let mut builder = self.serialize_map(Some(len))?;
+        builder.casing = self.options.key_cases.and_then(|cases| cases.get(name));
+        Ok(builder)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapBuilder, Error> {
+        let mut builder = self.serialize_map(Some(len))?;
+        builder.variant = Some(variant);
+        Ok(builder)
+    }
+}
+
+/// Wraps `value` as `{ variant: value }` when serializing an enum variant.
+fn wrap_variant(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(variant) => {
+            let mut map = Map::new();
+            map.insert(variant.to_string(), value);
+            Value::Object(map)
+        }
+        None => value,
+    }
+}
+
+struct SeqBuilder {
+    ser: ValueSerializer,
+    variant: Option<&'static str>,
+    items: Vec<Value>,
+}
+
+impl SeqBuilder {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        Ok(wrap_variant(self.variant, Value::Array(self.items)))
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+struct MapBuilder {
+    ser: ValueSerializer,
+    variant: Option<&'static str>,
+    /// How the fields of the struct being written are renamed.
+    casing: Option<&'static KeyCasing>,
+    map: Map<String, Value>,
+    next_key: Option<String>,
+}
+
+impl MapBuilder {
+    fn finish(self) -> Result<Value, Error> {
+        Ok(wrap_variant(self.variant, Value::Object(self.map)))
+    }
+}
+
+impl ser::SerializeMap for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = key.serialize(self.ser)?;
+        self.next_key = Some(self.ser.map_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| Error::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(key, value.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let key = match self.casing {
+            Some(casing) => casing.wire_name(key).into_owned(),
+            None => key.to_string(),
+        };
+        self.map.insert(key, value.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map.insert(key.to_string(), value.serialize(self.ser)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
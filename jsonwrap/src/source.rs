@@ -0,0 +1,46 @@
+//! Where the schema of a foreign type comes from, in builds with and without tracing.
+//!
+//! With the `trace` feature every type can be traced from its serde impls, so every type is a
+//! [`SchemaSource`]. Without it, a type has to be marked [`StaticSchema`], so that a missing
+//! schema is a compile error naming the alternatives rather than an opaque object in the spec.
+
+/// Marks a type whose schema is given at startup instead of traced, for builds without the
+/// `trace` feature.
+///
+/// Without tracing, the schema of `T` comes from [`ForeignConfig::declare_repr`],
+/// [`ForeignConfig::enum_samples`], `ForeignConfig::schemars` with the `schemars` feature,
+/// or a trace saved elsewhere and loaded with [`Foreign::trace_from_json`]. Implementing this
+/// states that one of them is given, and lets `Foreign<T>` compile; a type marked but left
+/// without one is described as any object, with a warning.
+///
+/// With `trace` the impl is not needed, but still allowed, so enabling the feature through
+/// another crate in the build does not break it.
+///
+/// [`ForeignConfig::declare_repr`]: crate::ForeignConfig::declare_repr
+/// [`ForeignConfig::enum_samples`]: crate::ForeignConfig::enum_samples
+/// [`Foreign::trace_from_json`]: crate::Foreign::trace_from_json
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no schema source, as jsonwrap is built without the `trace` feature",
+    label = "`{Self}` cannot be traced in this build",
+    note = "give it a schema with `ForeignConfig::declare_repr`, `ForeignConfig::enum_samples`, \
+            `ForeignConfig::schemars` or `Foreign::trace_from_json`, then mark it with \
+            `impl jsonwrap::StaticSchema for {Self} {{}}`",
+    note = "or enable the `trace` feature of jsonwrap to trace it from its serde impls"
+)]
+pub trait StaticSchema {}
+
+/// Described as the nullable schema of `T`.
+impl<T: StaticSchema> StaticSchema for Option<T> {}
+
+/// Described as an array of `T`.
+impl<T: StaticSchema> StaticSchema for Vec<T> {}
+
+/// A type `Foreign` can describe: any type with the `trace` feature, and a [`StaticSchema`]
+/// without it.
+pub trait SchemaSource {}
+
+#[cfg(feature = "trace")]
+impl<T: ?Sized> SchemaSource for T {}
+
+#[cfg(not(feature = "trace"))]
+impl<T: StaticSchema + ?Sized> SchemaSource for T {}
@@ -0,0 +1,36 @@
+//! `uuid::Uuid`.
+//!
+//! uuid parses the string it is given, or reads 16 bytes from compact formats, and the
+//! tracer's placeholders are neither, so `Uuid` is traced through
+//! [`stand_in`](crate::stand_in) as the container `uuid::Uuid`, and described as a `uuid`
+//! string.
+
+#[cfg(feature = "trace")]
+use std::any::type_name;
+
+#[cfg(feature = "trace")]
+use uuid::Uuid;
+
+#[cfg(feature = "trace")]
+use crate::stand_in::StandIn;
+
+const UUID: &str = "uuid::Uuid";
+
+/// A UUID that is not nil, which `NonNilUuid` rejects.
+#[cfg(feature = "trace")]
+static SAMPLE: Uuid = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+
+/// The stand-in for `Uuid`, if the visitor reads values of the type path `value`.
+#[cfg(feature = "trace")]
+pub(crate) fn stand_in(value: &str) -> Option<StandIn> {
+    (value == type_name::<Uuid>()).then(|| StandIn {
+        container: UUID,
+        text: "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        bytes: Some(SAMPLE.as_bytes()),
+    })
+}
+
+/// The string `format` of the container `name`, if it is the one `Uuid` is traced as.
+pub(crate) fn format(name: &str) -> Option<&'static str> {
+    (name == UUID).then_some("uuid")
+}
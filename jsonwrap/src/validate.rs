@@ -0,0 +1,116 @@
+//! Enforcement of [`FieldConstraints`](crate::FieldConstraints) on incoming JSON.
+//!
+//! The value is walked alongside the traced format so that constraints registered for a
+//! nested type are checked wherever that type appears in the body.
+
+use serde_json::Value;
+
+use crate::config::ForeignConfig;
+use crate::error::ForeignParseError;
+use crate::format::{ContainerFormat, Format, Registry as SerdeRegistry};
+
+/// Checks `value` against the enforced constraints of every struct it contains.
+///
+/// Errors locate the offending property with a JSON pointer into the body.
+pub(crate) fn check(
+    value: &Value,
+    format: &Format,
+    serde_reg: &SerdeRegistry,
+    config: &ForeignConfig,
+) -> Result<(), ForeignParseError> {
+    Walker {
+        serde_reg,
+        config,
+        path: String::new(),
+    }
+    .format(value, format)
+}
+
+struct Walker<'a> {
+    serde_reg: &'a SerdeRegistry,
+    config: &'a ForeignConfig,
+    path: String,
+}
+
+impl Walker<'_> {
+    fn format(&mut self, value: &Value, format: &Format) -> Result<(), ForeignParseError> {
+        match (format, value) {
+            (Format::TypeName(name), _) => match self.serde_reg.get(name) {
+                Some(container) => self.container(value, name, container),
+                None => Ok(()),
+            },
+            (Format::Option(inner), value) if !value.is_null() => self.format(value, inner),
+            (Format::Seq(inner), Value::Array(items)) => items
+                .iter()
+                .enumerate()
+                .try_for_each(|(index, item)| self.nested(&index.to_string(), item, inner)),
+            (Format::Tuple(formats), Value::Array(items)) => formats
+                .iter()
+                .zip(items)
+                .enumerate()
+                .try_for_each(|(index, (format, item))| {
+                    self.nested(&index.to_string(), item, format)
+                }),
+            (Format::Map { value: inner, .. }, Value::Object(map)) => map
+                .iter()
+                .try_for_each(|(key, item)| self.nested(key, item, inner)),
+            _ => Ok(()),
+        }
+    }
+
+    fn container(
+        &mut self,
+        value: &Value,
+        name: &str,
+        container: &ContainerFormat,
+    ) -> Result<(), ForeignParseError> {
+        match (container, value) {
+            (ContainerFormat::NewTypeStruct(inner), value) => self.format(value, inner),
+            (ContainerFormat::TupleStruct(formats), Value::Array(items)) => formats
+                .iter()
+                .zip(items)
+                .enumerate()
+                .try_for_each(|(index, (format, item))| {
+                    self.nested(&index.to_string(), item, format)
+                }),
+            (ContainerFormat::Struct(fields), Value::Object(map)) => {
+                for field in fields {
+                    let key = self.config.wire_name(name, &field.name);
+                    let Some(item) = map.get(key.as_ref()) else {
+                        continue;
+                    };
+                    if let Some(overrides) = self.config.field(name, &field.name)
+                        && overrides.constraints.enforce
+                        && let Err(problem) = overrides.constraints.check(item)
+                    {
+                        let path = format!("{}/{}", self.path, escape(&key));
+                        return Err(ForeignParseError::new(path, problem));
+                    }
+                    self.nested(&key, item, &field.value)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Descends into `value` under the pointer segment `segment`.
+    fn nested(
+        &mut self,
+        segment: &str,
+        value: &Value,
+        format: &Format,
+    ) -> Result<(), ForeignParseError> {
+        let len = self.path.len();
+        self.path.push('/');
+        self.path.push_str(&escape(segment));
+        let result = self.format(value, format);
+        self.path.truncate(len);
+        result
+    }
+}
+
+/// Escapes a JSON pointer reference token, RFC 6901.
+pub(crate) fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
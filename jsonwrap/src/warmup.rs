@@ -0,0 +1,37 @@
+//! Registering foreign types ahead of the first spec render.
+//!
+//! Tracing is cached per type, but the first render of a spec with hundreds of foreign types
+//! still traces them all, on whichever request asks for it first. Warming up traces them
+//! beforehand, typically on a blocking thread at startup.
+
+use poem_openapi::registry::Registry;
+
+/// Registers every type in `types` into a scratch registry, so that their traces are cached
+/// before a spec is built.
+///
+/// Pass the `register` function of each type, for example
+/// `<Foreign<User> as Type>::register`, or of whole responses and payloads. Registration is
+/// synchronous, so run it on a blocking thread:
+///
+/// ```ignore
+/// tokio::task::spawn_blocking(|| {
+///     jsonwrap::warmup(&[<Foreign<User> as Type>::register, <Foreign<Order> as Type>::register])
+/// });
+/// ```
+///
+/// A spec built before warming up finishes traces the types not warm yet itself, and gets
+/// the same schemas. With `inline_all` types register nothing, so they are only traced when
+/// their schema is first asked for.
+pub fn warmup(types: &[fn(&mut Registry)]) {
+    warmup_with_progress(types, |_, _| {});
+}
+
+/// Like [`warmup`], calling `progress(done, total)` after each type, for example to log how
+/// far warming up has got.
+pub fn warmup_with_progress(types: &[fn(&mut Registry)], mut progress: impl FnMut(usize, usize)) {
+    let mut scratch = Registry::new();
+    for (done, register) in types.iter().enumerate() {
+        register(&mut scratch);
+        progress(done + 1, types.len());
+    }
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{EnumRepr, Foreign, ForeignConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Both keys hold a different string in every sample, so inference alone could take either
+/// for the tag.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "op", content = "arg")]
+enum Command {
+    Get(String),
+    Set(String),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "payload")]
+enum Event {
+    Started,
+    Logged(String),
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .enum_samples([Command::Get("a".to_string()), Command::Set("b".to_string())])
+            .enum_repr::<Command>(EnumRepr::Adjacent {
+                tag: "op".to_string(),
+                content: "arg".to_string(),
+            })
+            .enum_samples([Event::Started, Event::Logged("hi".to_string())])
+            .enum_repr::<Event>(EnumRepr::Adjacent {
+                tag: "kind".to_string(),
+                content: "data".to_string(),
+            })
+            .install();
+    });
+}
+
+#[test]
+fn stated_tag_and_content_keys_name_the_properties() {
+    install();
+    let spec = common::spec_for::<Foreign<Command>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["Command"]["discriminator"]["propertyName"], "op");
+    assert_eq!(
+        schemas["Command_Set"],
+        json!({
+            "type": "object",
+            "required": ["op", "arg"],
+            "properties": {
+                "op": { "type": "string", "enum": ["Set"] },
+                "arg": { "type": "string" },
+            },
+        })
+    );
+    common::assert_valid::<Foreign<Command>>();
+}
+
+#[test]
+#[should_panic(expected = "whose `payload` is neither the tag nor the content")]
+fn samples_must_use_the_stated_content_key() {
+    install();
+    common::spec_for::<Foreign<Event>>();
+}
@@ -0,0 +1,22 @@
+#![cfg(all(feature = "trace", feature = "arrayvec"))]
+
+mod common;
+
+use arrayvec::ArrayString;
+use jsonwrap::Foreign;
+use serde_json::json;
+
+#[test]
+fn array_strings_carry_their_capacity_as_max_length() {
+    let spec = common::spec_for::<Foreign<ArrayString<16>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "maxLength": 16 })
+    );
+    common::assert_valid::<Foreign<ArrayString<16>>>();
+    assert_eq!(
+        common::validate_value(&spec, &json!("sixteen-chars-ok")),
+        Ok(())
+    );
+    assert!(common::validate_value(&spec, &json!("seventeen-chars!!")).is_err());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "trace")]
+
+use std::num::NonZeroU16;
+
+use jsonwrap::Foreign;
+use poem_openapi::types::Type;
+use serde::{Deserialize, Serialize};
+
+/// Untraceable: the tracer's zero is not a valid `NonZeroU16`.
+#[derive(Serialize, Deserialize)]
+struct Port(NonZeroU16);
+
+#[derive(Serialize, Deserialize)]
+struct Endpoint {
+    host: String,
+    port: Port,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Level {
+    Low,
+    High,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Alarm {
+    levels: Vec<Level>,
+}
+
+/// Below a `Vec` only the first variant is seen, as tracing the second one fails.
+#[derive(Serialize, Deserialize)]
+enum Forward {
+    Off,
+    To(Port),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Relay {
+    forwards: Vec<Forward>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Threshold {
+    level: Level,
+    value: f64,
+}
+
+#[test]
+fn untraceable_and_partial_types_are_reported() {
+    let opaque = jsonwrap::opaque_types(&[
+        <Foreign<Endpoint> as Type>::register,
+        <Foreign<Alarm> as Type>::register,
+        <Foreign<Relay> as Type>::register,
+        <Foreign<String> as Type>::register,
+    ]);
+    assert_eq!(opaque, ["Endpoint", "Forward"]);
+}
+
+#[test]
+fn enums_traced_in_full_elsewhere_are_not_reported() {
+    let opaque = jsonwrap::opaque_types(&[
+        <Foreign<Alarm> as Type>::register,
+        <Foreign<Threshold> as Type>::register,
+    ]);
+    assert!(opaque.is_empty(), "{opaque:?}");
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::fmt;
+
+use jsonwrap::Foreign;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+
+/// A byte buffer that serializes like `serde_bytes::ByteBuf`.
+#[derive(Debug, PartialEq)]
+struct ByteBuf(Vec<u8>);
+
+impl Serialize for ByteBuf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(ByteBuf(bytes))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Body {
+    Empty,
+    Attached { name: String, content: ByteBuf },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Upload {
+    checksum: ByteBuf,
+    thumbnail: Option<ByteBuf>,
+    body: Body,
+}
+
+#[test]
+fn bytes_are_base64_in_schema_and_payload() {
+    let spec = common::spec_for::<Foreign<Upload>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Upload"]["properties"]["checksum"],
+        json!({ "type": "string", "format": "byte" })
+    );
+
+    let upload = Upload {
+        checksum: ByteBuf(vec![0xde, 0xad, 0xbe, 0xef]),
+        thumbnail: None,
+        body: Body::Attached {
+            name: "a.txt".to_string(),
+            content: ByteBuf(b"hi".to_vec()),
+        },
+    };
+    let value = Foreign(upload).to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "checksum": "3q2+7w==",
+            "thumbnail": null,
+            "body": { "Attached": { "name": "a.txt", "content": "aGk=" } },
+        })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+
+    let Foreign(parsed) = Foreign::<Upload>::parse_from_json(Some(value)).unwrap_or_else(|err| {
+        panic!("{}", err.message());
+    });
+    assert_eq!(parsed.checksum, ByteBuf(vec![0xde, 0xad, 0xbe, 0xef]));
+    assert_eq!(
+        parsed.body,
+        Body::Attached {
+            name: "a.txt".to_string(),
+            content: ByteBuf(b"hi".to_vec()),
+        }
+    );
+}
+
+#[test]
+fn invalid_base64_is_rejected_with_its_path() {
+    let value = json!({
+        "checksum": "AAAA",
+        "thumbnail": "not base64!",
+        "body": "Empty",
+    });
+    let Err(err) = Foreign::<Upload>::from_json_value(value) else {
+        panic!("invalid base64 parsed");
+    };
+    assert_eq!(err.path(), "/thumbnail");
+}
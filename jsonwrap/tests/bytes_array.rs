@@ -0,0 +1,97 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::fmt;
+use std::sync::Once;
+
+use jsonwrap::{BytesRepr, Foreign, ForeignConfig};
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+
+/// A byte buffer that serializes like `serde_bytes::ByteBuf`.
+#[derive(Debug, PartialEq)]
+struct ByteBuf(Vec<u8>);
+
+impl Serialize for ByteBuf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(ByteBuf(bytes))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Upload {
+    checksum: ByteBuf,
+    thumbnail: Option<ByteBuf>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| ForeignConfig::new().bytes_repr(BytesRepr::Array).install());
+}
+
+#[test]
+fn bytes_are_arrays_in_schema_and_payload() {
+    install();
+    let spec = common::spec_for::<Foreign<Upload>>();
+    let byte_array = json!({
+        "type": "array",
+        "items": { "type": "integer", "format": "int32", "minimum": 0.0, "maximum": 255.0 },
+    });
+    let properties = &spec["components"]["schemas"]["Upload"]["properties"];
+    assert_eq!(properties["checksum"], byte_array);
+    common::assert_valid::<Foreign<Upload>>();
+
+    let upload = Upload {
+        checksum: ByteBuf(vec![0, 0xbe, 0xff]),
+        thumbnail: Some(ByteBuf(b"hi".to_vec())),
+    };
+    let value = Foreign(upload).to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({ "checksum": [0, 190, 255], "thumbnail": [104, 105] })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    assert!(common::validate_value(&spec, &json!({ "checksum": [256] })).is_err());
+
+    let Foreign(parsed) = Foreign::<Upload>::parse_from_json(Some(value)).unwrap_or_else(|err| {
+        panic!("{}", err.message());
+    });
+    assert_eq!(
+        parsed,
+        Upload {
+            checksum: ByteBuf(vec![0, 0xbe, 0xff]),
+            thumbnail: Some(ByteBuf(b"hi".to_vec())),
+        }
+    );
+}
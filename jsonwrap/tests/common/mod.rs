@@ -0,0 +1,128 @@
+//! Checks generated specs against the OpenAPI 3.0 meta-schema.
+
+// Each test binary uses a different subset of these helpers.
+#![allow(dead_code)]
+
+use std::sync::LazyLock;
+
+use jsonschema::Validator;
+use poem_openapi::registry::{MetaSchemaRef, Registry};
+use poem_openapi::types::Type;
+use serde_json::{Value, json};
+
+static META_SCHEMA: LazyLock<Validator> = LazyLock::new(|| {
+    let schema = serde_json::from_str(include_str!("../openapi-3.0.schema.json"))
+        .expect("meta-schema is valid JSON");
+    jsonschema::draft4::new(&schema).expect("meta-schema compiles")
+});
+
+/// A document with a single operation returning `schema`, with `registry` as its components.
+pub fn spec_with(schema: MetaSchemaRef, registry: &Registry) -> Value {
+    json!({
+        "openapi": "3.0.0",
+        "info": { "title": "jsonwrap", "version": "1" },
+        "paths": {
+            "/value": {
+                "get": {
+                    "responses": {
+                        "200": {
+                            "description": "",
+                            "content": { "application/json": { "schema": schema } }
+                        }
+                    }
+                }
+            }
+        },
+        "components": { "schemas": registry.schemas },
+    })
+}
+
+/// The spec poem-openapi would produce for an operation returning `T`.
+pub fn spec_for<T: Type>() -> Value {
+    let mut registry = Registry::new();
+    T::register(&mut registry);
+    spec_with(T::schema_ref(), &registry)
+}
+
+/// The response schema of a spec built by [`spec_for`].
+pub fn response_schema(spec: &Value) -> &Value {
+    &spec["paths"]["/value"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]
+}
+
+/// Validates `spec`, returning every violation with the path it was found at.
+pub fn validate(spec: &Value) -> Result<(), Vec<String>> {
+    let errors: Vec<_> = META_SCHEMA
+        .iter_errors(spec)
+        .map(|error| format!("{}: {error}", error.instance_path))
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validates `value` against the response schema of a spec built by [`spec_for`].
+pub fn validate_value(spec: &Value, value: &Value) -> Result<(), Vec<String>> {
+    validate_value_with(spec, value, false)
+}
+
+/// [`validate_value`] as OpenAPI 3.0.3 reads `nullable`: only next to a `type`, so a
+/// nullable schema that only composes others still applies them to `null`.
+pub fn validate_value_strict(spec: &Value, value: &Value) -> Result<(), Vec<String>> {
+    validate_value_with(spec, value, true)
+}
+
+fn validate_value_with(spec: &Value, value: &Value, strict: bool) -> Result<(), Vec<String>> {
+    // References point into `components`, so it travels along as part of the root schema.
+    let mut schema = json!({
+        "allOf": [response_schema(spec)],
+        "components": spec["components"],
+    });
+    expand_nullable(&mut schema, strict);
+    let validator = jsonschema::draft4::new(&schema).map_err(|error| vec![error.to_string()])?;
+    let errors: Vec<_> = validator
+        .iter_errors(value)
+        .map(|error| format!("{}: {error}", error.instance_path))
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Rewrites OpenAPI's `nullable: true`, which JSON Schema ignores, into an explicit
+/// alternative of `null`. When `strict`, a `nullable` without a `type` is dropped instead.
+fn expand_nullable(schema: &mut Value, strict: bool) {
+    match schema {
+        Value::Object(map) => {
+            map.values_mut()
+                .for_each(|value| expand_nullable(value, strict));
+            if map.get("nullable") == Some(&Value::Bool(true)) {
+                map.remove("nullable");
+                if strict && !map.contains_key("type") {
+                    return;
+                }
+                let inner = Value::Object(std::mem::take(map));
+                *schema = json!({ "anyOf": [{ "type": "null" }, inner] });
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| expand_nullable(item, strict)),
+        _ => {}
+    }
+}
+
+/// Panics with the violations if the spec for `T` is not a valid OpenAPI 3.0 document.
+pub fn assert_valid<T: Type>() {
+    let spec = spec_for::<T>();
+    if let Err(errors) = validate(&spec) {
+        panic!(
+            "invalid spec for `{}`:\n{}\n{spec:#}",
+            std::any::type_name::<T>(),
+            errors.join("\n")
+        );
+    }
+}
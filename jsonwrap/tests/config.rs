@@ -0,0 +1,19 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::{Foreign, ForeignConfig};
+use poem_openapi::types::Type;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Item {
+    id: u32,
+}
+
+#[test]
+#[should_panic(expected = "called after the configuration was already installed or read")]
+fn installing_after_first_use_panics() {
+    // poem-openapi may keep this name in operation metadata, so settings installed now
+    // could no longer apply consistently to the registered component.
+    let _ = Foreign::<Item>::name();
+    ForeignConfig::new().inline_all(true).install();
+}
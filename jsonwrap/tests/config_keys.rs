@@ -0,0 +1,143 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig, ForeignDocsSide, Repr};
+use serde_json::json;
+
+/// Types named like those in `b`, each given a setting.
+mod a {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Id(pub String);
+
+    #[derive(Clone, Serialize, Deserialize)]
+    #[serde(untagged)]
+    pub enum Event {
+        Started { at: u64 },
+        Stopped { code: i32 },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Record {
+        pub name: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Grid {
+        pub cells: BTreeMap<(u8, u8), String>,
+    }
+}
+
+/// The same short names as in `a`, without any setting.
+mod b {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "SerialId")]
+    pub struct Id {
+        pub serial: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "kind")]
+    pub enum Event {
+        Paused,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Entry")]
+    pub struct Record {
+        pub count: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Grid {
+        pub cells: BTreeMap<(u8, u8), String>,
+    }
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .declare_repr(
+                Repr::HyphenatedString,
+                a::Id("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string()),
+            )
+            .enum_samples([a::Event::Started { at: 1 }, a::Event::Stopped { code: 2 }])
+            .docs_side(
+                ForeignDocsSide::Serialize,
+                a::Record {
+                    name: "ada".to_string(),
+                },
+            )
+            .map_key_encoder::<a::Grid>(|key| format!("{}x{}", key[0], key[1]))
+            .install();
+    });
+}
+
+#[test]
+fn declared_reprs_apply_to_their_own_type() {
+    install();
+    let spec = common::spec_for::<Foreign<a::Id>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "uuid" })
+    );
+
+    let spec = common::spec_for::<Foreign<b::Id>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "$ref": "#/components/schemas/SerialId" })
+    );
+    assert_eq!(
+        spec["components"]["schemas"]["SerialId"]["properties"]["serial"]["type"],
+        "integer"
+    );
+}
+
+#[test]
+fn enum_samples_apply_to_their_own_type() {
+    install();
+    let spec = common::spec_for::<Foreign<a::Event>>();
+    assert!(spec["components"]["schemas"]["Event"]["oneOf"].is_array());
+
+    // `b::Event` cannot be traced and has no samples, so it is described as any object.
+    let spec = common::spec_for::<Foreign<b::Event>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Event"],
+        json!({ "type": "object" })
+    );
+}
+
+#[test]
+fn docs_sides_apply_to_their_own_type() {
+    install();
+    let spec = common::spec_for::<Foreign<b::Record>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "$ref": "#/components/schemas/Entry" })
+    );
+    assert!(spec["components"]["schemas"].get("Record").is_none());
+}
+
+#[test]
+fn map_key_encoders_apply_to_their_own_type() {
+    install();
+    common::assert_valid::<Foreign<a::Grid>>();
+}
+
+#[test]
+#[should_panic(expected = "register an encoder with `ForeignConfig::map_key_encoder::<Grid>()`")]
+fn map_key_encoders_are_not_shared_by_name() {
+    install();
+    common::spec_for::<Foreign<b::Grid>>();
+}
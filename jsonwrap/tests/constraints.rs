@@ -0,0 +1,103 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Once;
+
+use jsonwrap::{FieldConstraints, Foreign, ForeignConfig};
+use poem_openapi::types::ParseFromJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Quota {
+    status: String,
+    limits: HashMap<String, u32>,
+    owners: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Account {
+    quotas: Vec<Quota>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .field_constraints::<Quota>(
+                "status",
+                FieldConstraints::new()
+                    .enum_values(&["A", "S"])
+                    .display_names(&["Active", "Suspended"])
+                    .enforce(true),
+            )
+            .field_constraints::<Quota>(
+                "limits",
+                FieldConstraints::new().max_properties(2).enforce(true),
+            )
+            .field_constraints::<Quota>(
+                "owners",
+                FieldConstraints::new()
+                    .min_items(1)
+                    .max_items(3)
+                    .enforce(true),
+            )
+            .install();
+    });
+}
+
+fn parse_error(value: serde_json::Value) -> String {
+    match Foreign::<Account>::parse_from_json(Some(value)) {
+        Ok(_) => panic!("expected the body to be rejected"),
+        Err(error) => error.into_message(),
+    }
+}
+
+#[test]
+fn constraints_are_emitted() {
+    install();
+    let spec = common::spec_for::<Foreign<Account>>();
+    let properties = &spec["components"]["schemas"]["Quota"]["properties"];
+    assert_eq!(properties["status"]["enum"], json!(["A", "S"]));
+    assert_eq!(
+        properties["status"]["description"],
+        "`A`: Active; `S`: Suspended"
+    );
+    assert_eq!(properties["limits"]["maxProperties"], 2);
+    assert_eq!(properties["owners"]["minItems"], 1);
+    assert_eq!(properties["owners"]["maxItems"], 3);
+    common::assert_valid::<Foreign<Account>>();
+}
+
+#[test]
+fn constraints_are_enforced() {
+    install();
+    let quota = |status: &str, limits: usize, owners: usize| {
+        json!({
+            "status": status,
+            "limits": (0..limits).map(|i| (i.to_string(), i)).collect::<HashMap<_, _>>(),
+            "owners": vec!["me"; owners],
+        })
+    };
+
+    let valid = json!({ "quotas": [quota("A", 2, 1)] });
+    assert!(Foreign::<Account>::parse_from_json(Some(valid)).is_ok());
+
+    let error = parse_error(json!({ "quotas": [quota("A", 1, 1), quota("X", 1, 1)] }));
+    assert!(error.contains("/quotas/1/status"), "{error}");
+    assert!(error.contains(r#"["A", "S"]"#), "{error}");
+
+    let error = parse_error(json!({ "quotas": [quota("A", 3, 1)] }));
+    assert!(
+        error.contains("/quotas/0/limits: has 3 entries, at most 2"),
+        "{error}"
+    );
+
+    let error = parse_error(json!({ "quotas": [quota("A", 1, 0)] }));
+    assert!(
+        error.contains("/quotas/0/owners: has 0 items, at least 1"),
+        "{error}"
+    );
+}
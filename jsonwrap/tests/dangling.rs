@@ -0,0 +1,84 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::Foreign;
+use poem_openapi::registry::Registry;
+use poem_openapi::types::Type;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_reflection::{
+    ContainerFormat, Error, Format, Named, Registry as SerdeRegistry, Samples, Tracer, TracerConfig,
+};
+
+#[derive(Serialize, Deserialize)]
+struct Customer {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    sku: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    id: u64,
+    lines: Vec<Line>,
+    customer: Customer,
+}
+
+/// Traces `T` the way a build step recording its registry would.
+fn recorded<T: for<'de> Deserialize<'de>>() -> (Format, SerdeRegistry) {
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let (root, _) = tracer.trace_type::<T>(&Samples::new()).unwrap();
+    (root, tracer.registry().unwrap())
+}
+
+#[derive(Serialize, Deserialize)]
+struct Invoice {
+    order: Order,
+}
+
+#[test]
+fn recorded_traces_are_used() {
+    let (root, registry) = recorded::<Invoice>();
+    Foreign::<Invoice>::trace_from(root, registry).unwrap();
+    let spec = common::spec_for::<Foreign<Invoice>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Order"]["properties"]["customer"],
+        json!({ "$ref": "#/components/schemas/Customer" })
+    );
+    assert_eq!(common::validate(&spec), Ok(()));
+}
+
+#[test]
+#[should_panic(
+    expected = "`Order.customer`, registered for `Order`, refers to `Customer`, which its trace \
+                does not describe"
+)]
+fn references_missing_from_the_trace_name_their_field() {
+    let (root, mut registry) = recorded::<Order>();
+    registry.remove("Customer");
+    Foreign::<Order>::trace_from(root, registry).unwrap();
+    Foreign::<Order>::register(&mut Registry::new());
+}
+
+#[test]
+fn recorded_traces_with_unknown_formats_are_rejected() {
+    #[derive(Serialize, Deserialize)]
+    struct Draft {
+        customer: Customer,
+    }
+
+    let (root, mut registry) = recorded::<Draft>();
+    registry.insert(
+        "Customer".to_string(),
+        ContainerFormat::Struct(vec![Named {
+            name: "name".to_string(),
+            value: Format::unknown(),
+        }]),
+    );
+    let error = Foreign::<Draft>::trace_from(root, registry).unwrap_err();
+    assert!(matches!(error, Error::UnknownFormat), "{error:?}");
+}
@@ -0,0 +1,166 @@
+#![cfg(all(feature = "trace", feature = "chrono"))]
+
+mod common;
+
+use std::sync::Once;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use jsonwrap::{Foreign, ForeignConfig, ForeignOpt};
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Written as seconds, like `chrono::serde::ts_seconds`, but traced as a struct.
+#[derive(Serialize, Deserialize)]
+struct Timestamp {
+    seconds: i64,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .string_type("Timestamp", "unix-time")
+            .install();
+    });
+}
+
+#[test]
+fn aware_and_naive_date_times_have_different_formats() {
+    install();
+    let spec = common::spec_for::<Foreign<DateTime<Utc>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "date-time" })
+    );
+    let spec = common::spec_for::<ForeignOpt<NaiveDateTime>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "local-date-time", "nullable": true })
+    );
+    common::assert_valid::<Foreign<DateTime<Utc>>>();
+    common::assert_valid::<Foreign<NaiveDateTime>>();
+}
+
+#[derive(Serialize, Deserialize)]
+struct Booking {
+    created: DateTime<Utc>,
+    day: NaiveDate,
+    starts: Option<NaiveTime>,
+    checked_in: Timestamp,
+}
+
+#[test]
+fn date_and_time_fields_are_formatted_strings() {
+    install();
+    let spec = common::spec_for::<Foreign<Booking>>();
+    let schemas = &spec["components"]["schemas"];
+    let properties = &schemas["Booking"]["properties"];
+    assert_eq!(
+        properties["created"],
+        json!({ "type": "string", "format": "date-time" })
+    );
+    assert_eq!(
+        properties["day"],
+        json!({ "type": "string", "format": "date" })
+    );
+    assert_eq!(
+        properties["starts"],
+        json!({ "type": "string", "format": "time", "nullable": true })
+    );
+    assert_eq!(
+        properties["checked_in"],
+        json!({ "type": "string", "format": "unix-time" })
+    );
+    assert_eq!(
+        schemas.as_object().unwrap().keys().collect::<Vec<_>>(),
+        ["Booking"]
+    );
+    common::assert_valid::<Foreign<Booking>>();
+
+    let spec = common::spec_for::<Foreign<NaiveDate>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "date" })
+    );
+}
+
+#[derive(Serialize, Deserialize)]
+struct Slot {
+    created: DateTime<Utc>,
+    day: NaiveDate,
+}
+
+#[test]
+fn date_fields_round_trip() {
+    install();
+    let body = json!({ "created": "2024-05-01T09:30:00Z", "day": "2024-05-02" });
+    let slot = Foreign::<Slot>::from_json_value(body.clone()).unwrap();
+    assert_eq!(
+        slot.0.created,
+        Utc.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap()
+    );
+    assert_eq!(slot.0.day, NaiveDate::from_ymd_opt(2024, 5, 2).unwrap());
+    assert_eq!(slot.to_json(), Some(body.clone()));
+
+    let spec = common::spec_for::<Foreign<Slot>>();
+    assert_eq!(common::validate_value(&spec, &body), Ok(()));
+
+    let mut malformed = body;
+    malformed["day"] = json!("2024-13-01");
+    assert!(Foreign::<Slot>::from_json_value(malformed).is_err());
+}
+
+/// Named like chrono's types, but not one of them.
+mod mine {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct DateTime {
+        pub seconds: i64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct NaiveDate(pub u32);
+}
+
+#[test]
+fn types_named_like_chrono_keep_their_schema() {
+    install();
+    let spec = common::spec_for::<Foreign<mine::DateTime>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "$ref": "#/components/schemas/DateTime" })
+    );
+    assert_eq!(spec["components"]["schemas"]["DateTime"]["type"], "object");
+
+    let spec = common::spec_for::<Foreign<mine::NaiveDate>>();
+    assert_eq!(
+        spec["components"]["schemas"]["NaiveDate"]["type"],
+        "integer"
+    );
+}
+
+/// Only ever traced from a sample.
+#[derive(Serialize, Deserialize)]
+struct Shift {
+    starts: DateTime<Utc>,
+    days: Vec<NaiveDate>,
+}
+
+#[test]
+fn samples_with_dates_trace() {
+    install();
+    let shift = Shift {
+        starts: Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap(),
+        days: vec![NaiveDate::from_ymd_opt(2024, 5, 2).unwrap()],
+    };
+    Foreign::<Shift>::trace_sample(&shift).unwrap();
+    let spec = common::spec_for::<Foreign<Shift>>();
+    let properties = &spec["components"]["schemas"]["Shift"]["properties"];
+    assert_eq!(
+        properties["starts"],
+        json!({ "type": "string", "format": "date-time" })
+    );
+    assert_eq!(properties["days"]["items"]["format"], "date");
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, Once};
+
+use jsonwrap::{Foreign, ForeignConfig, ForeignFieldDefaults};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+#[derive(Serialize, Deserialize)]
+struct Settings {
+    retries: u32,
+    mode: String,
+    verbose: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            retries: 3,
+            mode: "fast".to_string(),
+            verbose: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Limits {
+    burst: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Quota {
+    burst: u32,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .field_defaults(ForeignFieldDefaults::from_default::<Settings>())
+            .field_defaults(ForeignFieldDefaults::new::<Limits>().field("burst", "many"))
+            .example_field::<Quota>("burst", "lots")
+            .install()
+    });
+}
+
+/// Collects the messages of the warnings logged on the current thread.
+#[derive(Clone, Default)]
+struct Warnings(Arc<Mutex<Vec<String>>>);
+
+struct Message<'a>(&'a mut String);
+
+impl Visit for Message<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            *self.0 = format!("{value:?}");
+        }
+    }
+}
+
+impl Subscriber for Warnings {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() == Level::WARN
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = String::new();
+        event.record(&mut Message(&mut message));
+        self.0.lock().unwrap().push(message);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn warnings_while(f: impl FnOnce()) -> Vec<String> {
+    let warnings = Warnings::default();
+    tracing::subscriber::with_default(warnings.clone(), f);
+    warnings.0.lock().unwrap().clone()
+}
+
+#[test]
+fn defaults_are_emitted_on_their_properties() {
+    install();
+    let mut spec = Value::Null;
+    let warnings = warnings_while(|| spec = common::spec_for::<Foreign<Settings>>());
+    assert_eq!(warnings, Vec::<String>::new());
+
+    let properties = &spec["components"]["schemas"]["Settings"]["properties"];
+    assert_eq!(properties["retries"]["default"], json!(3));
+    assert_eq!(properties["mode"]["default"], json!("fast"));
+    assert_eq!(properties["verbose"]["default"], json!(false));
+    common::assert_valid::<Foreign<Settings>>();
+
+    // Each default fits the property it is documented on.
+    let defaults = serde_json::to_value(Settings::default()).unwrap();
+    assert_eq!(common::validate_value(&spec, &defaults), Ok(()));
+}
+
+#[test]
+fn defaults_that_do_not_fit_are_reported() {
+    install();
+    let mut spec = Value::Null;
+    let warnings = warnings_while(|| spec = common::spec_for::<Foreign<Limits>>());
+    assert!(!warnings.is_empty());
+    for warning in &warnings {
+        assert_eq!(
+            warning,
+            "jsonwrap: default \"many\" for `Limits.burst` does not match its schema"
+        );
+    }
+    // The default is still documented as configured.
+    assert_eq!(
+        spec["components"]["schemas"]["Limits"]["properties"]["burst"]["default"],
+        json!("many")
+    );
+}
+
+#[test]
+fn examples_that_do_not_fit_are_reported() {
+    install();
+    let mut spec = Value::Null;
+    let warnings = warnings_while(|| spec = common::spec_for::<Foreign<Quota>>());
+    assert!(!warnings.is_empty());
+    for warning in &warnings {
+        assert_eq!(
+            warning,
+            "jsonwrap: example \"lots\" for `Quota.burst` does not match its schema"
+        );
+    }
+    assert_eq!(
+        spec["components"]["schemas"]["Quota"]["properties"]["burst"]["example"],
+        json!("lots")
+    );
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Address {
+    street: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Customer {
+    name: String,
+    fax: Option<String>,
+    billing: Address,
+}
+
+#[test]
+fn deprecated_fields_are_flagged() {
+    ForeignConfig::new()
+        .deprecate_field::<Customer>("fax")
+        .deprecate_field::<Customer>("billing")
+        .install();
+
+    let spec = common::spec_for::<Foreign<Customer>>();
+    let properties = &spec["components"]["schemas"]["Customer"]["properties"];
+    assert_eq!(properties["name"], json!({ "type": "string" }));
+    assert_eq!(
+        properties["fax"],
+        json!({ "type": "string", "nullable": true, "deprecated": true })
+    );
+    assert_eq!(
+        properties["billing"],
+        json!({ "deprecated": true, "allOf": [{ "$ref": "#/components/schemas/Address" }] })
+    );
+    common::assert_valid::<Foreign<Customer>>();
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::Foreign;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Money {
+    amount: i64,
+    currency: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Price(Money);
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    total: Price,
+    lines: Vec<Money>,
+}
+
+#[test]
+fn transparent_newtypes_report_both_names() {
+    let description = Foreign::<Price>::describe();
+    assert!(description.rust_name().ends_with("::Price"));
+    assert_eq!(description.name(), "Money");
+    assert_eq!(description.kind(), "component");
+    assert_eq!(description.serde_name(), Some("Price"));
+    assert_eq!(description.documented_as(), Some("Money"));
+    assert_eq!(description.components(), ["Money"]);
+    assert!(!description.was_cached());
+
+    let again = Foreign::<Price>::describe();
+    assert!(again.was_cached());
+    assert_eq!(
+        again.to_string(),
+        format!(
+            "`{}` as `Money` (component)\n  \
+             serde container `Price` is transparent, documented as `Money`\n  \
+             components: Money\n  \
+             trace: cached",
+            description.rust_name()
+        )
+    );
+}
+
+#[test]
+fn wrappers_and_nested_components_are_listed() {
+    let description = Foreign::<Option<Vec<Order>>>::describe();
+    assert_eq!(description.name(), "[Order]");
+    assert_eq!(description.kind(), "nullable array of component");
+    assert_eq!(description.serde_name(), Some("Order"));
+    assert_eq!(description.documented_as(), Some("Order"));
+    assert_eq!(description.components(), ["Money", "Order"]);
+
+    let description = Foreign::<Vec<u32>>::describe();
+    assert_eq!(description.kind(), "array of inline");
+    assert_eq!(description.serde_name(), None);
+    assert!(description.components().is_empty());
+    assert!(description.to_string().contains("components: none"));
+}
@@ -0,0 +1,191 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig, ForeignDocsSide, ForeignJson};
+use poem::Route;
+use poem::test::TestClient;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Written as `"#rrggbb"`, read from that or from its channels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "String", from = "ColorInput")]
+struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+#[derive(Deserialize)]
+enum ColorInput {
+    Hex(String),
+    Rgb { r: u8, g: u8, b: u8 },
+}
+
+impl From<ColorInput> for Color {
+    fn from(input: ColorInput) -> Self {
+        match input {
+            ColorInput::Rgb { r, g, b } => Color { r, g, b },
+            ColorInput::Hex(hex) => {
+                let channel = |at: usize| {
+                    hex.get(at..at + 2)
+                        .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                        .unwrap_or(0)
+                };
+                Color {
+                    r: channel(1),
+                    g: channel(3),
+                    b: channel(5),
+                }
+            }
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Contact {
+    name: String,
+    color: Color,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Badge {
+    label: String,
+    color: Color,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Palette {
+    color: Color,
+}
+
+const RED: Color = Color { r: 255, g: 0, b: 0 };
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .docs_side::<Contact>(
+                ForeignDocsSide::Both,
+                Contact {
+                    name: "ada".to_string(),
+                    color: RED,
+                },
+            )
+            .docs_side::<Badge>(
+                ForeignDocsSide::Serialize,
+                Badge {
+                    label: "new".to_string(),
+                    color: RED,
+                },
+            )
+            .install();
+    });
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/contacts", method = "post")]
+    async fn create(&self, contact: ForeignJson<Contact>) -> ForeignJson<Contact> {
+        contact
+    }
+}
+
+#[tokio::test]
+async fn both_sides_get_their_own_component() {
+    install();
+    let service = OpenApiService::new(Api, "Contacts", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    assert_eq!(common::validate(&spec), Ok(()));
+
+    let operation = &spec["paths"]["/contacts"]["post"];
+    assert_eq!(
+        operation["requestBody"]["content"]["application/json; charset=utf-8"]["schema"],
+        json!({ "$ref": "#/components/schemas/ContactInput" })
+    );
+    assert_eq!(
+        operation["responses"]["200"]["content"]["application/json; charset=utf-8"]["schema"],
+        json!({ "$ref": "#/components/schemas/Contact" })
+    );
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Contact"]["properties"]["color"],
+        json!({ "type": "string" })
+    );
+    assert_eq!(
+        schemas["ContactInput"]["properties"]["color"],
+        json!({ "$ref": "#/components/schemas/ColorInput" })
+    );
+    assert!(schemas["ColorInput"]["oneOf"].is_array());
+
+    let client = TestClient::new(Route::new().nest("/", service));
+    for color in [
+        json!({ "Rgb": { "r": 255, "g": 0, "b": 0 } }),
+        json!({ "Hex": "#ff0000" }),
+    ] {
+        let response = client
+            .post("/contacts")
+            .body_json(&json!({ "name": "ada", "color": color }))
+            .send()
+            .await;
+        response.assert_status_is_ok();
+        response
+            .assert_json(json!({ "name": "ada", "color": "#ff0000" }))
+            .await;
+    }
+}
+
+#[test]
+fn serialize_side_documents_what_is_written() {
+    install();
+    let spec = common::spec_for::<Foreign<Badge>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Badge"]["properties"]["color"],
+        json!({ "type": "string" })
+    );
+    let badge = Badge {
+        label: "new".to_string(),
+        color: RED,
+    };
+    let value = poem_openapi::types::ToJSON::to_json(&Foreign(badge)).unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+
+    // Requests are still parsed with `Deserialize`.
+    let Foreign(parsed) =
+        Foreign::<Badge>::from_json_value(json!({ "label": "new", "color": { "Hex": "#ff0000" } }))
+            .unwrap();
+    assert_eq!(parsed.color, RED);
+}
+
+#[test]
+fn other_types_keep_the_deserialize_side() {
+    install();
+    let spec = common::spec_for::<Foreign<Palette>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Palette"]["properties"]["color"],
+        json!({ "$ref": "#/components/schemas/ColorInput" })
+    );
+}
+
+#[test]
+#[should_panic(expected = "the sample given for `Palette` could not be traced")]
+fn samples_have_to_show_the_whole_shape() {
+    #[derive(Serialize)]
+    struct Palette {
+        colors: Vec<Color>,
+    }
+    ForeignConfig::new()
+        .docs_side::<Palette>(ForeignDocsSide::Serialize, Palette { colors: vec![] });
+}
@@ -0,0 +1,30 @@
+#![cfg(all(feature = "trace", feature = "erased-serde"))]
+
+mod common;
+
+use jsonwrap::ForeignDyn;
+use poem_openapi::types::ToJSON;
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize)]
+struct Report {
+    rows: u32,
+}
+
+#[test]
+fn dynamic_payloads_serialize_with_an_any_schema() {
+    let payloads = [
+        ForeignDyn::new(Report { rows: 3 }),
+        ForeignDyn::new(vec!["a", "b"]),
+    ];
+    let values: Vec<_> = payloads.iter().map(|payload| payload.to_json()).collect();
+    assert_eq!(
+        values,
+        [Some(json!({ "rows": 3 })), Some(json!(["a", "b"]))]
+    );
+
+    let spec = common::spec_for::<ForeignDyn>();
+    assert_eq!(*common::response_schema(&spec), json!({}));
+    common::assert_valid::<ForeignDyn>();
+}
@@ -0,0 +1,110 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::Foreign;
+use poem::Route;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem_openapi::payload::Json;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    sku: String,
+    quantity: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    customer: String,
+    lines: Vec<Line>,
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/orders", method = "post")]
+    async fn create(&self, order: Json<Foreign<Order>>) -> Json<Foreign<Order>> {
+        order
+    }
+}
+
+fn client() -> TestClient<Route> {
+    TestClient::new(Route::new().nest("/", OpenApiService::new(Api, "Orders", "1.0")))
+}
+
+#[tokio::test]
+async fn malformed_bodies_are_rejected_with_their_path() {
+    let response = client()
+        .post("/orders")
+        .body_json(&json!({
+            "customer": "ada",
+            "lines": [
+                { "sku": "A-1", "quantity": 2 },
+                { "sku": "B-2", "quantity": "three" },
+            ],
+        }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body = response.0.into_body().into_string().await.unwrap();
+    assert!(body.contains("/lines/1/quantity: invalid type"), "{body}");
+}
+
+#[test]
+fn errors_point_at_the_offending_value() {
+    let missing = Foreign::<Order>::from_json_value(json!({
+        "customer": "ada",
+        "lines": [{ "sku": "A-1" }],
+    }));
+    let Err(err) = missing else {
+        panic!("a line without a quantity parsed");
+    };
+    assert_eq!(err.path(), "/lines/0");
+    assert_eq!(err.message(), "missing field `quantity`");
+    assert_eq!(err.to_string(), "/lines/0: missing field `quantity`");
+
+    let Err(err) = Foreign::<Order>::from_json_value(json!([])) else {
+        panic!("an array parsed as an order");
+    };
+    assert_eq!(err.path(), "");
+
+    assert_eq!(poem::Error::from(err).status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn keys_are_escaped_in_paths() {
+    #[derive(Serialize, Deserialize)]
+    struct Catalog {
+        prices: std::collections::HashMap<String, f64>,
+    }
+
+    let Err(err) = Foreign::<Catalog>::from_json_value(json!({
+        "prices": { "a/b \"c\": d": "cheap" },
+    })) else {
+        panic!("a string parsed as a price");
+    };
+    assert_eq!(err.path(), "/prices/a~1b \"c\": d");
+}
+
+#[test]
+fn variants_are_the_keys_they_are_written_under() {
+    #[derive(Serialize, Deserialize)]
+    enum Shape {
+        Circle { radius: f64 },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Drawing {
+        shapes: Vec<Shape>,
+    }
+
+    let Err(err) = Foreign::<Drawing>::from_json_value(json!({
+        "shapes": [{ "Circle": { "radius": 1.0 } }, { "Circle": { "radius": "big" } }],
+    })) else {
+        panic!("a string parsed as a radius");
+    };
+    assert_eq!(err.path(), "/shapes/1/Circle/radius");
+}
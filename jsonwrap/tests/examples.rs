@@ -0,0 +1,51 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Address {
+    street: String,
+    city: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Customer {
+    email: String,
+    age: Option<u8>,
+    home: Address,
+}
+
+#[test]
+fn field_examples_are_documented_on_their_property() {
+    ForeignConfig::new()
+        .example_field::<Customer>("email", "ada@example.com")
+        .example_field::<Customer>("age", 36)
+        .example_field::<Customer>(
+            "home",
+            Address {
+                street: "12 Marylebone Rd".to_string(),
+                city: "London".to_string(),
+            },
+        )
+        .install();
+
+    let spec = common::spec_for::<Foreign<Customer>>();
+    let properties = &spec["components"]["schemas"]["Customer"]["properties"];
+    assert_eq!(
+        properties["email"],
+        json!({ "type": "string", "example": "ada@example.com" })
+    );
+    assert_eq!(properties["age"]["example"], 36);
+    assert_eq!(
+        properties["home"],
+        json!({
+            "example": { "street": "12 Marylebone Rd", "city": "London" },
+            "allOf": [{ "$ref": "#/components/schemas/Address" }],
+        })
+    );
+    common::assert_valid::<Foreign<Customer>>();
+}
@@ -0,0 +1,145 @@
+#![cfg(all(feature = "trace", feature = "bitflags"))]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{FlagsRepr, Foreign, ForeignConfig};
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Permissions: u8 {
+        const READ = 1;
+        const WRITE = 1 << 1;
+        const EXECUTE = 1 << 2;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Features: u32 {
+        const SEARCH = 1;
+        const EXPORT = 1 << 1;
+        const SHARE = 1 << 2;
+    }
+}
+
+bitflags::bitflags! {
+    /// Serialized the way bitflags recommends, so tracing only sees a `u16`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct Mode: u16 {
+        const FAST = 1;
+        const SAFE = 1 << 1;
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Mount {
+    path: String,
+    permissions: Permissions,
+    features: Option<Features>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .bitflags::<Permissions>(FlagsRepr::Integer)
+            .bitflags::<Features>(FlagsRepr::Names)
+            .bitflags::<Mode>(FlagsRepr::Names)
+            .install();
+    });
+}
+
+#[test]
+fn flags_fields_follow_their_declared_mode() {
+    install();
+    let spec = common::spec_for::<Foreign<Mount>>();
+    let properties = &spec["components"]["schemas"]["Mount"]["properties"];
+    assert_eq!(
+        properties["permissions"],
+        json!({
+            "type": "integer",
+            "format": "int32",
+            "minimum": 0.0,
+            "description": "Bit flags, combined with bitwise or: `READ` = 1, `WRITE` = 2, `EXECUTE` = 4.",
+        })
+    );
+    assert_eq!(
+        properties["features"],
+        json!({
+            "type": "array",
+            "items": { "type": "string", "enum": ["SEARCH", "EXPORT", "SHARE"] },
+            "uniqueItems": true,
+            "nullable": true,
+        })
+    );
+    assert_eq!(common::validate(&spec), Ok(()));
+
+    let mount = Mount {
+        path: "/srv".to_string(),
+        permissions: Permissions::READ | Permissions::WRITE,
+        features: Some(Features::SEARCH | Features::SHARE),
+    };
+    let value = Foreign(mount).to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({ "path": "/srv", "permissions": 3, "features": ["SEARCH", "SHARE"] })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+
+    let Foreign(parsed) = Foreign::<Mount>::parse_from_json(Some(value.clone()))
+        .unwrap_or_else(|err| panic!("{}", err.message()));
+    assert_eq!(parsed.permissions, Permissions::READ | Permissions::WRITE);
+    assert_eq!(parsed.features, Some(Features::SEARCH | Features::SHARE));
+
+    let bytes = serde_json::to_vec(&value).unwrap();
+    let Foreign(sliced) = Foreign::<Mount>::from_json_slice(&bytes).unwrap();
+    assert_eq!(sliced, parsed);
+
+    let value = json!({ "path": "/srv", "permissions": 0, "features": null });
+    let Foreign(parsed) = Foreign::<Mount>::from_json_value(value).unwrap();
+    assert_eq!(parsed.permissions, Permissions::empty());
+    assert_eq!(parsed.features, None);
+}
+
+#[test]
+fn bodies_in_another_mode_are_rejected_with_their_path() {
+    install();
+    let value = json!({ "path": "/srv", "permissions": "READ | WRITE", "features": null });
+    let Err(err) = Foreign::<Mount>::from_json_value(value) else {
+        panic!("flags text parsed in integer mode");
+    };
+    assert_eq!(err.path(), "/permissions");
+
+    let value = json!({ "path": "/srv", "permissions": 1, "features": ["SEARCH", "PRINT"] });
+    let Err(err) = Foreign::<Mount>::from_json_value(value) else {
+        panic!("an unknown flag parsed");
+    };
+    assert_eq!(err.path(), "/features");
+    assert!(err.message().contains("`PRINT`"), "{err}");
+}
+
+#[test]
+fn transparent_flags_convert_at_the_root() {
+    install();
+    let spec = common::spec_for::<Foreign<Mode>>();
+    assert_eq!(
+        common::response_schema(&spec),
+        &json!({
+            "type": "array",
+            "items": { "type": "string", "enum": ["FAST", "SAFE"] },
+            "uniqueItems": true,
+        })
+    );
+
+    let value = Foreign(Mode::FAST | Mode::SAFE).to_json().unwrap();
+    assert_eq!(value, json!(["FAST", "SAFE"]));
+    let Foreign(parsed) = Foreign::<Mode>::parse_from_json(Some(json!(["SAFE"])))
+        .unwrap_or_else(|err| panic!("{}", err.message()));
+    assert_eq!(parsed, Mode::SAFE);
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig, NonFiniteFloats};
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Reading {
+    ratio: f64,
+    samples: Vec<f32>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .non_finite_floats(NonFiniteFloats::String)
+            .install();
+    });
+}
+
+#[test]
+fn non_finite_floats_are_written_as_strings() {
+    install();
+    let spec = common::spec_for::<Foreign<Reading>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Reading"]["properties"]["ratio"],
+        json!({
+            "oneOf": [
+                { "type": "number", "format": "double" },
+                { "type": "string", "enum": ["NaN", "Infinity", "-Infinity"] },
+            ],
+        })
+    );
+
+    let reading = Reading {
+        ratio: f64::NAN,
+        samples: vec![0.5, f32::INFINITY, f32::NEG_INFINITY],
+    };
+    let value = Foreign(reading).to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({ "ratio": "NaN", "samples": [0.5, "Infinity", "-Infinity"] })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[test]
+fn request_bodies_cannot_carry_them() {
+    install();
+    let Err(err) = Foreign::<Reading>::from_json_value(json!({ "ratio": "NaN", "samples": [] }))
+    else {
+        panic!("a NaN sentinel parsed");
+    };
+    assert_eq!(err.path(), "/ratio");
+}
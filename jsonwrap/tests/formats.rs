@@ -0,0 +1,53 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Email(String);
+
+#[derive(Serialize, Deserialize)]
+struct Hostname(String);
+
+#[derive(Serialize, Deserialize)]
+struct Mailbox {
+    address: Email,
+    server: Option<Hostname>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .string_format::<Email>("email")
+            .string_format::<Hostname>("hostname")
+            .install();
+    });
+}
+
+#[test]
+fn registered_newtypes_carry_their_format() {
+    install();
+    let spec = common::spec_for::<Foreign<Mailbox>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Email"],
+        json!({ "type": "string", "format": "email" })
+    );
+    assert_eq!(
+        schemas["Hostname"],
+        json!({ "type": "string", "format": "hostname" })
+    );
+    common::assert_valid::<Foreign<Mailbox>>();
+
+    let spec = common::spec_for::<Foreign<Email>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Email"],
+        json!({ "type": "string", "format": "email" })
+    );
+}
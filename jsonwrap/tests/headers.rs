@@ -0,0 +1,123 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, WithMetaHeaders};
+use poem::Route;
+use poem::http::HeaderValue;
+use poem::test::TestClient;
+use poem_openapi::payload::Json;
+use poem_openapi::types::ToHeader;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Value, json};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ResponseMeta {
+    x_request_id: String,
+    x_rate_limit_remaining: u32,
+    x_cache: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Greeting {
+    text: String,
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/greeting", method = "get")]
+    async fn greeting(&self) -> WithMetaHeaders<Json<Foreign<Greeting>>, ResponseMeta> {
+        WithMetaHeaders(
+            Json(Foreign(Greeting {
+                text: "hello".to_string(),
+            })),
+            ResponseMeta {
+                x_request_id: "req-1".to_string(),
+                x_rate_limit_remaining: 41,
+                x_cache: None,
+            },
+        )
+    }
+}
+
+#[tokio::test]
+async fn fields_are_documented_and_written_as_headers() {
+    let service = OpenApiService::new(Api, "Greetings", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    assert_eq!(common::validate(&spec), Ok(()));
+
+    let response = &spec["paths"]["/greeting"]["get"]["responses"]["200"];
+    assert_eq!(
+        response["headers"],
+        json!({
+            "x-request-id": { "required": true, "deprecated": false, "schema": { "type": "string" } },
+            "x-rate-limit-remaining": {
+                "required": true,
+                "deprecated": false,
+                "schema": { "type": "integer", "format": "int64", "minimum": 0.0, "maximum": 4294967295.0 }
+            },
+            "x-cache": { "deprecated": false, "schema": { "type": "boolean" } },
+        })
+    );
+    assert_eq!(
+        response["content"]["application/json; charset=utf-8"]["schema"],
+        json!({ "$ref": "#/components/schemas/Greeting" })
+    );
+
+    let client = TestClient::new(Route::new().nest("/", service));
+    let response = client.get("/greeting").send().await;
+    response.assert_status_is_ok();
+    response.assert_header("x-request-id", "req-1");
+    response.assert_header("x-rate-limit-remaining", "41");
+    response.assert_header_is_not_exist("x-cache");
+    response.assert_json(json!({ "text": "hello" })).await;
+}
+
+#[test]
+#[should_panic(expected = "the header `tags` of `Tagged` does not serialize to a string")]
+fn fields_have_to_be_scalars() {
+    #[derive(Serialize, Deserialize)]
+    struct Tagged {
+        tags: Vec<String>,
+    }
+    <WithMetaHeaders<Json<Foreign<Greeting>>, Tagged> as poem_openapi::ApiResponse>::meta();
+}
+
+/// A digest that serializes as raw bytes.
+#[derive(Deserialize)]
+struct Digest(Vec<u8>);
+
+impl Serialize for Digest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[test]
+fn bytes_are_written_as_base64_headers() {
+    let digest = Foreign(Digest(vec![0xde, 0xad, 0xbe, 0xef]));
+    assert_eq!(
+        digest.to_header(),
+        Some(HeaderValue::from_static("3q2+7w=="))
+    );
+}
+
+#[test]
+fn values_that_are_not_header_text_write_no_header() {
+    #[derive(Deserialize)]
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("not today"))
+        }
+    }
+
+    assert_eq!(Foreign("two\nlines".to_string()).to_header(), None);
+    assert_eq!(Foreign(None::<String>).to_header(), None);
+    assert_eq!(Foreign(Unserializable).to_header(), None);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use poem_openapi::payload::Json;
+use poem_openapi::types::Type;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    sku: String,
+    quantity: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    customer: String,
+    lines: Vec<Line>,
+    note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Node {
+    value: i32,
+    children: Vec<Node>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| ForeignConfig::new().inline_all(true).install());
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/orders", method = "post")]
+    async fn create(&self, order: Json<Foreign<Order>>) -> Json<Foreign<Order>> {
+        order
+    }
+}
+
+#[test]
+fn nested_types_are_inlined_without_components() {
+    install();
+    let spec: Value =
+        serde_json::from_str(&OpenApiService::new(Api, "Orders", "1.0").spec()).unwrap();
+    assert_eq!(spec["components"]["schemas"], json!({}));
+
+    let order = json!({
+        "type": "object",
+        "properties": {
+            "customer": { "type": "string" },
+            "lines": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "sku": { "type": "string" },
+                        "quantity": {
+                            "type": "integer",
+                            "format": "int32",
+                            "minimum": 0.0,
+                            "maximum": 65535.0,
+                        },
+                    },
+                    "required": ["sku", "quantity"],
+                },
+            },
+            "note": { "type": "string", "nullable": true },
+        },
+        "required": ["customer", "lines"],
+    });
+    let operation = &spec["paths"]["/orders"]["post"];
+    let content = "application/json; charset=utf-8";
+    assert_eq!(
+        operation["requestBody"]["content"][content]["schema"],
+        order
+    );
+    assert_eq!(
+        operation["responses"]["200"]["content"][content]["schema"],
+        order
+    );
+    common::assert_valid::<Foreign<Order>>();
+}
+
+#[test]
+#[should_panic(expected = "`Node` is recursive (Node -> Node) and cannot be inlined")]
+fn recursive_types_cannot_be_inlined() {
+    install();
+    let _ = Foreign::<Node>::schema_ref();
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::Foreign;
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Ledger {
+    balance: i128,
+    supply: u128,
+    history: Vec<u128>,
+}
+
+#[test]
+fn u128_fields_are_decimal_strings() {
+    let spec = common::spec_for::<Foreign<Ledger>>();
+    let properties = &spec["components"]["schemas"]["Ledger"]["properties"];
+    assert_eq!(
+        properties["supply"],
+        json!({ "type": "string", "format": "int128", "pattern": "^[0-9]+$" })
+    );
+    assert_eq!(
+        properties["history"]["items"],
+        json!({ "type": "string", "format": "int128", "pattern": "^[0-9]+$" })
+    );
+
+    // Beyond 64 bits, where serde_json cannot write a number at all.
+    let ledger = Ledger {
+        balance: -5,
+        supply: u128::MAX,
+        history: vec![7],
+    };
+    let value = Foreign(ledger).to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "balance": "-5",
+            "supply": "340282366920938463463374607431768211455",
+            "history": ["7"],
+        })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[test]
+fn strings_are_read_back_within_64_bits() {
+    let Foreign(ledger) = Foreign::<Ledger>::from_json_value(json!({
+        "balance": "-5",
+        "supply": "18446744073709551615",
+        "history": ["7"],
+    }))
+    .unwrap();
+    assert_eq!(
+        ledger,
+        Ledger {
+            balance: -5,
+            supply: u64::MAX.into(),
+            history: vec![7],
+        }
+    );
+
+    let Err(err) = Foreign::<Ledger>::from_json_slice(
+        br#"{ "balance": "0", "supply": "0", "history": ["18446744073709551616"] }"#,
+    ) else {
+        panic!("a value beyond 64 bits parsed");
+    };
+    assert_eq!(err.path(), "/history/0");
+    assert!(err.to_string().contains("64-bit range"), "{err}");
+
+    let Err(err) = Foreign::<Ledger>::from_json_value(
+        json!({ "balance": "-1.5", "supply": "0", "history": [] }),
+    ) else {
+        panic!("a fraction parsed as an integer");
+    };
+    assert_eq!(err.path(), "/balance");
+}
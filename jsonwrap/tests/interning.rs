@@ -0,0 +1,73 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::Foreign;
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use poem_openapi::types::Type;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+enum Status {
+    Active,
+    Suspended { reason: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Member {
+    handle: String,
+    nickname: Option<String>,
+    status: Status,
+}
+
+/// The addresses of every property and `required` name in `schema`, one level deep.
+fn name_addresses(schema: &MetaSchema) -> Vec<usize> {
+    let properties = schema.properties.iter().map(|(name, _)| name.as_ptr());
+    let required = schema.required.iter().map(|name| name.as_ptr());
+    properties.chain(required).map(|ptr| ptr as usize).collect()
+}
+
+/// The names in the components of `Member`: its fields, and the variant key of `Status`.
+fn registered_names() -> Vec<usize> {
+    let mut registry = Registry::new();
+    Foreign::<Member>::register(&mut registry);
+    let MetaSchemaRef::Inline(suspended) = &registry.schemas["Status"].one_of[1] else {
+        panic!("expected the `Suspended` variant inline");
+    };
+    let mut names = name_addresses(&registry.schemas["Member"]);
+    names.extend(name_addresses(suspended));
+    names
+}
+
+#[test]
+fn repeated_registration_reuses_the_same_names() {
+    let first = registered_names();
+    assert_eq!(first.len(), 7);
+    // Each distinct name is leaked once, so `required` shares the property's copy.
+    assert_eq!(first[0], first[3]);
+
+    for _ in 0..2_000 {
+        assert_eq!(registered_names(), first);
+    }
+}
+
+#[test]
+fn equal_names_are_interned_once() {
+    #[derive(Serialize, Deserialize)]
+    struct Cat {
+        name: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Dog {
+        name: String,
+    }
+
+    let mut registry = Registry::new();
+    Foreign::<Cat>::register(&mut registry);
+    Foreign::<Dog>::register(&mut registry);
+    let name = |component: &str| {
+        let (name, _) = registry.schemas[component].properties[0];
+        assert_eq!(name, "name");
+        name.as_ptr()
+    };
+    assert_eq!(name("Cat"), name("Dog"));
+}
@@ -0,0 +1,133 @@
+#![cfg(all(feature = "trace", feature = "schemars"))]
+
+mod common;
+
+use std::borrow::Cow;
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Where parcels are delivered.
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Address {
+    /// Street and house number.
+    street: String,
+    #[schemars(length(min = 4, max = 10))]
+    postcode: String,
+    country: Country,
+    note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+enum Country {
+    DE,
+    FR,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Shipment {
+    id: u64,
+    to: Address,
+    discount: Percent,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Percent(f64);
+
+impl JsonSchema for Percent {
+    fn schema_name() -> Cow<'static, str> {
+        "Percent".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "number",
+            "description": "A share of the price.",
+            "minimum": 0,
+            "exclusiveMaximum": 100,
+            "multipleOf": 0.5,
+            "examples": [12.5],
+            "not": { "const": 13 },
+        })
+    }
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .schemars::<Address>()
+            .schemars::<Percent>()
+            .install();
+    });
+}
+
+#[test]
+fn json_schema_impls_replace_tracing() {
+    install();
+    let spec = common::spec_for::<Foreign<Address>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "$ref": "#/components/schemas/Address" })
+    );
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Address"],
+        json!({
+            "type": "object",
+            "description": "Where parcels are delivered.",
+            "properties": {
+                "street": { "type": "string", "description": "Street and house number." },
+                "postcode": { "type": "string", "minLength": 4, "maxLength": 10 },
+                "country": { "$ref": "#/components/schemas/Country" },
+                "note": { "type": "string", "nullable": true },
+            },
+            "required": ["street", "postcode", "country"],
+        })
+    );
+    assert_eq!(
+        schemas["Country"],
+        json!({ "type": "string", "enum": ["DE", "FR"] })
+    );
+    common::assert_valid::<Foreign<Address>>();
+
+    let address = json!({ "street": "Main St 1", "postcode": "10115", "country": "DE" });
+    assert_eq!(common::validate_value(&spec, &address), Ok(()));
+    let address = json!({ "street": "Main St 1", "postcode": "1", "country": "DE" });
+    assert!(common::validate_value(&spec, &address).is_err());
+}
+
+#[test]
+fn fields_use_the_json_schema_of_their_type() {
+    install();
+    let spec = common::spec_for::<Foreign<Shipment>>();
+    let schemas = &spec["components"]["schemas"];
+    let properties = &schemas["Shipment"]["properties"];
+    assert_eq!(
+        properties["to"],
+        json!({ "$ref": "#/components/schemas/Address" })
+    );
+    assert_eq!(
+        properties["discount"],
+        json!({ "$ref": "#/components/schemas/Percent" })
+    );
+    assert!(schemas["Address"]["properties"]["street"].is_object());
+    // `exclusiveMaximum` is turned into OpenAPI 3.0's boolean form, `examples` into
+    // `example`, and `not`, which `MetaSchema` cannot hold, is dropped.
+    assert_eq!(
+        schemas["Percent"],
+        json!({
+            "type": "number",
+            "description": "A share of the price.",
+            "minimum": 0.0,
+            "maximum": 100.0,
+            "exclusiveMaximum": true,
+            "multipleOf": 0.5,
+            "example": 12.5,
+        })
+    );
+    common::assert_valid::<Foreign<Shipment>>();
+}
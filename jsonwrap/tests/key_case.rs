@@ -0,0 +1,201 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Once;
+
+use jsonwrap::{FieldConstraints, Foreign, ForeignConfig, ForeignJson, KeyCase};
+use poem::Route;
+use poem::test::TestClient;
+use poem_openapi::payload::Json;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// As a vendor crate writes it, in snake_case.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Account {
+    account_id: u64,
+    display_name: String,
+    billing_address: Address,
+    past_addresses: Vec<Address>,
+    tags: BTreeMap<String, u32>,
+    created_unix: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Address {
+    street_line: String,
+    postal_code: Option<String>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .key_case::<Account>(KeyCase::CamelCase, &["created_unix"])
+            .field_constraints::<Account>(
+                "past_addresses",
+                FieldConstraints::new().max_items(1).enforce(true),
+            )
+            .install();
+    });
+}
+
+fn account() -> Account {
+    Account {
+        account_id: 7,
+        display_name: "Ada".to_string(),
+        billing_address: Address {
+            street_line: "1 Main St".to_string(),
+            postal_code: None,
+        },
+        past_addresses: vec![Address {
+            street_line: "2 Side St".to_string(),
+            postal_code: Some("12345".to_string()),
+        }],
+        tags: BTreeMap::from([("vip_level".to_string(), 2)]),
+        created_unix: 1_700_000_000,
+    }
+}
+
+fn wire() -> Value {
+    json!({
+        "accountId": 7,
+        "displayName": "Ada",
+        "billingAddress": { "streetLine": "1 Main St", "postalCode": null },
+        "pastAddresses": [{ "streetLine": "2 Side St", "postalCode": "12345" }],
+        // Map keys are data, not fields, and keep their casing.
+        "tags": { "vip_level": 2 },
+        "created_unix": 1_700_000_000,
+    })
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/accounts", method = "post")]
+    async fn create(&self, account: Json<Foreign<Account>>) -> Json<Foreign<Account>> {
+        account
+    }
+
+    #[oai(path = "/accounts-direct", method = "post")]
+    async fn create_direct(&self, account: ForeignJson<Account>) -> ForeignJson<Account> {
+        account
+    }
+}
+
+#[test]
+fn schemas_use_the_renamed_fields() {
+    install();
+    let spec = common::spec_for::<Foreign<Account>>();
+    let schemas = &spec["components"]["schemas"];
+    let names = |schema: &Value| {
+        schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(&schemas["Account"]),
+        [
+            "accountId",
+            "billingAddress",
+            "created_unix",
+            "displayName",
+            "pastAddresses",
+            "tags"
+        ]
+    );
+    assert_eq!(
+        schemas["Account"]["required"],
+        json!([
+            "accountId",
+            "displayName",
+            "billingAddress",
+            "pastAddresses",
+            "tags",
+            "created_unix"
+        ])
+    );
+    // Nested structs are renamed too.
+    assert_eq!(names(&schemas["Address"]), ["postalCode", "streetLine"]);
+    assert_eq!(schemas["Address"]["required"], json!(["streetLine"]));
+    // Field settings keep the declared names and land on the renamed property.
+    assert_eq!(
+        schemas["Account"]["properties"]["pastAddresses"]["maxItems"],
+        1
+    );
+    common::assert_valid::<Foreign<Account>>();
+    assert_eq!(common::validate_value(&spec, &wire()), Ok(()));
+}
+
+#[test]
+fn values_are_written_and_read_renamed() {
+    install();
+    assert_eq!(Foreign(account()).to_json(), Some(wire()));
+    let Foreign(parsed) = Foreign::<Account>::from_json_value(wire()).unwrap();
+    assert_eq!(parsed, account());
+    let Foreign(parsed) =
+        Foreign::<Account>::from_json_slice(&serde_json::to_vec(&wire()).unwrap()).unwrap();
+    assert_eq!(parsed, account());
+}
+
+#[test]
+fn constraints_locate_the_renamed_field() {
+    install();
+    let mut body = wire();
+    body["pastAddresses"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "streetLine": "3 Back St" }));
+    let Err(error) = Foreign::<Account>::parse_from_json(Some(body)) else {
+        panic!("expected two past addresses to be rejected");
+    };
+    assert!(
+        error.message().contains("/pastAddresses: has 2 items"),
+        "{}",
+        error.message()
+    );
+}
+
+#[tokio::test]
+async fn bodies_round_trip_through_both_payloads() {
+    install();
+    let client =
+        TestClient::new(Route::new().nest("/", OpenApiService::new(Api, "Accounts", "1.0")));
+    for path in ["/accounts", "/accounts-direct"] {
+        let response = client.post(path).body_json(&wire()).send().await;
+        response.assert_status_is_ok();
+        response.assert_json(wire()).await;
+    }
+}
+
+#[test]
+fn casings_convert_from_any_casing() {
+    assert_eq!(KeyCase::CamelCase.apply("street_line"), "streetLine");
+    assert_eq!(KeyCase::CamelCase.apply("StreetLine"), "streetLine");
+    assert_eq!(KeyCase::PascalCase.apply("street_line"), "StreetLine");
+    assert_eq!(KeyCase::SnakeCase.apply("streetLine2"), "street_line2");
+    assert_eq!(KeyCase::SnakeCase.apply("line2Street"), "line2_street");
+    assert_eq!(KeyCase::KebabCase.apply("streetLine"), "street-line");
+}
+
+#[test]
+#[should_panic(
+    expected = "`Clash` has the fields `user_id` and `userId`, which are both written as `userId`"
+)]
+fn collisions_are_rejected() {
+    #[derive(Serialize, Deserialize)]
+    struct Clash {
+        user_id: u32,
+        #[serde(rename = "userId")]
+        legacy_id: u32,
+    }
+    ForeignConfig::new().key_case::<Clash>(KeyCase::CamelCase, &[]);
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use poem_openapi::types::{ToJSON, Type};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Score {
+    points: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Scoreboard {
+    by_name: HashMap<String, Score>,
+    by_id: BTreeMap<u64, Score>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Cell {
+    row: u16,
+    column: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Grid {
+    cells: BTreeMap<Cell, String>,
+}
+
+/// The same map as `Grid`, without an encoder configured.
+#[derive(Serialize, Deserialize)]
+struct Board {
+    cells: BTreeMap<Cell, String>,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .map_key_encoder::<Grid>(|key| format!("{}x{}", key["row"], key["column"]))
+            .install();
+    });
+}
+
+#[test]
+fn string_and_integer_keys_are_object_keys() {
+    install();
+    let spec = common::spec_for::<Foreign<Scoreboard>>();
+    let properties = &spec["components"]["schemas"]["Scoreboard"]["properties"];
+    let score = json!({ "$ref": "#/components/schemas/Score" });
+    assert_eq!(
+        properties["by_name"],
+        json!({ "type": "object", "additionalProperties": score })
+    );
+    assert_eq!(
+        properties["by_id"],
+        json!({
+            "type": "object",
+            "description": "Keys are integers written as strings.",
+            "additionalProperties": score,
+        })
+    );
+
+    let board = Scoreboard {
+        by_name: HashMap::from([("ada".to_string(), Score { points: 3 })]),
+        by_id: BTreeMap::from([(7, Score { points: 5 })]),
+    };
+    let value = Foreign(board).to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({ "by_name": { "ada": { "points": 3 } }, "by_id": { "7": { "points": 5 } } })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[test]
+fn struct_keys_are_written_with_their_encoder() {
+    install();
+    let spec = common::spec_for::<Foreign<Grid>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Grid"]["properties"]["cells"],
+        json!({
+            "type": "object",
+            "description": "Keys are encoded into strings by the server.",
+            "additionalProperties": { "type": "string" },
+        })
+    );
+    let grid = Grid {
+        cells: BTreeMap::from([(Cell { row: 1, column: 2 }, "x".to_string())]),
+    };
+    let value = Foreign(grid).to_json().unwrap();
+    assert_eq!(value, json!({ "cells": { "1x2": "x" } }));
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[test]
+#[should_panic(expected = "register an encoder with `ForeignConfig::map_key_encoder::<Board>()`")]
+fn struct_keys_without_an_encoder_fail_registration() {
+    install();
+    let _ = Foreign::<Board>::schema_ref();
+}
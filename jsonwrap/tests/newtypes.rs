@@ -0,0 +1,71 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{FieldConstraints, Foreign, ForeignConfig};
+use poem_openapi::types::Type;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Money {
+    amount: i64,
+    currency: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Price(Money);
+
+#[derive(Serialize, Deserialize)]
+struct Invoice {
+    total: Money,
+    unit_price: Price,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .description::<Price>("An amount in minor units.")
+            .field_constraints::<Price>(
+                "currency",
+                FieldConstraints::new().enum_values(&["EUR", "USD"]),
+            )
+            .install();
+    });
+}
+
+#[test]
+fn settings_for_a_newtype_apply_to_the_wrapped_component() {
+    install();
+    assert_eq!(Foreign::<Price>::name(), "Money");
+    let expected = json!({
+        "type": "object",
+        "description": "An amount in minor units.",
+        "required": ["amount", "currency"],
+        "properties": {
+            "amount": { "type": "integer", "format": "int64" },
+            "currency": { "type": "string", "enum": ["EUR", "USD"] },
+        },
+    });
+    for spec in [
+        common::spec_for::<Foreign<Money>>(),
+        common::spec_for::<Foreign<Price>>(),
+    ] {
+        assert_eq!(spec["components"]["schemas"]["Money"], expected);
+    }
+}
+
+#[test]
+fn newtype_fields_reference_the_wrapped_component() {
+    install();
+    let spec = common::spec_for::<Foreign<Invoice>>();
+    let schemas = &spec["components"]["schemas"];
+    let money = json!({ "$ref": "#/components/schemas/Money" });
+    assert_eq!(schemas["Invoice"]["properties"]["total"], money);
+    assert_eq!(schemas["Invoice"]["properties"]["unit_price"], money);
+    assert!(schemas.get("Price").is_none());
+    common::assert_valid::<Foreign<Invoice>>();
+}
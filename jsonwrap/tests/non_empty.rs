@@ -0,0 +1,41 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, NonEmptyString};
+use poem_openapi::types::ParseFromJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Tag {
+    label: NonEmptyString,
+}
+
+#[test]
+fn non_empty_strings_have_a_minimum_length() {
+    let spec = common::spec_for::<Foreign<Tag>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Tag"]["properties"]["label"],
+        json!({ "$ref": "#/components/schemas/NonEmptyString" })
+    );
+    assert_eq!(
+        schemas["NonEmptyString"],
+        json!({ "type": "string", "minLength": 1 })
+    );
+    common::assert_valid::<Foreign<Tag>>();
+    common::assert_valid::<Foreign<NonEmptyString>>();
+}
+
+#[test]
+fn empty_strings_are_rejected() {
+    assert!(Foreign::<NonEmptyString>::parse_from_json(Some(json!(""))).is_err());
+    assert!(Foreign::<Tag>::parse_from_json(Some(json!({ "label": "" }))).is_err());
+
+    match Foreign::<Tag>::parse_from_json(Some(json!({ "label": "urgent" }))) {
+        Ok(Foreign(tag)) => assert_eq!(tag.label.as_str(), "urgent"),
+        Err(err) => panic!("{}", err.into_message()),
+    }
+    assert_eq!(NonEmptyString::new(""), None);
+}
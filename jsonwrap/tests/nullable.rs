@@ -0,0 +1,140 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, ForeignOpt};
+use poem_openapi::types::ParseFromJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Serialize, Deserialize)]
+struct Parcel {
+    weight: u32,
+    forward_to: Option<Depot>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Depot {
+    code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Carrier {
+    Post,
+    Courier { name: String },
+}
+
+#[derive(Serialize, Deserialize)]
+enum Speed {
+    Standard,
+    Express,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Booking {
+    speed: Option<Speed>,
+}
+
+#[test]
+fn optional_references_accept_null_under_strict_nullable() {
+    let spec = common::spec_for::<Foreign<Option<Parcel>>>();
+    assert_eq!(common::validate(&spec), Ok(()));
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "$ref": "#/components/schemas/ParcelNullable" })
+    );
+    let parcel = json!({ "weight": 2, "forward_to": null });
+    assert_eq!(common::validate_value_strict(&spec, &Value::Null), Ok(()));
+    assert_eq!(common::validate_value_strict(&spec, &parcel), Ok(()));
+    assert!(common::validate_value_strict(&spec, &json!({ "weight": "2" })).is_err());
+
+    // The copy is nullable, titled after the component it was made from and otherwise
+    // that component.
+    let schemas = &spec["components"]["schemas"];
+    let mut copy = schemas["Parcel"].clone();
+    copy["nullable"] = true.into();
+    copy["title"] = "Parcel".into();
+    assert_eq!(schemas["ParcelNullable"], copy);
+    assert_eq!(schemas["DepotNullable"]["title"], "Depot");
+    assert_eq!(
+        schemas["Parcel"]["properties"]["forward_to"],
+        json!({ "$ref": "#/components/schemas/DepotNullable" })
+    );
+
+    // `ForeignOpt<T>` registers the copy its schema points to.
+    assert_eq!(common::spec_for::<ForeignOpt<Parcel>>(), spec);
+}
+
+#[test]
+fn optional_values_parse_null_and_missing_as_none() {
+    let parse = |value| match ForeignOpt::<Depot>::parse_from_json(value) {
+        Ok(ForeignOpt(depot)) => Ok(depot),
+        Err(err) => Err(err.into_message()),
+    };
+    assert_eq!(parse(None), Ok(None));
+    assert_eq!(parse(Some(Value::Null)), Ok(None));
+    assert_eq!(
+        parse(Some(json!({ "code": "BER" }))),
+        Ok(Some(Depot {
+            code: "BER".to_string()
+        }))
+    );
+    assert_eq!(
+        parse(Some(json!({ "code": 7 }))),
+        Err(
+            "failed to parse \"Depot\": /code: invalid type: integer `7`, expected a string"
+                .to_string()
+        )
+    );
+    assert!(parse(Some(json!({}))).is_err());
+}
+
+#[test]
+fn a_nullable_all_of_wrapper_is_rejected_by_strict_nullable() {
+    let mut spec = common::spec_for::<Foreign<Parcel>>();
+    *spec
+        .pointer_mut("/paths/~1value/get/responses/200/content/application~1json/schema")
+        .unwrap() = json!({
+        "nullable": true,
+        "allOf": [{ "$ref": "#/components/schemas/Parcel" }],
+    });
+    assert_eq!(common::validate_value(&spec, &Value::Null), Ok(()));
+    assert!(common::validate_value_strict(&spec, &Value::Null).is_err());
+}
+
+#[test]
+fn optional_enums_without_a_type_keep_the_wrapper() {
+    let spec = common::spec_for::<Foreign<Option<Carrier>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({
+            "title": "Carrier",
+            "nullable": true,
+            "allOf": [{ "$ref": "#/components/schemas/Carrier" }],
+        })
+    );
+    assert_eq!(common::validate_value(&spec, &Value::Null), Ok(()));
+}
+
+#[test]
+fn optional_unit_enum_fields_are_nullable_string_enums() {
+    let spec = common::spec_for::<Foreign<Booking>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Booking"]["properties"]["speed"],
+        json!({
+            "type": "string",
+            "enum": ["Standard", "Express", null],
+            "nullable": true,
+        })
+    );
+    for speed in [Value::Null, json!("Standard"), json!("Express")] {
+        let booking = json!({ "speed": speed });
+        assert_eq!(
+            common::validate_value_strict(&spec, &booking),
+            Ok(()),
+            "{booking}"
+        );
+    }
+    assert!(common::validate_value_strict(&spec, &json!({ "speed": "Overnight" })).is_err());
+    common::assert_valid::<Foreign<Booking>>();
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Parcel {
+    weight: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Carrier {
+    Post,
+    Courier { name: String },
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| ForeignConfig::new().nullable_title(false).install());
+}
+
+#[test]
+fn nullable_copies_are_untitled() {
+    install();
+    let spec = common::spec_for::<Foreign<Option<Parcel>>>();
+    let schemas = &spec["components"]["schemas"];
+    let mut copy = schemas["Parcel"].clone();
+    copy["nullable"] = true.into();
+    assert_eq!(schemas["ParcelNullable"], copy);
+    assert!(copy.get("title").is_none(), "{copy}");
+    common::assert_valid::<Foreign<Option<Parcel>>>();
+}
+
+#[test]
+fn nullable_wrappers_are_untitled() {
+    install();
+    let spec = common::spec_for::<Foreign<Option<Carrier>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({
+            "nullable": true,
+            "allOf": [{ "$ref": "#/components/schemas/Carrier" }],
+        })
+    );
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use jsonwrap::Foreign;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Metric {
+    value: f64,
+    unit: String,
+}
+
+/// Keeps every key it does not declare as a metric.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Reading {
+    sensor: String,
+    taken_unix: i64,
+    #[serde(flatten)]
+    metrics: HashMap<String, Metric>,
+}
+
+/// Keeps any key it does not declare, as is.
+#[derive(Serialize, Deserialize)]
+struct Note {
+    text: String,
+    #[serde(flatten)]
+    extra: HashMap<String, String>,
+}
+
+fn reading() -> Reading {
+    Reading {
+        sensor: "roof".to_string(),
+        taken_unix: 1_700_000_000,
+        metrics: HashMap::from([(
+            "temperature".to_string(),
+            Metric {
+                value: 21.5,
+                unit: "C".to_string(),
+            },
+        )]),
+    }
+}
+
+#[test]
+fn flattened_maps_become_typed_additional_properties() {
+    // Without a sample, the declared fields and the entries cannot be told apart.
+    let spec = common::spec_for::<Foreign<Reading>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Reading"],
+        json!({ "type": "object" })
+    );
+
+    Foreign::<Reading>::trace_sample(&reading()).unwrap();
+    let spec = common::spec_for::<Foreign<Reading>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Reading"],
+        json!({
+            "type": "object",
+            "properties": {
+                "sensor": { "type": "string" },
+                "taken_unix": { "type": "integer", "format": "int64" },
+            },
+            "required": ["sensor", "taken_unix"],
+            "additionalProperties": { "$ref": "#/components/schemas/Metric" },
+        })
+    );
+    assert_eq!(schemas["Metric"]["required"], json!(["value", "unit"]));
+    common::assert_valid::<Foreign<Reading>>();
+
+    let value = Foreign(reading()).to_json().unwrap();
+    assert_eq!(value["temperature"], json!({ "value": 21.5, "unit": "C" }));
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    let mut invalid = value.clone();
+    invalid["humidity"] = json!(40);
+    assert!(common::validate_value(&spec, &invalid).is_err());
+
+    let Ok(Foreign(parsed)) = Foreign::<Reading>::parse_from_json(Some(value)) else {
+        panic!("expected the reading to parse");
+    };
+    assert_eq!(parsed, reading());
+}
+
+#[test]
+fn flattened_maps_of_plain_values() {
+    let sample = Note {
+        text: "hello".to_string(),
+        extra: HashMap::from([("lang".to_string(), "en".to_string())]),
+    };
+    Foreign::<Note>::trace_sample(&sample).unwrap();
+    let spec = common::spec_for::<Foreign<Note>>();
+    let note = &spec["components"]["schemas"]["Note"];
+    assert_eq!(note["properties"], json!({ "text": { "type": "string" } }));
+    assert_eq!(note["additionalProperties"], json!({ "type": "string" }));
+}
+
+#[test]
+fn samples_without_entries_keep_the_original_error() {
+    #[derive(Serialize, Deserialize)]
+    struct Empty {
+        id: u32,
+        #[serde(flatten)]
+        extra: HashMap<String, u32>,
+    }
+    let error = Foreign::<Empty>::trace_sample(&Empty {
+        id: 1,
+        extra: HashMap::new(),
+    })
+    .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            serde_reflection::Error::NotSupported("deserialize_identifier")
+        ),
+        "{error:?}"
+    );
+}
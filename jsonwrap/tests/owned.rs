@@ -0,0 +1,81 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::ForeignOwned;
+use poem_openapi::types::{ParseFromJSON, ToJSON, Type};
+use serde_json::json;
+
+/// Stands in for a foreign crate with a borrowing type and its owned variant.
+mod events {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Event<'a> {
+        pub name: &'a str,
+        pub tags: Vec<&'a str>,
+    }
+
+    impl Event<'_> {
+        pub fn to_owned(&self) -> OwnedEvent {
+            OwnedEvent {
+                name: self.name.to_string(),
+                tags: self.tags.iter().map(|tag| tag.to_string()).collect(),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct OwnedEvent {
+        pub name: String,
+        pub tags: Vec<String>,
+    }
+
+    impl OwnedEvent {
+        pub fn as_event(&self) -> Event<'_> {
+            Event {
+                name: &self.name,
+                tags: self.tags.iter().map(String::as_str).collect(),
+            }
+        }
+    }
+}
+
+jsonwrap::owned_projection! {
+    /// `events::OwnedEvent`, built with `Event::to_owned`.
+    struct EventProjection(events::OwnedEvent) from events::Event via to_owned;
+}
+
+type Body = ForeignOwned<events::Event<'static>, EventProjection>;
+
+#[test]
+fn borrowed_types_are_documented_as_their_projection() {
+    assert_eq!(Body::name(), "OwnedEvent");
+    let spec = common::spec_for::<Body>();
+    assert_eq!(
+        spec["components"]["schemas"]["OwnedEvent"]["required"],
+        json!(["name", "tags"])
+    );
+    common::assert_valid::<Body>();
+}
+
+#[test]
+fn borrowed_values_round_trip_through_their_projection() {
+    let name = String::from("deploy");
+    let event = events::Event {
+        name: &name,
+        tags: vec!["prod"],
+    };
+    let value = ForeignOwned::<_, EventProjection>::new(event)
+        .to_json()
+        .unwrap();
+    assert_eq!(value, json!({ "name": "deploy", "tags": ["prod"] }));
+
+    let parsed = match Body::parse_from_json(Some(value)) {
+        Ok(parsed) => parsed.into_owned(),
+        Err(err) => panic!("{}", err.into_message()),
+    };
+    let event = parsed.0.as_event();
+    assert_eq!(event.name, "deploy");
+    assert_eq!(event.tags, ["prod"]);
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::ForeignPage;
+use poem_openapi::types::{ParseFromJSON, ToJSON, Type};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Invoice {
+    number: String,
+    amount: f64,
+}
+
+#[test]
+fn pages_are_components_named_after_their_items() {
+    let spec = common::spec_for::<ForeignPage<User>>();
+    assert_eq!(ForeignPage::<User>::name(), "ForeignPage_User");
+    assert_eq!(
+        common::response_schema(&spec),
+        &json!({ "$ref": "#/components/schemas/ForeignPage_User" })
+    );
+    let page = &spec["components"]["schemas"]["ForeignPage_User"];
+    assert_eq!(
+        page["properties"]["items"],
+        json!({ "type": "array", "items": { "$ref": "#/components/schemas/User" } })
+    );
+    assert_eq!(page["required"], json!(["items"]));
+    assert_eq!(
+        page["properties"]["next_cursor"],
+        json!({ "type": "string", "nullable": true })
+    );
+    assert!(spec["components"]["schemas"]["User"].is_object());
+    assert_eq!(common::validate(&spec), Ok(()));
+
+    let spec = common::spec_for::<ForeignPage<Invoice>>();
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+    let mut names = schemas.keys().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["ForeignPage_Invoice", "Invoice"]);
+    assert_eq!(
+        schemas["ForeignPage_Invoice"]["properties"]["items"]["items"],
+        json!({ "$ref": "#/components/schemas/Invoice" })
+    );
+}
+
+#[test]
+fn pages_serialize_with_and_without_a_cursor() {
+    let spec = common::spec_for::<ForeignPage<User>>();
+    let users = || {
+        (1..=2).map(|id| User {
+            id,
+            name: format!("user {id}"),
+        })
+    };
+
+    let last = users().collect::<ForeignPage<_>>();
+    let value = last.to_json().unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "items": [{ "id": 1, "name": "user 1" }, { "id": 2, "name": "user 2" }],
+            "next_cursor": null,
+            "total": null,
+        })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+
+    let first = ForeignPage::new(users())
+        .next_cursor("b2Zmc2V0PTI=")
+        .total(5);
+    let value = first.to_json().unwrap();
+    assert_eq!(value["next_cursor"], "b2Zmc2V0PTI=");
+    assert_eq!(value["total"], 5);
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+
+    let parsed = ForeignPage::<User>::parse_from_json(Some(json!({ "items": [] })))
+        .unwrap_or_else(|err| panic!("{}", err.message()));
+    assert_eq!(parsed, ForeignPage::new([]));
+
+    let names = ForeignPage::new(users())
+        .total(5)
+        .map_items(|user| user.name);
+    assert_eq!(names.items, ["user 1", "user 2"]);
+    assert_eq!(names.total, Some(5));
+}
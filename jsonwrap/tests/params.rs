@@ -0,0 +1,198 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::Foreign;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem::{Route, handler, web};
+use poem_openapi::param::{Path, Query};
+use poem_openapi::payload::PlainText;
+use poem_openapi::types::ParseFromParameter;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct UserId(String);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Limit(u32);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Offset(i32);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Role {
+    Admin,
+    Member,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct OrgId(u64);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Filter {
+    role: Role,
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/users/:role", method = "get")]
+    async fn users(
+        &self,
+        role: Path<Foreign<Role>>,
+        after: Query<Option<Foreign<UserId>>>,
+        limit: Query<Foreign<Limit>>,
+        offset: Query<Option<Foreign<Offset>>>,
+    ) -> PlainText<String> {
+        let after = after.0.map(|Foreign(UserId(id))| id);
+        let offset = offset.0.map_or(0, |Foreign(Offset(offset))| offset);
+        PlainText(format!(
+            "{:?} after {after:?}, {} from {offset}",
+            role.0.0, limit.0.0.0
+        ))
+    }
+
+    #[oai(path = "/orgs/:org/members/:role", method = "get")]
+    async fn members(
+        &self,
+        org: Path<Foreign<OrgId>>,
+        role: Path<Foreign<Role>>,
+    ) -> PlainText<String> {
+        PlainText(format!("{:?} of {}", role.0.0, org.0.0.0))
+    }
+}
+
+#[handler]
+fn members(
+    web::Path((Foreign(OrgId(org)), Foreign(role))): web::Path<(Foreign<OrgId>, Foreign<Role>)>,
+) -> String {
+    format!("{role:?} of {org}")
+}
+
+#[tokio::test]
+async fn foreign_scalars_parse_from_query_and_path() {
+    let service = OpenApiService::new(Api, "Users", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    let parameters = &spec["paths"]["/users/{role}"]["get"]["parameters"];
+    assert_eq!(
+        parameters[0]["schema"],
+        json!({ "$ref": "#/components/schemas/Role" })
+    );
+    assert_eq!(
+        parameters[1]["schema"],
+        json!({ "$ref": "#/components/schemas/UserId" })
+    );
+    assert_eq!(parameters[1]["required"], false);
+    // Negative limits are documented as invalid, not only rejected.
+    assert_eq!(
+        spec["components"]["schemas"]["Limit"],
+        json!({
+            "type": "integer",
+            "format": "int64",
+            "minimum": 0.0,
+            "maximum": f64::from(u32::MAX),
+        })
+    );
+    // Signed integers keep the full range of their width rather than becoming unbounded.
+    assert_eq!(
+        spec["components"]["schemas"]["Offset"],
+        json!({
+            "type": "integer",
+            "format": "int32",
+            "minimum": f64::from(i32::MIN),
+            "maximum": f64::from(i32::MAX),
+        })
+    );
+
+    let client = TestClient::new(Route::new().nest("/", service));
+    let response = client
+        .get("/users/Admin")
+        .query("after", &"u-41")
+        .query("limit", &20)
+        .query("offset", &-3)
+        .send()
+        .await;
+    response.assert_status_is_ok();
+    response
+        .assert_text("Admin after Some(\"u-41\"), 20 from -3")
+        .await;
+
+    let response = client.get("/users/Member").query("limit", &5).send().await;
+    response.assert_text("Member after None, 5 from 0").await;
+
+    for (role, limit) in [("Owner", "5"), ("Admin", "-1"), ("Admin", "many")] {
+        let response = client
+            .get(format!("/users/{role}"))
+            .query("limit", &limit)
+            .send()
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn several_foreign_path_segments() {
+    let service = OpenApiService::new(Api, "Users", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    let parameters = &spec["paths"]["/orgs/{org}/members/{role}"]["get"]["parameters"];
+    assert_eq!(
+        parameters[0]["schema"],
+        json!({ "$ref": "#/components/schemas/OrgId" })
+    );
+    assert_eq!(
+        parameters[1]["schema"],
+        json!({ "$ref": "#/components/schemas/Role" })
+    );
+
+    let client = TestClient::new(
+        Route::new()
+            .at("/plain/:org/:role", members)
+            .nest("/", service),
+    );
+    for prefix in ["/orgs/7/members", "/plain/7"] {
+        let response = client.get(format!("{prefix}/Admin")).send().await;
+        response.assert_status_is_ok();
+        response.assert_text("Admin of 7").await;
+
+        for (org, role) in [("seven", "Admin"), ("7", "Owner")] {
+            let response = client
+                .get(format!("{}/{role}", prefix.replace('7', org)))
+                .send()
+                .await;
+            response.assert_status(StatusCode::BAD_REQUEST);
+        }
+    }
+}
+
+#[test]
+fn object_shaped_types_are_rejected_as_parameters() {
+    let Err(err) = Foreign::<Filter>::parse_from_parameter("{\"role\":\"Admin\"}") else {
+        panic!("a struct parsed from a parameter");
+    };
+    assert!(err.message().contains("an object"), "{}", err.message());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Serial(u128);
+
+#[test]
+fn wide_integers_parse_in_their_full_range() {
+    let parse = |value: &str| match Foreign::<Serial>::parse_from_parameter(value) {
+        Ok(Foreign(serial)) => Ok(serial),
+        Err(err) => Err(err.into_message()),
+    };
+    assert_eq!(parse(&u128::MAX.to_string()), Ok(Serial(u128::MAX)));
+    assert!(parse("-1").is_err());
+    assert!(parse("1.5").is_err());
+
+    let Ok(Foreign(offset)) = Foreign::<i128>::parse_from_parameter(&i128::MIN.to_string()) else {
+        panic!("i128::MIN did not parse");
+    };
+    assert_eq!(offset, i128::MIN);
+    let Ok(Foreign(offset)) = Foreign::<Option<i128>>::parse_from_parameter("-7") else {
+        panic!("an optional i128 did not parse");
+    };
+    assert_eq!(offset, Some(-7));
+}
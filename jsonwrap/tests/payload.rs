@@ -0,0 +1,267 @@
+#![cfg(feature = "trace")]
+
+use std::collections::BTreeMap;
+
+use jsonwrap::{Foreign, ForeignJson, ForeignOpt};
+use poem::Route;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem_openapi::payload::Json;
+use poem_openapi::types::{ToJSON, Type};
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Unit {
+    Piece,
+    Kilogram,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Line {
+    sku: String,
+    quantity: u32,
+    unit: Unit,
+    note: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Order {
+    customer: String,
+    lines: Vec<Line>,
+    tags: BTreeMap<String, i64>,
+}
+
+fn parse_both(body: &Value) -> (Result<Order, String>, Result<Order, String>) {
+    let bytes = serde_json::to_vec(body).unwrap();
+    let direct = Foreign::<Order>::from_json_slice(&bytes)
+        .map(|Foreign(order)| order)
+        .map_err(|err| err.to_string());
+    let via_value = Foreign::<Order>::from_json_value(body.clone())
+        .map(|Foreign(order)| order)
+        .map_err(|err| err.to_string());
+    (direct, via_value)
+}
+
+#[test]
+fn slices_parse_like_values() {
+    let bodies = [
+        json!({
+            "customer": "ada",
+            "lines": [
+                { "sku": "A-1", "quantity": 2, "unit": "Piece", "note": null },
+                { "sku": "B-2", "quantity": 1, "unit": "Kilogram" },
+            ],
+            "tags": { "priority": 1 },
+        }),
+        json!({ "customer": "ada", "lines": [], "tags": {} }),
+        json!({ "customer": "ada", "lines": [{ "sku": "A-1" }], "tags": {} }),
+        json!({ "customer": "ada", "lines": [], "tags": { "priority": "high" } }),
+        json!({
+            "customer": "ada",
+            "lines": [{ "sku": "A-1", "quantity": 2, "unit": "Litre" }],
+            "tags": {},
+        }),
+        json!([]),
+        Value::Null,
+    ];
+    for body in &bodies {
+        let (direct, via_value) = parse_both(body);
+        assert_eq!(direct, via_value, "{body}");
+    }
+}
+
+#[test]
+fn errors_from_slices_keep_their_path() {
+    let body =
+        br#"{"customer":"ada","lines":[{"sku":"A-1","quantity":-1,"unit":"Piece"}],"tags":{}}"#;
+    let Err(err) = Foreign::<Order>::from_json_slice(body) else {
+        panic!("a negative quantity parsed");
+    };
+    assert_eq!(err.path(), "/lines/0/quantity");
+
+    let Err(err) = Foreign::<Order>::from_json_slice(b"{\"customer\":") else {
+        panic!("truncated JSON parsed");
+    };
+    assert_eq!(err.path(), "");
+    assert!(err.message().contains("EOF"), "{err}");
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/fast", method = "post")]
+    async fn fast(&self, order: ForeignJson<Order>) -> ForeignJson<Order> {
+        order
+    }
+
+    #[oai(path = "/value", method = "post")]
+    async fn value(&self, order: Json<Foreign<Order>>) -> Json<Foreign<Order>> {
+        order
+    }
+}
+
+#[tokio::test]
+async fn the_payload_matches_json_of_foreign() {
+    let service = OpenApiService::new(Api, "Orders", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    let paths = &spec["paths"];
+    assert_eq!(
+        paths["/fast"]["post"]["requestBody"],
+        paths["/value"]["post"]["requestBody"]
+    );
+    assert_eq!(
+        paths["/fast"]["post"]["responses"],
+        paths["/value"]["post"]["responses"]
+    );
+
+    let client = TestClient::new(Route::new().nest("/", service));
+    let order = Order {
+        customer: "ada".to_string(),
+        lines: vec![],
+        tags: BTreeMap::new(),
+    };
+    let body = Foreign(order).to_json().unwrap();
+    let response = client.post("/fast").body_json(&body).send().await;
+    response.assert_status_is_ok();
+    response.assert_json(&body).await;
+
+    let response = client
+        .post("/fast")
+        .body_json(&json!({ "customer": 7, "lines": [], "tags": {} }))
+        .send()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let text = response.0.into_body().into_string().await.unwrap();
+    assert!(text.contains("/customer: invalid type"), "{text}");
+}
+
+/// Accepted, but never written.
+#[derive(Deserialize)]
+struct Unwritable;
+
+impl Serialize for Unwritable {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("not today"))
+    }
+}
+
+struct Optional;
+
+#[OpenApi]
+impl Optional {
+    #[oai(path = "/foreign-opt-none", method = "get")]
+    async fn foreign_opt_none(&self) -> Json<ForeignOpt<Line>> {
+        Json(ForeignOpt(None))
+    }
+
+    #[oai(path = "/option-of-foreign", method = "get")]
+    async fn option_of_foreign(&self) -> Json<Option<Foreign<Line>>> {
+        Json(None)
+    }
+
+    #[oai(path = "/foreign-option", method = "get")]
+    async fn foreign_option(&self) -> Json<Foreign<Option<Line>>> {
+        Json(Foreign(None))
+    }
+
+    #[oai(path = "/foreign-json-option", method = "get")]
+    async fn foreign_json_option(&self) -> ForeignJson<Option<Line>> {
+        ForeignJson(None)
+    }
+
+    #[oai(path = "/units", method = "get")]
+    async fn units(&self) -> Json<Vec<ForeignOpt<Unit>>> {
+        Json(vec![ForeignOpt(Some(Unit::Piece)), ForeignOpt(None)])
+    }
+
+    #[oai(path = "/unwritable", method = "get")]
+    async fn unwritable(&self) -> ForeignJson<Unwritable> {
+        ForeignJson(Unwritable)
+    }
+}
+
+#[tokio::test]
+async fn missing_values_are_written_as_null() {
+    let client = TestClient::new(Route::new().nest("/", OpenApiService::new(Optional, "", "")));
+    let response = client.get("/foreign-opt-none").send().await;
+    response.assert_status_is_ok();
+    response.assert_content_type("application/json; charset=utf-8");
+    response.assert_bytes("null").await;
+
+    let response = client.get("/units").send().await;
+    response.assert_bytes(r#"["Piece",null]"#).await;
+
+    let response = client.get("/unwritable").send().await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[test]
+fn optional_responses_are_nullable() {
+    let service = OpenApiService::new(Optional, "", "");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    let schema = |path: &str| {
+        let content = &spec["paths"][path]["get"]["responses"]["200"]["content"];
+        content["application/json; charset=utf-8"]["schema"].clone()
+    };
+    let nullable = json!({ "$ref": "#/components/schemas/LineNullable" });
+    assert_eq!(schema("/foreign-opt-none"), nullable);
+    assert_eq!(schema("/foreign-option"), nullable);
+    assert_eq!(schema("/foreign-json-option"), nullable);
+    let mut copy = spec["components"]["schemas"]["Line"].clone();
+    copy["nullable"] = true.into();
+    copy["title"] = "Line".into();
+    assert_eq!(spec["components"]["schemas"]["LineNullable"], copy);
+    // poem's own `Option<T>` reuses the schema of `T` and cannot say it is nullable.
+    assert_eq!(
+        schema("/option-of-foreign"),
+        json!({ "$ref": "#/components/schemas/Line" })
+    );
+}
+
+struct Pairs;
+
+#[OpenApi]
+impl Pairs {
+    #[oai(path = "/pair", method = "get")]
+    async fn pair(&self) -> Json<Foreign<(String, i32)>> {
+        Json(Foreign(("answer".to_string(), 42)))
+    }
+}
+
+#[tokio::test]
+async fn tuples_are_inline_arrays() {
+    assert_eq!(Foreign::<(String, i32)>::name(), "(String, i32)");
+    assert_eq!(Foreign::<(Vec<Line>, Option<u8>)>::name(), "([Line], u8)");
+
+    let service = OpenApiService::new(Pairs, "Pairs", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    assert_eq!(
+        spec["paths"]["/pair"]["get"]["responses"]["200"]["content"]["application/json; charset=utf-8"]
+            ["schema"],
+        json!({
+            "type": "array",
+            "minItems": 2,
+            "maxItems": 2,
+            "items": {
+                "anyOf": [
+                    { "type": "string" },
+                    {
+                        "type": "integer",
+                        "format": "int32",
+                        "minimum": f64::from(i32::MIN),
+                        "maximum": f64::from(i32::MAX),
+                    },
+                ],
+            },
+        })
+    );
+    assert_eq!(spec["components"]["schemas"], json!({}));
+
+    let client = TestClient::new(Route::new().nest("/", service));
+    let response = client.get("/pair").send().await;
+    response.assert_status_is_ok();
+    response.assert_json(json!(["answer", 42])).await;
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig, TupleItem};
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct LatLng(f64, f64);
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .description::<LatLng>("A WGS 84 coordinate.")
+            .tuple_items::<LatLng>([
+                TupleItem::new()
+                    .title("latitude")
+                    .minimum(-90.0)
+                    .maximum(90.0),
+                TupleItem::new()
+                    .title("longitude")
+                    .description("East of Greenwich.")
+                    .minimum(-180.0)
+                    .maximum(180.0),
+            ])
+            .install();
+    });
+}
+
+#[test]
+fn tuple_items_annotate_their_positions() {
+    install();
+    let spec = common::spec_for::<Foreign<LatLng>>();
+    assert_eq!(
+        spec["components"]["schemas"]["LatLng"],
+        json!({
+            "type": "array",
+            "description": "A WGS 84 coordinate.",
+            "minItems": 2,
+            "maxItems": 2,
+            "items": {
+                "anyOf": [
+                    {
+                        "type": "number",
+                        "format": "double",
+                        "title": "latitude",
+                        "minimum": -90.0,
+                        "maximum": 90.0,
+                    },
+                    {
+                        "type": "number",
+                        "format": "double",
+                        "title": "longitude",
+                        "description": "East of Greenwich.",
+                        "minimum": -180.0,
+                        "maximum": 180.0,
+                    },
+                ],
+            },
+        })
+    );
+    common::assert_valid::<Foreign<LatLng>>();
+
+    let value = Foreign(LatLng(52.5, 13.4)).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()));
+    for invalid in [
+        json!([52.5]),
+        json!([52.5, 13.4, 0.0]),
+        json!([52.5, 200.0]),
+    ] {
+        assert!(
+            common::validate_value(&spec, &invalid).is_err(),
+            "{invalid}"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, ForeignJson};
+use poem::Route;
+use poem::test::TestClient;
+use poem_openapi::types::ToJSON;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Serialize, Deserialize)]
+struct TreeNode {
+    label: String,
+    children: Vec<TreeNode>,
+}
+
+/// Refers to itself only through `Manager`.
+#[derive(Serialize, Deserialize)]
+struct Employee {
+    name: String,
+    manager: Option<Box<Manager>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Manager {
+    reports: Vec<Employee>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Expr {
+    Number(i64),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/tree", method = "post")]
+    async fn tree(&self, node: ForeignJson<TreeNode>) -> ForeignJson<TreeNode> {
+        node
+    }
+
+    #[oai(path = "/employee", method = "post")]
+    async fn employee(&self, employee: ForeignJson<Employee>) -> ForeignJson<Employee> {
+        employee
+    }
+
+    #[oai(path = "/expr", method = "post")]
+    async fn expr(&self, expr: ForeignJson<Expr>) -> ForeignJson<Expr> {
+        expr
+    }
+}
+
+/// Every `$ref` in `value`.
+fn references(value: &Value, found: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(target)) = map.get("$ref") {
+                found.push(target.clone());
+            }
+            map.values().for_each(|value| references(value, found));
+        }
+        Value::Array(items) => items.iter().for_each(|item| references(item, found)),
+        _ => {}
+    }
+}
+
+#[test]
+fn recursive_types_resolve_to_their_components() {
+    let service = OpenApiService::new(Api, "Recursion", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    assert_eq!(common::validate(&spec), Ok(()));
+
+    let schemas = &spec["components"]["schemas"];
+    let mut found = Vec::new();
+    references(&spec, &mut found);
+    for target in &found {
+        let name = target.strip_prefix("#/components/schemas/").unwrap();
+        assert!(schemas[name].is_object(), "{target} does not resolve");
+    }
+
+    // Directly recursive.
+    assert_eq!(
+        schemas["TreeNode"]["properties"]["children"]["items"],
+        json!({ "$ref": "#/components/schemas/TreeNode" })
+    );
+    // Mutually recursive.
+    assert_eq!(
+        schemas["Employee"]["properties"]["manager"],
+        json!({ "$ref": "#/components/schemas/ManagerNullable" })
+    );
+    // `Manager` is only reached through an `Option`, so only its nullable copy is served.
+    assert_eq!(
+        schemas["ManagerNullable"]["properties"]["reports"]["items"],
+        json!({ "$ref": "#/components/schemas/Employee" })
+    );
+    // A recursive enum, through `Box`.
+    let expr = json!({ "$ref": "#/components/schemas/Expr" });
+    assert_eq!(schemas["Expr"]["oneOf"][1]["properties"]["Neg"], expr);
+    assert_eq!(
+        schemas["Expr"]["oneOf"][2]["properties"]["Add"]["items"],
+        expr
+    );
+
+    let value = Foreign(Expr::Add(
+        Box::new(Expr::Number(1)),
+        Box::new(Expr::Neg(Box::new(Expr::Number(2)))),
+    ))
+    .to_json()
+    .unwrap();
+    let spec = common::spec_for::<Foreign<Expr>>();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[tokio::test]
+async fn recursive_bodies_round_trip() {
+    let client =
+        TestClient::new(Route::new().nest("/", OpenApiService::new(Api, "Recursion", "1.0")));
+    let bodies = [
+        (
+            "/tree",
+            json!({ "label": "root", "children": [{ "label": "leaf", "children": [] }] }),
+        ),
+        (
+            "/employee",
+            json!({
+                "name": "ada",
+                "manager": { "reports": [{ "name": "grace", "manager": null }] },
+            }),
+        ),
+        (
+            "/expr",
+            json!({ "Add": [{ "Number": 1 }, { "Neg": { "Number": 2 } }] }),
+        ),
+    ];
+    for (path, body) in bodies {
+        let response = client.post(path).body_json(&body).send().await;
+        response.assert_status_is_ok();
+        response.assert_json(&body).await;
+    }
+}
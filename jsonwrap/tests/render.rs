@@ -0,0 +1,81 @@
+#![cfg(feature = "trace")]
+
+use std::collections::HashMap;
+
+use jsonwrap::render::markdown_for;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Address {
+    street: String,
+    number: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Customer {
+    name: String,
+    tags: Vec<String>,
+    billing: Option<Address>,
+    notes: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Payment {
+    Cash,
+    Card { last_four: String },
+    Voucher(Address),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CustomerId(u64);
+
+#[test]
+fn renders_a_struct_and_the_components_it_references() {
+    let expected = "\
+## Customer
+
+| Property | Type | Required | Nullable | Constraints | Description |
+|----------|------|----------|----------|-------------|-------------|
+| `name` | `string` | yes | no |  |  |
+| `tags` | array of `string` | yes | no |  |  |
+| `billing` | [`AddressNullable`](#addressnullable) | no | yes |  |  |
+| `notes` | map of `string` | yes | no |  |  |
+
+## AddressNullable
+
+| Property | Type | Required | Nullable | Constraints | Description |
+|----------|------|----------|----------|-------------|-------------|
+| `street` | `string` | yes | no |  |  |
+| `number` | `integer` (int32) | no | yes | ≥ 0, ≤ 65535 |  |
+";
+    assert_eq!(markdown_for::<Customer>(), expected);
+}
+
+#[test]
+fn renders_an_enum_as_its_branches() {
+    let expected = "\
+## Payment
+
+One of the following:
+
+- `string`, one of `\"Cash\"`
+- `{ \"Card\": … }` with `object` with `last_four`
+- `{ \"Voucher\": … }` with [`Address`](#address)
+
+## Address
+
+| Property | Type | Required | Nullable | Constraints | Description |
+|----------|------|----------|----------|-------------|-------------|
+| `street` | `string` | yes | no |  |  |
+| `number` | `integer` (int32) | no | yes | ≥ 0, ≤ 65535 |  |
+";
+    assert_eq!(markdown_for::<Payment>(), expected);
+}
+
+#[test]
+fn renders_a_newtype_as_its_inner_type() {
+    assert_eq!(
+        markdown_for::<CustomerId>(),
+        "## CustomerId\n\nType: `integer` (int64)\n\nConstraints: ≥ 0\n"
+    );
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig, Repr};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Stands in for `uuid::Uuid` with its human-readable serialization.
+#[derive(Serialize, Deserialize)]
+struct TextId(String);
+
+/// Stands in for a uuid built with the compact, byte-array serialization.
+#[derive(Serialize, Deserialize)]
+struct CompactId([u8; 16]);
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    id: TextId,
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .declare_repr(
+                Repr::HyphenatedString,
+                TextId("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string()),
+            )
+            .declare_repr(Repr::HyphenatedString, CompactId([0; 16]))
+            .install();
+    });
+}
+
+#[test]
+fn declared_repr_replaces_traced_schema() {
+    install();
+    let spec = common::spec_for::<Foreign<Order>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Order"]["properties"]["id"],
+        json!({ "type": "string", "format": "uuid" })
+    );
+    common::assert_valid::<Foreign<Order>>();
+}
+
+#[test]
+#[should_panic(expected = "`CompactId` is declared as HyphenatedString")]
+fn mismatched_repr_fails_registration() {
+    install();
+    common::spec_for::<Foreign<CompactId>>();
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::Foreign;
+use poem::Route;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem_openapi::payload::Json;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Serialize, Deserialize)]
+struct CreateOrderRequest {
+    customer: String,
+    quantity: u32,
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/orders", method = "post")]
+    async fn create(
+        &self,
+        order: Json<Foreign<CreateOrderRequest>>,
+    ) -> Json<Foreign<CreateOrderRequest>> {
+        order
+    }
+}
+
+fn service() -> OpenApiService<Api, ()> {
+    OpenApiService::new(Api, "Orders", "1.0")
+}
+
+#[test]
+fn bodies_reference_the_response_component() {
+    let spec: Value = serde_json::from_str(&service().spec()).unwrap();
+    let operation = &spec["paths"]["/orders"]["post"];
+    let component = json!({ "$ref": "#/components/schemas/CreateOrderRequest" });
+    assert_eq!(
+        operation["requestBody"]["content"]["application/json; charset=utf-8"]["schema"],
+        component
+    );
+    assert_eq!(
+        operation["responses"]["200"]["content"]["application/json; charset=utf-8"]["schema"],
+        component
+    );
+}
+
+#[tokio::test]
+async fn foreign_bodies_are_parsed() {
+    let client = TestClient::new(Route::new().nest("/", service()));
+    let body = json!({ "customer": "ada", "quantity": 2 });
+    let response = client.post("/orders").body_json(&body).send().await;
+    response.assert_status_is_ok();
+    response.assert_json(&body).await;
+
+    for malformed in [
+        json!({ "customer": "ada" }),
+        json!({ "customer": "ada", "quantity": "two" }),
+        json!([1, 2]),
+    ] {
+        let response = client.post("/orders").body_json(&malformed).send().await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
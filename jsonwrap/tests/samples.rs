@@ -0,0 +1,43 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::num::NonZeroU16;
+
+use jsonwrap::Foreign;
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize)]
+struct Port(NonZeroU16);
+
+#[derive(Serialize, Deserialize)]
+enum Endpoint {
+    Local,
+    Remote { host: String, port: Port },
+}
+
+#[test]
+fn samples_complete_what_tracing_cannot() {
+    // A port of 0 is rejected, so tracing from placeholder values fails.
+    let spec = common::spec_for::<Foreign<Endpoint>>();
+    assert!(spec["components"]["schemas"]["Port"].is_null());
+
+    let sample = Endpoint::Remote {
+        host: "db".to_string(),
+        port: Port(NonZeroU16::new(5432).unwrap()),
+    };
+    Foreign::<Endpoint>::trace_sample(&sample).unwrap();
+
+    let spec = common::spec_for::<Foreign<Endpoint>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Port"],
+        json!({ "type": "integer", "format": "int32", "minimum": 0.0, "maximum": 65535.0 })
+    );
+    assert_eq!(schemas["Endpoint"]["oneOf"][0]["enum"], json!(["Local"]));
+    common::assert_valid::<Foreign<Endpoint>>();
+    let value = Foreign(sample).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
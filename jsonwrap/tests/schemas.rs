@@ -0,0 +1,72 @@
+#![cfg(feature = "trace")]
+
+use jsonwrap::{Foreign, ForeignOpt};
+use poem::Route;
+use poem::http::StatusCode;
+use poem::test::TestClient;
+use poem_openapi::payload::Json;
+use poem_openapi::{OpenApi, OpenApiService};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Serialize, Deserialize)]
+struct Customer {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Invoice {
+    customer: Customer,
+    total: u64,
+}
+
+struct Api;
+
+#[OpenApi]
+impl Api {
+    #[oai(path = "/invoices", method = "post")]
+    async fn create(&self, invoice: Json<Foreign<Invoice>>) -> Json<ForeignOpt<Invoice>> {
+        Json(ForeignOpt(Some(invoice.0.0)))
+    }
+
+    #[oai(path = "/invoices", method = "get")]
+    async fn list(&self) -> Json<Foreign<Page<Invoice>>> {
+        Json(Foreign(Page { items: vec![] }))
+    }
+}
+
+#[tokio::test]
+async fn components_are_served_as_in_the_spec() {
+    let service = OpenApiService::new(Api, "Invoices", "1.0");
+    let spec: Value = serde_json::from_str(&service.spec()).unwrap();
+    let components = &spec["components"]["schemas"];
+
+    let client = TestClient::new(Route::new().nest("/schemas", jsonwrap::schema_endpoint()));
+
+    let response = client.get("/schemas").send().await;
+    response.assert_status_is_ok();
+    response
+        .assert_json(json!([
+            "Customer",
+            "Invoice",
+            "InvoiceNullable",
+            "Page_Invoice"
+        ]))
+        .await;
+
+    let response = client.get("/schemas/_all").send().await;
+    response.assert_status_is_ok();
+    response.assert_json(components).await;
+
+    let response = client.get("/schemas/Invoice").send().await;
+    response.assert_status_is_ok();
+    response.assert_json(&components["Invoice"]).await;
+
+    let response = client.get("/schemas/Receipt").send().await;
+    response.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,1093 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use jsonwrap::{Foreign, ForeignOpt};
+use poem_openapi::registry::{MetaSchema, MetaSchemaRef, Registry};
+use poem_openapi::types::{ParseFromJSON, ToJSON, Type};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Address {
+    street: String,
+    number: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserId(u64);
+
+#[derive(Serialize, Deserialize)]
+enum Contact {
+    Email(String),
+    Post {
+        address: Address,
+        attention: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct User {
+    id: UserId,
+    name: String,
+    score: f64,
+    address: Option<Address>,
+    history: Vec<Address>,
+    labels: HashMap<String, Option<i64>>,
+    contact: Contact,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Status {
+    Pending,
+    Active,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Marker;
+
+#[derive(Serialize, Deserialize)]
+struct Beacon {
+    marker: Marker,
+    ping: (),
+}
+
+#[test]
+fn generated_specs_are_valid() {
+    common::assert_valid::<Foreign<User>>();
+    common::assert_valid::<ForeignOpt<User>>();
+    common::assert_valid::<Foreign<UserId>>();
+    common::assert_valid::<Foreign<Vec<Address>>>();
+    common::assert_valid::<Foreign<String>>();
+    common::assert_valid::<Foreign<Status>>();
+    common::assert_valid::<Foreign<()>>();
+    common::assert_valid::<Foreign<Marker>>();
+    common::assert_valid::<Foreign<Beacon>>();
+}
+
+#[test]
+fn units_admit_only_null() {
+    let null = serde_json::json!({ "nullable": true, "enum": [null] });
+    let spec = common::spec_for::<Foreign<Beacon>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["Marker"], null);
+    assert_eq!(schemas["Beacon"]["properties"]["ping"], null);
+
+    let spec = common::spec_for::<Foreign<()>>();
+    assert_eq!(*common::response_schema(&spec), null);
+    for strict in [false, true] {
+        let validate = if strict {
+            common::validate_value_strict
+        } else {
+            common::validate_value
+        };
+        assert_eq!(validate(&spec, &serde_json::Value::Null), Ok(()));
+        assert!(validate(&spec, &serde_json::json!({})).is_err());
+    }
+}
+
+#[test]
+fn unit_enums_are_string_enums() {
+    let spec = common::spec_for::<Foreign<Status>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Status"],
+        serde_json::json!({ "type": "string", "enum": ["Pending", "Active", "Closed"] })
+    );
+    for status in [Status::Pending, Status::Active, Status::Closed] {
+        let value = Foreign(status).to_json().unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()));
+    }
+    assert!(common::validate_value(&spec, &serde_json::json!({ "Pending": null })).is_err());
+}
+
+#[derive(Serialize, Deserialize)]
+struct Case {
+    status: Status,
+}
+
+#[test]
+fn unit_enum_fields_refer_to_string_enums() {
+    let spec = common::spec_for::<Foreign<Case>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Case"]["properties"]["status"],
+        serde_json::json!({ "$ref": "#/components/schemas/Status" })
+    );
+    assert_eq!(
+        schemas["Status"],
+        serde_json::json!({ "type": "string", "enum": ["Pending", "Active", "Closed"] })
+    );
+    for (status, valid) in [
+        (serde_json::json!("Closed"), true),
+        (serde_json::json!("Reopened"), false),
+        (serde_json::json!({ "Closed": null }), false),
+    ] {
+        let value = serde_json::json!({ "status": status });
+        assert_eq!(
+            common::validate_value(&spec, &value).is_ok(),
+            valid,
+            "{value}"
+        );
+    }
+    common::assert_valid::<Foreign<Case>>();
+}
+
+#[test]
+fn enums_inside_structs_are_traced_with_every_variant() {
+    let mut registry = Registry::new();
+    Foreign::<User>::register(&mut registry);
+    let schemas = serde_json::to_value(&registry.schemas).unwrap();
+    assert_eq!(schemas["User"]["type"], "object");
+    let variants: Vec<_> = schemas["Contact"]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|variant| variant["properties"].as_object().unwrap().keys())
+        .collect();
+    assert_eq!(variants, ["Email", "Post"]);
+}
+
+#[test]
+fn required_lists_every_field_but_options() {
+    let mut registry = Registry::new();
+    Foreign::<User>::register(&mut registry);
+    let schemas = serde_json::to_value(&registry.schemas).unwrap();
+    assert_eq!(
+        schemas["User"]["required"],
+        serde_json::json!(["id", "name", "score", "history", "labels", "contact"])
+    );
+    // Reached through a reference rather than registered on its own.
+    assert_eq!(
+        schemas["Address"]["required"],
+        serde_json::json!(["street"])
+    );
+    let post = schemas["Contact"]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find_map(|variant| variant["properties"].get("Post"))
+        .expect("a `Post` variant");
+    assert_eq!(post["required"], serde_json::json!(["address"]));
+
+    // `required` shares the names leaked for `properties`.
+    let user = &registry.schemas["User"];
+    for name in &user.required {
+        let (property, _) = user
+            .properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .unwrap();
+        assert_eq!(property.as_ptr(), name.as_ptr(), "{name}");
+    }
+}
+
+#[test]
+fn only_non_option_fields_are_required() {
+    #[derive(Serialize, Deserialize)]
+    struct Profile {
+        handle: String,
+        bio: Option<String>,
+        age: i32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum Change {
+        Profile {
+            handle: String,
+            bio: Option<String>,
+            age: i32,
+        },
+    }
+
+    let mut registry = Registry::new();
+    Foreign::<Profile>::register(&mut registry);
+    Foreign::<Change>::register(&mut registry);
+    let schemas = serde_json::to_value(&registry.schemas).unwrap();
+    let required = serde_json::json!(["handle", "age"]);
+    assert_eq!(schemas["Profile"]["required"], required);
+    let variant = &schemas["Change"]["oneOf"][0]["properties"]["Profile"];
+    assert_eq!(variant["required"], required);
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Priority {
+    Low,
+    Normal,
+    Urgent,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Escalation {
+    steps: Vec<Priority>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Shift {
+    Early,
+    Late,
+    Night,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Rota {
+    shift: Option<Shift>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Piece {
+    Circle,
+    Square,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Board {
+    pieces: HashMap<String, Piece>,
+}
+
+#[test]
+fn enums_only_reached_below_an_option_list_every_variant() {
+    let spec = common::spec_for::<Foreign<Rota>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Rota"]["properties"]["shift"],
+        serde_json::json!({
+            "type": "string",
+            "enum": ["Early", "Late", "Night", null],
+            "nullable": true,
+        })
+    );
+    for (shift, valid) in [
+        (serde_json::json!(null), true),
+        (serde_json::json!("Night"), true),
+        (serde_json::json!("Noon"), false),
+    ] {
+        let value = serde_json::json!({ "shift": shift });
+        assert_eq!(
+            common::validate_value(&spec, &value).is_ok(),
+            valid,
+            "{value}"
+        );
+    }
+    common::assert_valid::<Foreign<Rota>>();
+}
+
+#[test]
+fn enums_only_reached_below_a_vec_list_every_variant() {
+    let spec = common::spec_for::<Foreign<Escalation>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Priority"],
+        serde_json::json!({ "type": "string", "enum": ["low", "normal", "urgent"] })
+    );
+    let value = serde_json::json!({ "steps": ["normal", "urgent"] });
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    let value = serde_json::json!({ "steps": ["later"] });
+    assert!(common::validate_value(&spec, &value).is_err(), "{value}");
+    common::assert_valid::<Foreign<Escalation>>();
+}
+
+#[test]
+fn enums_only_reached_below_a_map_list_every_variant() {
+    let spec = common::spec_for::<Foreign<Board>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Piece"],
+        serde_json::json!({ "type": "string", "enum": ["Circle", "Square"] })
+    );
+    let value = serde_json::json!({ "pieces": { "a1": "Square", "b2": "Circle" } });
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    let value = serde_json::json!({ "pieces": { "a1": "Triangle" } });
+    assert!(common::validate_value(&spec, &value).is_err(), "{value}");
+    assert!(jsonwrap::opaque_types(&[<Foreign<Board> as Type>::register]).is_empty());
+}
+
+#[derive(Serialize, Deserialize)]
+struct Ticket {
+    status: Status,
+    previous: Option<Status>,
+}
+
+#[test]
+fn optional_unit_enums_list_null() {
+    let spec = common::spec_for::<Foreign<Ticket>>();
+    let properties = &spec["components"]["schemas"]["Ticket"]["properties"];
+    assert_eq!(
+        properties["status"],
+        serde_json::json!({ "$ref": "#/components/schemas/Status" })
+    );
+    let nullable_status = serde_json::json!({
+        "type": "string",
+        "enum": ["Pending", "Active", "Closed", null],
+        "nullable": true,
+    });
+    assert_eq!(properties["previous"], nullable_status);
+    common::assert_valid::<Foreign<Ticket>>();
+
+    for (previous, valid) in [
+        (serde_json::json!(null), true),
+        (serde_json::json!("Active"), true),
+        (serde_json::json!("Reopened"), false),
+    ] {
+        let value = serde_json::json!({ "status": "Pending", "previous": previous });
+        assert_eq!(
+            common::validate_value(&spec, &value).is_ok(),
+            valid,
+            "{value}"
+        );
+    }
+
+    let spec = common::spec_for::<ForeignOpt<Status>>();
+    assert_eq!(*common::response_schema(&spec), nullable_status);
+}
+
+fn spec_with_component(schema: MetaSchema) -> serde_json::Value {
+    let mut registry = Registry::new();
+    registry.schemas.insert("Broken".to_string(), schema);
+    common::spec_with(MetaSchemaRef::Reference("Broken".to_string()), &registry)
+}
+
+#[test]
+fn rejects_tuples_as_array_all_of() {
+    // The shape tuples used to be described with: an array without `items`.
+    let spec = spec_with_component(MetaSchema {
+        ty: "array",
+        all_of: vec![
+            MetaSchemaRef::Inline(Box::new(MetaSchema::new("integer"))),
+            MetaSchemaRef::Inline(Box::new(MetaSchema::new("string"))),
+        ],
+        ..MetaSchema::ANY
+    });
+    assert!(common::validate(&spec).is_err());
+}
+
+#[test]
+fn rejects_null_type() {
+    // OpenAPI 3.0 has no `null` type; nullability is expressed with `nullable`.
+    let spec = spec_with_component(MetaSchema::new("null"));
+    assert!(common::validate(&spec).is_err());
+}
+
+#[derive(Serialize, Deserialize)]
+struct Renamed {
+    #[serde(rename = "displayName")]
+    display_name: String,
+    #[serde(rename = "nickName")]
+    nick_name: Option<String>,
+}
+
+#[test]
+fn renamed_fields_use_their_serialized_names() {
+    // serde_reflection traces the names serde reads, so renames need no extra handling.
+    let spec = common::spec_for::<Foreign<Renamed>>();
+    let schema = &spec["components"]["schemas"]["Renamed"];
+    let properties: Vec<_> = schema["properties"].as_object().unwrap().keys().collect();
+    assert_eq!(properties, ["displayName", "nickName"]);
+    assert_eq!(schema["required"], serde_json::json!(["displayName"]));
+
+    let value = Foreign(Renamed {
+        display_name: "Ada".to_string(),
+        nick_name: None,
+    })
+    .to_json()
+    .unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()));
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Account {
+    #[serde(rename = "id")]
+    account_number: u32,
+    owner_name: String,
+    plan: Plan,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Plan {
+    FreeTier,
+    #[serde(rename = "pro")]
+    Professional {
+        seat_count: u16,
+    },
+}
+
+#[test]
+fn rename_all_applies_to_fields_and_variants() {
+    let spec = common::spec_for::<Foreign<Account>>();
+    let schemas = &spec["components"]["schemas"];
+    let properties: Vec<_> = schemas["Account"]["properties"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .collect();
+    assert_eq!(properties, ["id", "ownerName", "plan"]);
+    assert_eq!(
+        schemas["Plan"]["oneOf"][0]["enum"],
+        serde_json::json!(["free-tier"])
+    );
+    assert_eq!(
+        schemas["Plan"]["oneOf"][1]["required"],
+        serde_json::json!(["pro"])
+    );
+
+    for plan in [Plan::FreeTier, Plan::Professional { seat_count: 3 }] {
+        let value = Foreign(Account {
+            account_number: 7,
+            owner_name: "Ada".to_string(),
+            plan,
+        })
+        .to_json()
+        .unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum Event {
+    Started,
+    Stopped,
+    Progress(u8),
+    Failed { code: i32, reason: Option<String> },
+}
+
+#[test]
+fn data_carrying_enums_are_one_of_their_serialized_shapes() {
+    let spec = common::spec_for::<Foreign<Event>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Event"],
+        serde_json::json!({
+            "oneOf": [
+                { "type": "string", "enum": ["Started", "Stopped"] },
+                {
+                    "type": "object",
+                    "required": ["Progress"],
+                    "properties": {
+                        "Progress": { "type": "integer", "format": "int32", "minimum": 0.0, "maximum": 255.0 },
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["Failed"],
+                    "properties": {
+                        "Failed": {
+                            "type": "object",
+                            "required": ["code"],
+                            "properties": {
+                                "code": {
+                                    "type": "integer",
+                                    "format": "int32",
+                                    "minimum": f64::from(i32::MIN),
+                                    "maximum": f64::from(i32::MAX),
+                                },
+                                "reason": { "type": "string", "nullable": true },
+                            },
+                        },
+                    },
+                },
+            ],
+        })
+    );
+    common::assert_valid::<Foreign<Event>>();
+
+    let events = [
+        Event::Started,
+        Event::Stopped,
+        Event::Progress(40),
+        Event::Failed {
+            code: 2,
+            reason: None,
+        },
+    ];
+    for event in events {
+        let value = Foreign(event).to_json().unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+    let wrapped_unit = serde_json::json!({ "Started": null });
+    assert!(common::validate_value(&spec, &wrapped_unit).is_err());
+    // A value is exactly one variant; `anyOf` would accept one carrying two.
+    let two_variants = serde_json::json!({ "Progress": 40, "Failed": { "code": 2 } });
+    assert!(common::validate_value(&spec, &two_variants).is_err());
+}
+
+#[derive(Serialize, Deserialize)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+type MaybeAddress = Option<Address>;
+type MaybePage = Option<Page<Address>>;
+
+#[test]
+fn top_level_options_match_foreign_opt() {
+    assert_eq!(Foreign::<MaybeAddress>::name(), "Address");
+    assert_eq!(
+        Foreign::<MaybeAddress>::schema_ref(),
+        ForeignOpt::<Address>::schema_ref()
+    );
+    assert_eq!(Foreign::<MaybePage>::name(), "Page_Address");
+    assert_eq!(
+        Foreign::<MaybePage>::schema_ref(),
+        ForeignOpt::<Page<Address>>::schema_ref()
+    );
+
+    let spec = common::spec_for::<Foreign<MaybePage>>();
+    assert!(spec["components"]["schemas"]["Page_Address"].is_object());
+    common::assert_valid::<Foreign<MaybePage>>();
+    let pages: [MaybePage; 2] = [None, Some(Page { items: vec![] })];
+    for page in pages {
+        let value = Foreign(page).to_json().unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+}
+
+type Pages = Vec<Page<Address>>;
+
+#[test]
+fn vecs_reference_their_item_components() {
+    assert_eq!(Foreign::<Vec<Address>>::name(), "[Address]");
+    assert_eq!(Foreign::<Pages>::name(), "[Page_Address]");
+
+    let spec = common::spec_for::<Foreign<Pages>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        serde_json::json!({
+            "type": "array",
+            "items": { "$ref": "#/components/schemas/Page_Address" },
+        })
+    );
+    assert!(spec["components"]["schemas"]["Page_Address"].is_object());
+    common::assert_valid::<Foreign<Pages>>();
+    let pages: Pages = vec![Page { items: vec![] }];
+    let value = Foreign(pages).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[test]
+fn nested_vecs_are_nested_arrays_of_one_component() {
+    assert_eq!(Foreign::<Vec<Vec<Address>>>::name(), "[[Address]]");
+
+    let spec = common::spec_for::<Foreign<Vec<Vec<Address>>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        serde_json::json!({
+            "type": "array",
+            "items": {
+                "type": "array",
+                "items": { "$ref": "#/components/schemas/Address" },
+            },
+        })
+    );
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+    assert_eq!(schemas.keys().collect::<Vec<_>>(), ["Address"]);
+    common::assert_valid::<Foreign<Vec<Vec<Address>>>>();
+
+    let grid = vec![
+        vec![Address {
+            street: "Main St".to_string(),
+            number: Some(1),
+        }],
+        vec![],
+    ];
+    let value = Foreign(grid).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[derive(Serialize, Deserialize)]
+struct Node {
+    value: i32,
+    children: Vec<Node>,
+    next: Option<Box<Node>>,
+}
+
+#[test]
+fn recursive_types_reference_themselves() {
+    let spec = common::spec_for::<Foreign<Node>>();
+    let node = &spec["components"]["schemas"]["Node"];
+    assert_eq!(
+        node["properties"]["children"]["items"],
+        serde_json::json!({ "$ref": "#/components/schemas/Node" })
+    );
+    assert_eq!(
+        node["properties"]["next"],
+        serde_json::json!({ "$ref": "#/components/schemas/NodeNullable" })
+    );
+    // Copied once `Node` is built, not from the placeholder standing in for it.
+    let mut copy = node.clone();
+    copy["nullable"] = true.into();
+    copy["title"] = "Node".into();
+    assert_eq!(spec["components"]["schemas"]["NodeNullable"], copy);
+    common::assert_valid::<Foreign<Node>>();
+
+    let leaf = || Node {
+        value: 2,
+        children: vec![],
+        next: None,
+    };
+    let tree = Node {
+        value: 1,
+        children: vec![leaf(), leaf()],
+        next: Some(Box::new(leaf())),
+    };
+    let value = Foreign(tree).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()));
+}
+
+#[derive(Serialize, Deserialize)]
+struct Directory {
+    name: String,
+    entries: HashMap<String, Directory>,
+}
+
+#[test]
+fn maps_of_recursive_types_reference_one_component() {
+    let spec = common::spec_for::<Foreign<HashMap<String, Directory>>>();
+    let reference = serde_json::json!({ "$ref": "#/components/schemas/Directory" });
+    assert_eq!(
+        common::response_schema(&spec)["additionalProperties"],
+        reference
+    );
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+    assert_eq!(schemas.keys().collect::<Vec<_>>(), ["Directory"]);
+    assert_eq!(
+        schemas["Directory"]["properties"]["entries"]["additionalProperties"],
+        reference
+    );
+    assert_eq!(common::validate(&spec), Ok(()));
+
+    let leaf = |name: &str| Directory {
+        name: name.to_string(),
+        entries: HashMap::new(),
+    };
+    let root = HashMap::from([(
+        "src".to_string(),
+        Directory {
+            name: "src".to_string(),
+            entries: HashMap::from([("bin".to_string(), leaf("bin"))]),
+        },
+    )]);
+    let value = Foreign(root).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[derive(Serialize, Deserialize)]
+struct Widths {
+    a: i8,
+    b: i16,
+    c: i32,
+    d: i64,
+    e: i128,
+    f: u8,
+    g: u16,
+    h: u32,
+    i: u64,
+    j: u128,
+    nested: Vec<HashMap<String, u8>>,
+}
+
+#[test]
+fn integers_carry_their_width_and_bounds() {
+    let spec = common::spec_for::<Foreign<Widths>>();
+    let properties = &spec["components"]["schemas"]["Widths"]["properties"];
+    // poem writes bounds as floats.
+    let byte = serde_json::json!({
+        "type": "integer", "format": "int32", "minimum": 0.0, "maximum": 255.0,
+    });
+    assert_eq!(
+        *properties,
+        serde_json::json!({
+            "a": { "type": "integer", "format": "int32", "minimum": -128.0, "maximum": 127.0 },
+            "b": { "type": "integer", "format": "int32", "minimum": -32768.0, "maximum": 32767.0 },
+            "c": {
+                "type": "integer",
+                "format": "int32",
+                "minimum": f64::from(i32::MIN),
+                "maximum": f64::from(i32::MAX),
+            },
+            "d": { "type": "integer", "format": "int64" },
+            "e": { "type": "string", "format": "int128", "pattern": "^-?[0-9]+$" },
+            "f": byte,
+            "g": { "type": "integer", "format": "int32", "minimum": 0.0, "maximum": 65535.0 },
+            "h": {
+                "type": "integer",
+                "format": "int64",
+                "minimum": 0.0,
+                "maximum": f64::from(u32::MAX),
+            },
+            "i": { "type": "integer", "format": "int64", "minimum": 0.0 },
+            "j": { "type": "string", "format": "int128", "pattern": "^[0-9]+$" },
+            "nested": {
+                "type": "array",
+                "items": { "type": "object", "additionalProperties": byte },
+            },
+        })
+    );
+    common::assert_valid::<Foreign<Widths>>();
+}
+
+#[derive(Serialize, Deserialize)]
+struct Measurement {
+    value: f64,
+}
+
+#[test]
+fn non_finite_floats_are_written_as_null_by_default() {
+    let value = Foreign(Measurement { value: f64::NAN }).to_json().unwrap();
+    assert_eq!(value, serde_json::json!({ "value": null }));
+}
+
+#[derive(Serialize, Deserialize)]
+struct Rgb(u8, u8, u8);
+
+#[derive(Serialize, Deserialize)]
+struct Labelled {
+    pair: (String, i32),
+    color: Rgb,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Shape {
+    Segment(f64, f64),
+}
+
+#[test]
+fn tuples_are_fixed_length_arrays() {
+    let spec = common::spec_for::<Foreign<Labelled>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Labelled"]["properties"]["pair"],
+        serde_json::json!({
+            "type": "array",
+            "minItems": 2,
+            "maxItems": 2,
+            "items": {
+                "anyOf": [
+                    { "type": "string" },
+                    {
+                        "type": "integer",
+                        "format": "int32",
+                        "minimum": f64::from(i32::MIN),
+                        "maximum": f64::from(i32::MAX),
+                    },
+                ],
+            },
+        })
+    );
+    // All three positions share one schema, so it is used for `items` directly.
+    assert_eq!(
+        schemas["Rgb"],
+        serde_json::json!({
+            "type": "array",
+            "minItems": 3,
+            "maxItems": 3,
+            "items": { "type": "integer", "format": "int32", "minimum": 0.0, "maximum": 255.0 },
+        })
+    );
+    common::assert_valid::<Foreign<Labelled>>();
+    common::assert_valid::<Foreign<Shape>>();
+
+    let value = Foreign(Labelled {
+        pair: ("x".to_string(), 3),
+        color: Rgb(1, 2, 3),
+    })
+    .to_json()
+    .unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()));
+    let short = serde_json::json!({ "pair": ["x"], "color": [1, 2, 3] });
+    assert!(common::validate_value(&spec, &short).is_err());
+
+    let spec = common::spec_for::<Foreign<Shape>>();
+    let value = Foreign(Shape::Segment(0.0, 1.5)).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()));
+}
+
+#[derive(Serialize, Deserialize)]
+struct Reading {
+    ratio: f32,
+    value: f64,
+    offset: Option<f32>,
+    series: Vec<f64>,
+    by_sensor: HashMap<String, f32>,
+    range: (f32, f64),
+}
+
+#[test]
+fn floats_are_float_or_double() {
+    let spec = common::spec_for::<Foreign<Reading>>();
+    let properties = &spec["components"]["schemas"]["Reading"]["properties"];
+    let float = serde_json::json!({ "type": "number", "format": "float" });
+    let double = serde_json::json!({ "type": "number", "format": "double" });
+    assert_eq!(properties["ratio"], float);
+    assert_eq!(properties["value"], double);
+    assert_eq!(
+        properties["offset"],
+        serde_json::json!({ "type": "number", "format": "float", "nullable": true })
+    );
+    assert_eq!(properties["series"]["items"], double);
+    assert_eq!(properties["by_sensor"]["additionalProperties"], float);
+    assert_eq!(
+        properties["range"]["items"],
+        serde_json::json!({ "anyOf": [float, double] })
+    );
+    common::assert_valid::<Foreign<Reading>>();
+}
+
+#[derive(Serialize, Deserialize)]
+struct Point(f64, f64);
+
+#[derive(Serialize, Deserialize)]
+struct Meters(f64);
+
+#[derive(Serialize, Deserialize)]
+struct Waypoint {
+    at: Point,
+    altitude: Meters,
+}
+
+#[test]
+fn tuple_structs_are_arrays_and_newtypes_stay_transparent() {
+    let spec = common::spec_for::<Foreign<Point>>();
+    let double = serde_json::json!({ "type": "number", "format": "double" });
+    assert_eq!(
+        spec["components"]["schemas"]["Point"],
+        serde_json::json!({
+            "type": "array",
+            "minItems": 2,
+            "maxItems": 2,
+            "items": double,
+        })
+    );
+    let value = Foreign(Point(1.0, 2.0)).to_json().unwrap();
+    assert_eq!(value, serde_json::json!([1.0, 2.0]));
+    assert_eq!(common::validate_value(&spec, &value), Ok(()));
+
+    let spec = common::spec_for::<Foreign<Waypoint>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["Meters"], double);
+    let value = Foreign(Waypoint {
+        at: Point(1.0, 2.0),
+        altitude: Meters(35.5),
+    })
+    .to_json()
+    .unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({ "at": [1.0, 2.0], "altitude": 35.5 })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[derive(Serialize, Deserialize)]
+struct Overrides {
+    values: HashMap<String, Option<u32>>,
+}
+
+#[test]
+fn map_values_keep_explicit_nulls() {
+    let spec = common::spec_for::<Foreign<Overrides>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Overrides"]["properties"]["values"]["additionalProperties"],
+        serde_json::json!({
+            "type": "integer",
+            "format": "int64",
+            "minimum": 0.0,
+            "maximum": f64::from(u32::MAX),
+            "nullable": true,
+        })
+    );
+
+    let overrides = Overrides {
+        values: HashMap::from([
+            ("timeout".to_string(), None),
+            ("retries".to_string(), Some(3)),
+        ]),
+    };
+    let value = Foreign(overrides).to_json().unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({ "values": { "timeout": null, "retries": 3 } })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+
+    let parsed = match Foreign::<Overrides>::parse_from_json(Some(value)) {
+        Ok(Foreign(parsed)) => parsed,
+        Err(err) => panic!("{}", err.message()),
+    };
+    assert_eq!(parsed.values.get("timeout"), Some(&None));
+    assert_eq!(parsed.values.get("retries"), Some(&Some(3)));
+    assert_eq!(parsed.values.get("backoff"), None);
+}
+
+#[derive(Serialize, Deserialize)]
+enum Limits {
+    Unlimited,
+    PerRoute(HashMap<String, i32>),
+}
+
+#[test]
+fn variants_can_wrap_maps() {
+    let spec = common::spec_for::<Foreign<Limits>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Limits"]["oneOf"][1],
+        serde_json::json!({
+            "type": "object",
+            "required": ["PerRoute"],
+            "properties": {
+                "PerRoute": {
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "integer",
+                        "format": "int32",
+                        "minimum": f64::from(i32::MIN),
+                        "maximum": f64::from(i32::MAX),
+                    },
+                },
+            },
+        })
+    );
+    common::assert_valid::<Foreign<Limits>>();
+    let limits = Limits::PerRoute(HashMap::from([("/search".to_string(), 10)]));
+    let value = Foreign(limits).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[derive(Serialize, Deserialize)]
+struct Grade {
+    letter: char,
+    modifier: Option<char>,
+}
+
+#[test]
+fn chars_are_single_character_strings() {
+    let spec = common::spec_for::<Foreign<Grade>>();
+    let properties = &spec["components"]["schemas"]["Grade"]["properties"];
+    assert_eq!(
+        properties["letter"],
+        serde_json::json!({ "type": "string", "minLength": 1, "maxLength": 1 })
+    );
+    common::assert_valid::<Foreign<Grade>>();
+
+    let grade = Grade {
+        letter: 'B',
+        modifier: Some('+'),
+    };
+    let value = Foreign(grade).to_json().unwrap();
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    let long = serde_json::json!({ "letter": "AB", "modifier": null });
+    assert!(common::validate_value(&spec, &long).is_err());
+}
+
+#[test]
+fn top_level_tuples_validate() {
+    let spec = common::spec_for::<Foreign<(String, u32)>>();
+    assert_eq!(common::response_schema(&spec)["minItems"], 2);
+    assert_eq!(common::response_schema(&spec)["maxItems"], 2);
+    assert!(common::response_schema(&spec)["allOf"].is_null());
+    common::assert_valid::<Foreign<(String, u32)>>();
+
+    let value = Foreign(("port".to_string(), 8080_u32)).to_json().unwrap();
+    assert_eq!(value, serde_json::json!(["port", 8080]));
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    let reversed = serde_json::json!([8080, "port", "extra"]);
+    assert!(common::validate_value(&spec, &reversed).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct AddressPatch {
+    street: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    number: Option<Option<u16>>,
+    forward_to: Option<Option<Address>>,
+}
+
+#[test]
+fn nested_options_collapse_into_one_nullable() {
+    let spec = common::spec_for::<Foreign<AddressPatch>>();
+    let patch = &spec["components"]["schemas"]["AddressPatch"];
+    assert_eq!(
+        patch["properties"],
+        serde_json::json!({
+            "street": { "type": "string", "nullable": true },
+            "number": {
+                "type": "integer",
+                "format": "int32",
+                "minimum": 0.0,
+                "maximum": 65535.0,
+                "nullable": true,
+            },
+            "forward_to": { "$ref": "#/components/schemas/AddressNullable" },
+        })
+    );
+    assert!(patch.get("required").is_none(), "{patch}");
+    common::assert_valid::<Foreign<AddressPatch>>();
+
+    // serde reads both an absent field and `null` as `None`, and writes `Some(None)` as
+    // `null`; only `skip_serializing_if` leaves the field out.
+    for (value, expected) in [
+        (
+            serde_json::json!({}),
+            AddressPatch {
+                street: None,
+                number: None,
+                forward_to: None,
+            },
+        ),
+        (
+            serde_json::json!({ "street": null, "number": null, "forward_to": null }),
+            AddressPatch {
+                street: None,
+                number: None,
+                forward_to: None,
+            },
+        ),
+        (
+            serde_json::json!({ "street": "Main St", "number": 4, "forward_to": null }),
+            AddressPatch {
+                street: Some(Some("Main St".to_string())),
+                number: Some(Some(4)),
+                forward_to: None,
+            },
+        ),
+    ] {
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+        let Foreign(parsed) = Foreign::<AddressPatch>::parse_from_json(Some(value))
+            .unwrap_or_else(|err| panic!("{}", err.message()));
+        assert_eq!(parsed, expected);
+    }
+    let cleared = AddressPatch {
+        street: Some(None),
+        number: Some(None),
+        forward_to: None,
+    };
+    let value = Foreign(cleared).to_json().unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({ "street": null, "number": null, "forward_to": null })
+    );
+    assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+}
+
+#[test]
+fn top_level_nested_options_match_single_ones() {
+    assert_eq!(Foreign::<Option<Option<Address>>>::name(), "Address");
+    let nested = common::spec_for::<Foreign<Option<Option<Address>>>>();
+    let single = common::spec_for::<Foreign<Option<Address>>>();
+    assert_eq!(
+        common::response_schema(&nested),
+        common::response_schema(&single)
+    );
+    assert_eq!(
+        nested["components"]["schemas"],
+        single["components"]["schemas"]
+    );
+    common::assert_valid::<Foreign<Option<Option<Address>>>>();
+
+    assert_eq!(
+        Foreign::<Option<Option<Address>>>(Some(None)).to_json(),
+        Some(serde_json::Value::Null)
+    );
+    assert_eq!(
+        Foreign::<Option<Option<Address>>>(None).to_json(),
+        Some(serde_json::Value::Null)
+    );
+}
@@ -0,0 +1,115 @@
+#![cfg(not(feature = "trace"))]
+
+//! Builds without the `trace` feature, where every schema is given at startup.
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{Foreign, ForeignConfig, Repr, StaticSchema};
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// A vendor id, declared as the uuid string it is written as.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct AccountId(String);
+
+impl StaticSchema for AccountId {}
+
+/// A vendor struct, described by a trace saved from a build with `trace`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Account {
+    id: String,
+    balance: i64,
+    tags: Vec<String>,
+}
+
+impl StaticSchema for Account {}
+
+/// Marked, but given no schema.
+#[derive(Serialize, Deserialize)]
+struct Forgotten {
+    note: String,
+}
+
+impl StaticSchema for Forgotten {}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .declare_repr(
+                Repr::HyphenatedString,
+                AccountId("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string()),
+            )
+            .install();
+        Foreign::<Account>::trace_from_json(
+            json!({ "TYPENAME": "Account" }),
+            json!({
+                "Account": { "STRUCT": [
+                    { "id": "STR" },
+                    { "balance": "I64" },
+                    { "tags": { "SEQ": "STR" } },
+                ] }
+            }),
+        )
+        .unwrap();
+    });
+}
+
+#[test]
+fn declared_reprs_describe_types_without_tracing() {
+    install();
+    let spec = common::spec_for::<Foreign<AccountId>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "uuid" })
+    );
+    common::assert_valid::<Foreign<AccountId>>();
+
+    let spec = common::spec_for::<Foreign<Option<AccountId>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "uuid", "nullable": true })
+    );
+
+    let id = Foreign::<AccountId>::from_json_value(json!("a-1")).unwrap();
+    assert_eq!(id.0, AccountId("a-1".to_string()));
+    assert_eq!(id.to_json(), Some(json!("a-1")));
+}
+
+#[test]
+fn saved_traces_describe_types_without_tracing() {
+    install();
+    let spec = common::spec_for::<Foreign<Vec<Account>>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "array", "items": { "$ref": "#/components/schemas/Account" } })
+    );
+    let account = &spec["components"]["schemas"]["Account"];
+    assert_eq!(account["properties"]["balance"]["format"], "int64");
+    assert_eq!(account["properties"]["tags"]["items"]["type"], "string");
+    common::assert_valid::<Foreign<Account>>();
+
+    let body = json!({ "id": "a-1", "balance": -5, "tags": ["gold"] });
+    let account = Foreign::<Account>::from_json_value(body.clone()).unwrap();
+    assert_eq!(account.0.balance, -5);
+    assert_eq!(account.to_json(), Some(body));
+}
+
+#[test]
+fn saved_traces_must_be_formats() {
+    let err = Foreign::<Forgotten>::trace_from_json(json!({ "TYPENAME": 1 }), json!({}));
+    assert!(err.is_err());
+}
+
+#[test]
+fn marked_types_without_a_schema_are_opaque() {
+    install();
+    let spec = common::spec_for::<Foreign<Forgotten>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Forgotten"],
+        json!({ "type": "object" })
+    );
+}
@@ -0,0 +1,357 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use std::sync::Once;
+
+use jsonwrap::{EnumRepr, Foreign, ForeignConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Shape {
+    Circle { radius: f64 },
+    Rect { width: f64, height: f64 },
+    Empty,
+}
+
+fn samples() -> Vec<Shape> {
+    vec![
+        Shape::Circle { radius: 1.5 },
+        Shape::Rect {
+            width: 2.0,
+            height: 3.0,
+        },
+        Shape::Empty,
+    ]
+}
+
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        ForeignConfig::new()
+            .enum_samples(samples())
+            .enum_samples(messages())
+            .enum_samples(pets())
+            .enum_samples(recipients())
+            .enum_samples(notes())
+            .enum_samples(settings())
+            .enum_samples(payments())
+            .enum_repr::<Note>(EnumRepr::Adjacent {
+                tag: "kind".to_string(),
+                content: "body".to_string(),
+            })
+            .enum_repr::<Pet>(EnumRepr::Internal {
+                tag: "type".to_string(),
+            })
+            .install()
+    });
+}
+
+#[test]
+fn internally_tagged_enums_use_a_discriminated_one_of() {
+    install();
+    let spec = common::spec_for::<Foreign<Shape>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Shape"]["oneOf"],
+        json!([
+            { "$ref": "#/components/schemas/Shape_Circle" },
+            { "$ref": "#/components/schemas/Shape_Empty" },
+            { "$ref": "#/components/schemas/Shape_Rect" },
+        ])
+    );
+    assert_eq!(schemas["Shape"]["discriminator"]["propertyName"], "type");
+    // The variants are objects; the union itself declares no `type` next to its `oneOf`.
+    assert_eq!(schemas["Shape"].get("type"), None);
+    assert_eq!(
+        schemas["Shape"]["discriminator"]["mapping"]["Circle"],
+        "#/components/schemas/Shape_Circle"
+    );
+    assert_eq!(
+        schemas["Shape_Circle"]["properties"]["type"],
+        json!({ "type": "string", "enum": ["Circle"] })
+    );
+    assert_eq!(schemas["Shape_Empty"]["required"], json!(["type"]));
+    common::assert_valid::<Foreign<Shape>>();
+
+    for shape in samples() {
+        let value = serde_json::to_value(shape).unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+    let mislabelled = json!({ "type": "Circle", "width": 1.0, "height": 1.0 });
+    assert!(common::validate_value(&spec, &mislabelled).is_err());
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "t", content = "c")]
+enum Message {
+    Text(String),
+    Move { x: i32, y: i32 },
+    Quit,
+}
+
+fn messages() -> Vec<Message> {
+    vec![
+        Message::Text("hi".to_string()),
+        Message::Move { x: 1, y: -1 },
+        Message::Quit,
+    ]
+}
+
+#[test]
+fn adjacently_tagged_enums_use_a_discriminated_one_of() {
+    install();
+    let spec = common::spec_for::<Foreign<Message>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["Message"]["discriminator"]["propertyName"], "t");
+    assert_eq!(
+        schemas["Message_Move"],
+        json!({
+            "type": "object",
+            "required": ["t", "c"],
+            "properties": {
+                "t": { "type": "string", "enum": ["Move"] },
+                "c": {
+                    "type": "object",
+                    "required": ["x", "y"],
+                    "properties": { "x": { "type": "integer" }, "y": { "type": "integer" } },
+                },
+            },
+        })
+    );
+    assert_eq!(
+        schemas["Message_Quit"]["properties"],
+        json!({ "t": { "type": "string", "enum": ["Quit"] } })
+    );
+    common::assert_valid::<Foreign<Message>>();
+
+    for message in messages() {
+        let value = serde_json::to_value(message).unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+}
+
+/// Every variant carries a distinct `name`, so inference alone would pick it as the tag.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Pet {
+    Cat { name: String, indoor: bool },
+    Dog { name: String },
+}
+
+fn pets() -> Vec<Pet> {
+    vec![
+        Pet::Cat {
+            name: "Tom".to_string(),
+            indoor: true,
+        },
+        Pet::Dog {
+            name: "Rex".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn declared_enum_repr_overrides_inference() {
+    install();
+    let spec = common::spec_for::<Foreign<Pet>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["Pet"]["discriminator"]["propertyName"], "type");
+    assert_eq!(
+        schemas["Pet_Cat"],
+        json!({
+            "type": "object",
+            "required": ["type", "indoor", "name"],
+            "properties": {
+                "type": { "type": "string", "enum": ["Cat"] },
+                "indoor": { "type": "boolean" },
+                "name": { "type": "string" },
+            },
+        })
+    );
+    common::assert_valid::<Foreign<Pet>>();
+
+    for pet in pets() {
+        let value = serde_json::to_value(pet).unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+    let untagged = json!({ "name": "Rex" });
+    assert!(common::validate_value(&spec, &untagged).is_err());
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Recipient {
+    Person { first: String, last: String },
+    Company { name: String, employees: u32 },
+}
+
+fn recipients() -> Vec<Recipient> {
+    vec![
+        Recipient::Person {
+            first: "Ada".to_string(),
+            last: "Lovelace".to_string(),
+        },
+        Recipient::Company {
+            name: "Analytical Engines".to_string(),
+            employees: 2,
+        },
+    ]
+}
+
+#[test]
+fn untagged_enums_are_a_one_of_their_payloads() {
+    install();
+    let spec = common::spec_for::<Foreign<Recipient>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Recipient"],
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["first", "last"],
+                    "properties": { "first": { "type": "string" }, "last": { "type": "string" } },
+                },
+                {
+                    "type": "object",
+                    "required": ["employees", "name"],
+                    "properties": {
+                        "employees": { "type": "integer" },
+                        "name": { "type": "string" },
+                    },
+                },
+            ],
+        })
+    );
+    common::assert_valid::<Foreign<Recipient>>();
+
+    for recipient in recipients() {
+        let value = serde_json::to_value(recipient).unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+}
+
+/// Tag and content are both strings in every sample, which inference cannot tell apart.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "body")]
+enum Note {
+    Text(String),
+    Link(String),
+}
+
+fn notes() -> Vec<Note> {
+    vec![
+        Note::Text("hello".to_string()),
+        Note::Link("https://example.com".to_string()),
+    ]
+}
+
+#[test]
+fn declared_adjacent_tagging_separates_tag_and_content() {
+    install();
+    let spec = common::spec_for::<Foreign<Note>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["Note"]["discriminator"]["propertyName"], "kind");
+    assert_eq!(
+        schemas["Note_Text"],
+        json!({
+            "type": "object",
+            "required": ["kind", "body"],
+            "properties": {
+                "kind": { "type": "string", "enum": ["Text"] },
+                "body": { "type": "string" },
+            },
+        })
+    );
+    common::assert_valid::<Foreign<Note>>();
+
+    for note in notes() {
+        let value = serde_json::to_value(note).unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Setting {
+    Flag(bool),
+    Range { min: i32, max: i32 },
+    Unset,
+}
+
+fn settings() -> Vec<Setting> {
+    vec![
+        Setting::Flag(true),
+        Setting::Range { min: 0, max: 10 },
+        Setting::Unset,
+    ]
+}
+
+#[test]
+fn untagged_unit_variants_make_the_enum_nullable() {
+    install();
+    let spec = common::spec_for::<Foreign<Setting>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Setting"],
+        json!({
+            "nullable": true,
+            "oneOf": [
+                { "type": "boolean" },
+                {
+                    "type": "object",
+                    "required": ["max", "min"],
+                    "properties": { "max": { "type": "integer" }, "min": { "type": "integer" } },
+                },
+            ],
+        })
+    );
+    common::assert_valid::<Foreign<Setting>>();
+
+    for setting in settings() {
+        let value = serde_json::to_value(setting).unwrap();
+        assert_eq!(common::validate_value(&spec, &value), Ok(()), "{value}");
+    }
+    assert!(common::validate_value(&spec, &json!("on")).is_err());
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Payment {
+    CreditCard { last4: String },
+    BankTransfer { iban: String },
+}
+
+fn payments() -> Vec<Payment> {
+    vec![
+        Payment::CreditCard {
+            last4: "4242".to_string(),
+        },
+        Payment::BankTransfer {
+            iban: "DE89".to_string(),
+        },
+    ]
+}
+
+#[test]
+fn discriminators_map_renamed_variants_to_their_components() {
+    install();
+    let spec = common::spec_for::<Foreign<Payment>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Payment"]["discriminator"],
+        json!({
+            "propertyName": "type",
+            "mapping": {
+                "bank_transfer": "#/components/schemas/Payment_bank_transfer",
+                "credit_card": "#/components/schemas/Payment_credit_card",
+            },
+        })
+    );
+    assert_eq!(
+        schemas["Payment_credit_card"]["properties"]["type"],
+        json!({ "type": "string", "enum": ["credit_card"] })
+    );
+    common::assert_valid::<Foreign<Payment>>();
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "trace")]
+
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use jsonwrap::Foreign;
+use poem_openapi::registry::{MetaSchemaRef, Registry};
+use poem_openapi::types::Type;
+use serde::{Deserialize, Deserializer, Serialize};
+
+static DESERIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts how often it is deserialized, which tracing does to every type it walks.
+#[derive(Serialize)]
+struct Counted(u32);
+
+impl<'de> Deserialize<'de> for Counted {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DESERIALIZED.fetch_add(1, Ordering::SeqCst);
+        u32::deserialize(deserializer).map(Counted)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Sensor {
+    id: Counted,
+    label: String,
+}
+
+#[test]
+fn each_type_is_traced_once() {
+    assert_eq!(Foreign::<Sensor>::name(), "Sensor");
+    let traced = DESERIALIZED.load(Ordering::SeqCst);
+    assert!(traced > 0);
+
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    Foreign::<Sensor>::name();
+                    Foreign::<Sensor>::schema_ref();
+                    Foreign::<Sensor>::register(&mut Registry::new());
+                }
+            });
+        }
+    });
+    assert_eq!(DESERIALIZED.load(Ordering::SeqCst), traced);
+}
+
+#[derive(Serialize, Deserialize)]
+struct Label(String);
+
+#[test]
+fn names_and_schema_refs_are_resolved_once() {
+    let name = Foreign::<Label>::name();
+    let schema_ref = Foreign::<Label>::schema_ref();
+    assert_eq!(name, "Label");
+    assert_eq!(schema_ref, MetaSchemaRef::Reference("Label".to_string()));
+    for _ in 0..10_000 {
+        // Borrowed from the cache rather than formatted again.
+        assert!(matches!(Foreign::<Label>::name(), Cow::Borrowed(_)));
+        assert_eq!(Foreign::<Label>::name(), name);
+        assert_eq!(Foreign::<Label>::schema_ref(), schema_ref);
+    }
+    assert!(Foreign::<Label>::describe().was_cached());
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "trace")]
+
+mod common;
+
+use jsonwrap::{Foreign, ForeignConfig, TracerConfig};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+
+/// A timestamp written as text for humans and as seconds otherwise, like many time crates.
+#[derive(Serialize)]
+struct Stamp(u64);
+
+impl<'de> Deserialize<'de> for Stamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            text.parse().map(Stamp).map_err(serde::de::Error::custom)
+        } else {
+            u64::deserialize(deserializer).map(Stamp)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Event {
+    at: Stamp,
+}
+
+#[test]
+fn the_configured_tracer_is_used() {
+    ForeignConfig::new()
+        .tracer_config(|| {
+            TracerConfig::default()
+                .is_human_readable(true)
+                .default_string_value("0".to_string())
+        })
+        .install();
+
+    // The default tracer is not human readable and would see an integer.
+    let spec = common::spec_for::<Foreign<Event>>();
+    assert_eq!(
+        spec["components"]["schemas"]["Event"]["properties"]["at"],
+        json!({ "type": "string" })
+    );
+}
@@ -0,0 +1,96 @@
+#![cfg(all(feature = "trace", feature = "uuid"))]
+
+mod common;
+
+use jsonwrap::Foreign;
+use poem_openapi::types::ToJSON;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+const ID: Uuid = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    id: Uuid,
+    parent: Option<Uuid>,
+}
+
+#[test]
+fn uuids_are_uuid_strings() {
+    let spec = common::spec_for::<Foreign<Uuid>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "type": "string", "format": "uuid" })
+    );
+    assert!(spec["components"]["schemas"]["Uuid"].is_null());
+
+    let spec = common::spec_for::<Foreign<Session>>();
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(
+        schemas["Session"]["properties"]["id"],
+        json!({ "type": "string", "format": "uuid" })
+    );
+    assert_eq!(
+        schemas["Session"]["properties"]["parent"],
+        json!({ "type": "string", "format": "uuid", "nullable": true })
+    );
+    assert!(schemas["Uuid"].is_null());
+    common::assert_valid::<Foreign<Session>>();
+}
+
+#[test]
+fn uuid_fields_round_trip() {
+    let body = json!({ "id": ID.to_string(), "parent": null });
+    let session = Foreign::<Session>::from_json_value(body.clone()).unwrap();
+    assert_eq!(session.0.id, ID);
+    assert_eq!(session.to_json(), Some(body.clone()));
+
+    let spec = common::spec_for::<Foreign<Session>>();
+    assert_eq!(common::validate_value(&spec, &body), Ok(()));
+    assert!(Foreign::<Session>::from_json_value(json!({ "id": "not-a-uuid" })).is_err());
+}
+
+/// Only ever traced from a sample.
+#[derive(Serialize, Deserialize)]
+struct Visit {
+    session: Uuid,
+    guests: Vec<Uuid>,
+}
+
+#[test]
+fn samples_with_uuids_trace() {
+    let visit = Visit {
+        session: ID,
+        guests: vec![ID],
+    };
+    Foreign::<Visit>::trace_sample(&visit).unwrap();
+    let spec = common::spec_for::<Foreign<Visit>>();
+    let properties = &spec["components"]["schemas"]["Visit"]["properties"];
+    assert_eq!(
+        properties["session"],
+        json!({ "type": "string", "format": "uuid" })
+    );
+    assert_eq!(properties["guests"]["items"]["format"], "uuid");
+}
+
+/// Named like uuid's type, but not it.
+mod mine {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Uuid {
+        pub high: u64,
+        pub low: u64,
+    }
+}
+
+#[test]
+fn types_named_like_uuid_keep_their_schema() {
+    let spec = common::spec_for::<Foreign<mine::Uuid>>();
+    assert_eq!(
+        *common::response_schema(&spec),
+        json!({ "$ref": "#/components/schemas/Uuid" })
+    );
+    assert_eq!(spec["components"]["schemas"]["Uuid"]["type"], "object");
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "trace")]
+
+use std::sync::{Barrier, Mutex};
+use std::thread;
+
+use jsonwrap::{Foreign, ForeignOpt};
+use poem_openapi::registry::Registry;
+use poem_openapi::types::Type;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Address {
+    street: String,
+    city: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Customer {
+    name: String,
+    address: Address,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Unit {
+    Piece,
+    Kilogram,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Line {
+    sku: String,
+    quantity: u32,
+    unit: Unit,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Order {
+    customer: Customer,
+    lines: Vec<Line>,
+}
+
+const TYPES: [fn(&mut Registry); 4] = [
+    <Foreign<Customer> as Type>::register,
+    <Foreign<Line> as Type>::register,
+    <Foreign<Order> as Type>::register,
+    <ForeignOpt<Address> as Type>::register,
+];
+
+fn schemas(registry: &Registry) -> serde_json::Value {
+    serde_json::to_value(&registry.schemas).unwrap()
+}
+
+#[test]
+fn a_spec_racing_the_warmup_gets_the_same_schemas() {
+    let start = Barrier::new(2);
+    let progress = Mutex::new(Vec::new());
+    let mut racing = Registry::new();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            start.wait();
+            jsonwrap::warmup_with_progress(&TYPES, |done, total| {
+                progress.lock().unwrap().push((done, total));
+            });
+        });
+        start.wait();
+        for register in TYPES.iter().rev() {
+            register(&mut racing);
+        }
+    });
+    assert_eq!(
+        progress.into_inner().unwrap(),
+        [(1, 4), (2, 4), (3, 4), (4, 4)]
+    );
+
+    // Everything is traced now, so registering again traces nothing.
+    assert!(Foreign::<Customer>::describe().was_cached());
+    assert!(Foreign::<Line>::describe().was_cached());
+    assert!(Foreign::<Order>::describe().was_cached());
+    assert!(Foreign::<Address>::describe().was_cached());
+    let mut warm = Registry::new();
+    for register in TYPES {
+        register(&mut warm);
+    }
+    assert_eq!(schemas(&racing), schemas(&warm));
+    assert_eq!(
+        warm.schemas.keys().map(String::as_str).collect::<Vec<_>>(),
+        [
+            "Address",
+            "AddressNullable",
+            "Customer",
+            "Line",
+            "Order",
+            "Unit"
+        ]
+    );
+}